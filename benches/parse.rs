@@ -0,0 +1,79 @@
+//! Rough timing of the parser on typical command-line shapes.
+//!
+//! Run with `cargo bench`. Uses only `std`, so the numbers are indicative rather than
+//! statistically rigorous.
+
+use argsparse::{ArgAction, ArgDef, ArgSpec, Args, ParserContext};
+use std::hint::black_box;
+use std::time::Instant;
+
+const ITERATIONS: u32 = 200_000;
+
+fn bench(name: &str, mut f: impl FnMut()) {
+    // Warm up caches and branch predictors before timing
+    for _ in 0..ITERATIONS / 10 {
+        f();
+    }
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let per_iter = start.elapsed() / ITERATIONS;
+
+    println!("{:<32} {:>8?}/iter", name, per_iter);
+}
+
+fn main() {
+    let flags = ["-abc", "-def", "-ghi", "-v", "-x", "-yz", "--", "file"];
+    let mixed = ["-v", "--output", "out.txt", "-j", "4", "input.txt", "--dry-run"];
+
+    let mut ctx = ParserContext::new();
+    for (short, long) in [('a', "a"), ('b', "b"), ('c', "c"), ('d', "d"), ('e', "e"), ('f', "f")] {
+        ctx.flag(short, long);
+    }
+    for (short, long) in [('g', "g"), ('h', "h"), ('i', "i"), ('x', "x"), ('y', "y"), ('z', "z")] {
+        ctx.flag(short, long);
+    }
+    ctx.flag('v', "verbose")
+        .option('o', "output")
+        .option('j', "jobs")
+        .flag('n', "dry-run");
+
+    bench("parse_all/flag clusters", || {
+        black_box(Args::parse_all(black_box(&flags)).unwrap());
+    });
+    bench("parse_all/mixed", || {
+        black_box(Args::parse_all(black_box(&mixed)).unwrap());
+    });
+    bench("parse_with_context/flag clusters", || {
+        black_box(Args::parse_with_context(black_box(&flags), &ctx).unwrap());
+    });
+    bench("parse_with_context/mixed", || {
+        black_box(Args::parse_with_context(black_box(&mixed), &ctx).unwrap());
+    });
+
+    // Contexts below and above the size at which lookups switch from scanning to hashing
+    let names: Vec<&'static str> =
+        (0..64).map(|i| &*String::leak(format!("option-{}", i))).collect();
+    let build = |count: usize| {
+        let mut ctx = ParserContext::new();
+        for long in &names[..count] {
+            ctx.register(ArgSpec::new(ArgDef::Long(long)).action(ArgAction::Flag)).unwrap();
+        }
+        ctx
+    };
+    let lookups: Vec<String> = [0, 3, 7].iter().map(|i| format!("--option-{}", i)).collect();
+    let lookups: Vec<&str> = lookups.iter().map(String::as_str).collect();
+
+    for count in [8, 64] {
+        bench(&format!("register/{} definitions", count), || {
+            black_box(build(black_box(count)));
+        });
+
+        let ctx = build(count);
+        bench(&format!("parse_with_context/{} definitions", count), || {
+            black_box(Args::parse_with_context(black_box(&lookups), &ctx).unwrap());
+        });
+    }
+}
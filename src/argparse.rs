@@ -0,0 +1,471 @@
+//! An [`ArgumentParser`] facade in the style of Python's `argparse` module.
+//!
+//! Arguments are declared with [`ArgumentParser::add_argument`], taking the option strings
+//! (`"-o", "--output"`) or a positional name, and configured with keyword-like builder
+//! methods. Parsing returns a [`Namespace`] in which every argument is found under its
+//! destination name, whether or not it was given.
+//!
+//! # Examples
+//!
+//! ```
+//! use argsparse::argparse::{Action, ArgumentParser, Value};
+//!
+//! let mut parser = ArgumentParser::new("prog");
+//! parser.add_argument(["-o", "--output"]).required(true).help("where to write");
+//! parser.add_argument(["-v", "--verbose"]).action(Action::StoreTrue);
+//! parser.add_argument(["--log-level"]).default("info").choices(["debug", "info", "warn"]);
+//! parser.add_argument(["input"]);
+//!
+//! let args = parser.try_parse_args(["--output", "out.txt", "in.txt"]).unwrap();
+//!
+//! assert_eq!(args["output"].as_str(), Some("out.txt"));
+//! assert_eq!(args["verbose"], Value::Bool(false));
+//! assert_eq!(args["log_level"].as_str(), Some("info"));
+//! assert_eq!(args["input"].as_str(), Some("in.txt"));
+//! ```
+
+use crate::defs::{ArgDef, Argument, SpecError};
+use crate::matches::Matches;
+use crate::parser::ParserContext;
+use crate::spec::{ArgAction, ArgSpec, PositionalSpec};
+use std::error::Error;
+use std::fmt;
+use std::ops::Index;
+use std::process;
+
+/// What an argument does when it is encountered, as the `action=` keyword.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Action {
+    /// `"store"`: stores the value, later occurrences overriding earlier ones.
+    Store,
+    /// `"store_true"`: `true` when present.
+    StoreTrue,
+    /// `"store_false"`: `false` when present.
+    StoreFalse,
+    /// `"count"`: counts the occurrences.
+    Count,
+    /// `"append"`: stores every value in a list.
+    Append,
+}
+
+/// How many values a positional consumes, as the `nargs=` keyword.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Nargs {
+    /// Exactly one value (the default).
+    One,
+    /// `"?"`: zero or one value.
+    Optional,
+    /// `"*"`: any number of values, collected in a list.
+    ZeroOrMore,
+    /// `"+"`: at least one value, collected in a list.
+    OneOrMore,
+}
+
+/// The value of an argument in a [`Namespace`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Value {
+    /// The argument was not given and has no default (Python's `None`).
+    None,
+    /// The state of a `store_true` or `store_false` argument.
+    Bool(bool),
+    /// The number of occurrences of a `count` argument.
+    Count(usize),
+    /// A single value.
+    Str(String),
+    /// The values of an `append` argument or a positional taking several values.
+    List(Vec<String>),
+}
+
+/// An argument added with [`ArgumentParser::add_argument`], configured through its builder
+/// methods.
+#[derive(Debug, Clone)]
+pub struct AddArgument {
+    /// The option strings, or the positional name.
+    names: Vec<String>,
+    dest: String,
+    action: Action,
+    nargs: Nargs,
+    default: Option<String>,
+    required: bool,
+    help: Option<String>,
+    metavar: Option<String>,
+    choices: Vec<String>,
+    /// The placeholder listing the choices, e.g. `{a,b}`.
+    choices_metavar: String,
+}
+
+/// A command-line parser in the style of Python's `argparse.ArgumentParser`.
+#[derive(Debug, Clone)]
+pub struct ArgumentParser {
+    prog: String,
+    description: Option<String>,
+    epilog: Option<String>,
+    args: Vec<AddArgument>,
+}
+
+/// The parsed arguments, looked up by destination name.
+///
+/// Indexing with an unknown name panics, as reading a missing attribute raises in Python.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Namespace {
+    values: Vec<(String, Value)>,
+}
+
+/// Why [`ArgumentParser::try_parse_args`] did not return a [`Namespace`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentError {
+    /// `-h` or `--help` was given; holds the help text.
+    Help(String),
+    /// The command line is invalid; holds the usage line and the error message.
+    Invalid(String),
+}
+
+impl AddArgument {
+    /// Sets the `action=` keyword. Defaults to [`Action::Store`].
+    pub fn action(&mut self, action: Action) -> &mut Self {
+        self.action = action;
+        self
+    }
+
+    /// Sets the `nargs=` keyword of a positional. Defaults to [`Nargs::One`].
+    pub fn nargs(&mut self, nargs: Nargs) -> &mut Self {
+        self.nargs = nargs;
+        self
+    }
+
+    /// Sets the `default=` keyword.
+    pub fn default(&mut self, value: impl Into<String>) -> &mut Self {
+        self.default = Some(value.into());
+        self
+    }
+
+    /// Sets the `required=` keyword of an option.
+    pub fn required(&mut self, required: bool) -> &mut Self {
+        self.required = required;
+        self
+    }
+
+    /// Sets the `help=` keyword.
+    pub fn help(&mut self, help: impl Into<String>) -> &mut Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    /// Sets the `metavar=` keyword, the value placeholder in help output.
+    pub fn metavar(&mut self, metavar: impl Into<String>) -> &mut Self {
+        self.metavar = Some(metavar.into());
+        self
+    }
+
+    /// Sets the `dest=` keyword, the name of the argument in the [`Namespace`].
+    pub fn dest(&mut self, dest: impl Into<String>) -> &mut Self {
+        self.dest = dest.into();
+        self
+    }
+
+    /// Sets the `choices=` keyword, the only values the argument accepts.
+    pub fn choices<I>(&mut self, choices: I) -> &mut Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.choices = choices.into_iter().map(Into::into).collect();
+        self.choices_metavar = format!("{{{}}}", self.choices.join(","));
+        self
+    }
+
+    fn is_positional(&self) -> bool {
+        !self.names[0].starts_with('-')
+    }
+
+    fn def(&self) -> Result<ArgDef<'_>, SpecError> {
+        ArgDef::from_names(&self.names.join(", "), self.names.iter().map(String::as_str))
+    }
+
+    /// Returns the names as shown in error messages (e.g., `-o/--output`).
+    fn display_name(&self) -> String {
+        self.names.join("/")
+    }
+}
+
+impl ArgumentParser {
+    /// Creates a parser for the program `prog`, with an automatic `-h`/`--help` flag.
+    pub fn new(prog: impl Into<String>) -> Self {
+        Self { prog: prog.into(), description: None, epilog: None, args: Vec::new() }
+    }
+
+    /// Sets the text shown before the usage line in help output.
+    pub fn description(&mut self, description: impl Into<String>) -> &mut Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the text shown after the arguments in help output.
+    pub fn epilog(&mut self, epilog: impl Into<String>) -> &mut Self {
+        self.epilog = Some(epilog.into());
+        self
+    }
+
+    /// Adds an argument: one or more option strings starting with `-`, or the name of a
+    /// positional.
+    ///
+    /// The destination name is the first long option string, or else the first short one,
+    /// without its dashes and with `-` replaced by `_`. Invalid names are reported when
+    /// parsing.
+    pub fn add_argument<I>(&mut self, names: I) -> &mut AddArgument
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let names: Vec<String> = names.into_iter().map(Into::into).collect();
+        assert!(!names.is_empty(), "add_argument needs at least one name");
+
+        let dest = names
+            .iter()
+            .find(|name| name.starts_with("--"))
+            .unwrap_or(&names[0])
+            .trim_start_matches('-')
+            .replace('-', "_");
+
+        self.args.push(AddArgument {
+            names,
+            dest,
+            action: Action::Store,
+            nargs: Nargs::One,
+            default: None,
+            required: false,
+            help: None,
+            metavar: None,
+            choices: Vec::new(),
+            choices_metavar: String::new(),
+        });
+        self.args.last_mut().unwrap()
+    }
+
+    /// Renders the help text.
+    pub fn format_help(&self) -> String {
+        match self.context() {
+            Ok(ctx) => ctx.render_help(&self.prog),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    /// Parses `args`, which should not include the program name, printing the help and
+    /// exiting with status 0 if it was requested, or printing the error and exiting with
+    /// status 2 if the command line is invalid.
+    pub fn parse_args<I>(&self, args: I) -> Namespace
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        match self.try_parse_args(args) {
+            Ok(namespace) => namespace,
+            Err(ArgumentError::Help(help)) => {
+                print!("{}", help);
+                process::exit(0)
+            }
+            Err(ArgumentError::Invalid(message)) => {
+                eprint!("{}", message);
+                process::exit(2)
+            }
+        }
+    }
+
+    /// Parses `args`, which should not include the program name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use argsparse::argparse::{ArgumentError, ArgumentParser, Nargs};
+    ///
+    /// let mut parser = ArgumentParser::new("prog");
+    /// parser.add_argument(["--mode"]).choices(["fast", "safe"]);
+    /// parser.add_argument(["files"]).nargs(Nargs::OneOrMore);
+    ///
+    /// let err = parser.try_parse_args(["--mode", "slow", "a"]).unwrap_err();
+    /// assert_eq!(err, ArgumentError::Invalid("\
+    /// Usage: prog [OPTIONS] <files>...
+    /// prog: error: argument --mode: invalid choice: 'slow' (choose from 'fast', 'safe')
+    /// ".into()));
+    ///
+    /// assert!(matches!(parser.try_parse_args(["-h"]), Err(ArgumentError::Help(_))));
+    /// ```
+    pub fn try_parse_args<I>(&self, args: I) -> Result<Namespace, ArgumentError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let ctx = self.context().map_err(|err| self.invalid(None, &err.to_string()))?;
+        let args: Vec<String> = args.into_iter().map(|arg| arg.as_ref().to_string()).collect();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+        // Help wins over any other error
+        let help = ArgDef::ShortAndLong { short: 'h', long: "help" };
+        let asked = crate::Args::parse_iter_with_context(args.iter().copied(), &ctx)
+            .map_while(Result::ok)
+            .any(|arg| arg.name().is_some_and(|name| help.matches(name)));
+        if asked {
+            return Err(ArgumentError::Help(ctx.render_help(&self.prog)));
+        }
+
+        let matches = Matches::parse(&args, &ctx)
+            .map_err(|err| self.invalid(Some(&ctx), &ctx.render_error(&err)))?;
+
+        let mut values = Vec::new();
+        for arg in &self.args {
+            let (given, count): (Vec<String>, usize) = if arg.is_positional() {
+                let given: Vec<String> =
+                    matches.positional_values(&arg.dest).into_iter().map(String::from).collect();
+                let count = given.len();
+                (given, count)
+            } else {
+                let def = arg.def().map_err(|err| self.invalid(None, &err.to_string()))?;
+                let given = matches
+                    .occurrences(def)
+                    .filter_map(|arg| match arg {
+                        Argument::Option { value, .. } => Some(value.to_string()),
+                        _ => None,
+                    })
+                    .collect();
+                (given, matches.count(def))
+            };
+
+            let invalid = given
+                .iter()
+                .find(|value| !arg.choices.is_empty() && !arg.choices.contains(value));
+            if let Some(value) = invalid {
+                let choices: Vec<String> =
+                    arg.choices.iter().map(|choice| format!("'{}'", choice)).collect();
+                let message = format!(
+                    "argument {}: invalid choice: '{}' (choose from {})",
+                    arg.display_name(),
+                    value,
+                    choices.join(", ")
+                );
+                return Err(self.invalid(Some(&ctx), &message));
+            }
+
+            let default = || arg.default.clone().map_or(Value::None, Value::Str);
+            let value = match (arg.action, arg.nargs) {
+                (Action::StoreTrue, _) => Value::Bool(count > 0),
+                (Action::StoreFalse, _) => Value::Bool(count == 0),
+                (Action::Count, _) => Value::Count(count),
+                (Action::Append, _) if given.is_empty() => default(),
+                (Action::Append, _) | (_, Nargs::ZeroOrMore | Nargs::OneOrMore) => {
+                    Value::List(given)
+                }
+                (Action::Store, _) => given.last().cloned().map_or_else(default, Value::Str),
+            };
+            values.push((arg.dest.clone(), value));
+        }
+
+        Ok(Namespace { values })
+    }
+
+    /// Builds the context equivalent to the arguments.
+    fn context(&self) -> Result<ParserContext<'_>, SpecError> {
+        let mut ctx = ParserContext::new();
+        if let Some(description) = &self.description {
+            ctx.before_help(description);
+        }
+        if let Some(epilog) = &self.epilog {
+            ctx.after_help(epilog);
+        }
+        ctx.register(ArgSpec::new(ArgDef::ShortAndLong { short: 'h', long: "help" })
+            .action(ArgAction::Flag)
+            .help("show this help message and exit"))
+            .unwrap();
+
+        for arg in &self.args {
+            if arg.is_positional() {
+                let mut spec = PositionalSpec::new(&arg.dest)
+                    .required(matches!(arg.nargs, Nargs::One | Nargs::OneOrMore))
+                    .variadic(matches!(arg.nargs, Nargs::ZeroOrMore | Nargs::OneOrMore));
+                if let Some(help) = &arg.help {
+                    spec = spec.help(help);
+                }
+                ctx.positional(spec);
+                continue;
+            }
+
+            let mut spec = ArgSpec::new(arg.def()?).required(arg.required);
+            spec = match arg.action {
+                Action::Store | Action::Append => spec.action(ArgAction::Value),
+                _ => spec.action(ArgAction::Flag),
+            };
+            if let Some(help) = &arg.help {
+                spec = spec.help(help);
+            }
+            if let Some(metavar) = &arg.metavar {
+                spec = spec.value_name(metavar);
+            } else if !arg.choices.is_empty() {
+                spec = spec.value_name(&arg.choices_metavar);
+            }
+            if let Some(default) = &arg.default {
+                spec = spec.default(default);
+            }
+            if let Err(err) = ctx.register(spec) {
+                panic!("argument {}: {}", arg.display_name(), err);
+            }
+        }
+
+        Ok(ctx)
+    }
+
+    fn invalid(&self, ctx: Option<&ParserContext>, message: &str) -> ArgumentError {
+        let usage = ctx.map(|ctx| format!("{}\n", ctx.usage(&self.prog))).unwrap_or_default();
+        ArgumentError::Invalid(format!("{}{}: error: {}\n", usage, self.prog, message))
+    }
+}
+
+impl Value {
+    /// Returns the single value, if any.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the state of a flag, if the value is one.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the list of values, if any.
+    pub fn as_list(&self) -> Option<&[String]> {
+        match self {
+            Value::List(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl Namespace {
+    /// Returns the value of the argument whose destination is `dest`, if it is defined.
+    pub fn get(&self, dest: &str) -> Option<&Value> {
+        self.values.iter().find(|(name, _)| name == dest).map(|(_, value)| value)
+    }
+}
+
+impl Index<&str> for Namespace {
+    type Output = Value;
+
+    fn index(&self, dest: &str) -> &Value {
+        self.get(dest)
+            .unwrap_or_else(|| panic!("'Namespace' object has no attribute '{}'", dest))
+    }
+}
+
+impl fmt::Display for ArgumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgumentError::Help(text) | ArgumentError::Invalid(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+impl Error for ArgumentError {}
@@ -1,23 +1,100 @@
-use crate::types::FromArgument;
+use crate::types::{self, FromArgument, TriState, TryFromArgument};
 use crate::{defs::*, parser};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
 use std::ops::Deref;
-use crate::parser::ParserContext;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use crate::matches::Matches;
+use crate::parser::{ParseIter, ParserContext};
 
 /// A parsed list of command-line arguments.
 ///
 /// The `Args` struct wraps a `Vec<Argument<'a>>` and provides convenience
 /// methods for querying and extracting data from parsed command-line arguments.
 ///
-/// You typically obtain an `Args` instance by calling [`Args::parse_all()`].
+/// You typically obtain an `Args` instance by calling [`Args::parse_all()`], or build one
+/// from a list of arguments with [`From`]. The list is read through [`Deref`], and taken
+/// back with [`From`] as well.
 ///
 /// # Examples
 ///
 /// ```
-/// # use argsparse::Args;
+/// # use argsparse::{ArgName, Args, Argument};
 /// let args = Args::parse_all(&["--flag", "-o", "value"]).unwrap();
+///
+/// let mut list = Vec::from(args);
+/// list.push(Argument::Flag { name: ArgName::Short('v') });
+/// let args = Args::from(list);
+/// assert_eq!(args.len(), 3);
+///
+/// // The list can also be reached directly
+/// let mut args = Args::parse_all(&["-v"]).unwrap();
+/// args.0.push(Argument::Positional { value: "input.txt".into() });
+/// assert_eq!(args.0.len(), 2);
 /// ```
-#[derive(Debug)]
-pub struct Args<'a>(pub Vec<Argument<'a>>);
+#[derive(Default)]
+pub struct Args<'a>(
+    /// The parsed arguments, in command-line order.
+    ///
+    /// The position of the `--` separator is not adjusted when the list is changed directly.
+    pub Vec<Argument<'a>>,
+    /// The position of the first argument after the `--` separator, if one was seen.
+    Option<usize>,
+    /// The names given to [`sensitive`] options, whose values are redacted when shown.
+    ///
+    /// [`sensitive`]: crate::ArgSpec::sensitive
+    Vec<ArgName<'static>>,
+);
+
+/// What the values of [`sensitive`] arguments are shown as.
+///
+/// [`sensitive`]: crate::ArgSpec::sensitive
+pub(crate) const REDACTED: &str = "<redacted>";
+
+
+/// How [`Args::merge`] resolves a flag or option given both in the base arguments and in
+/// the ones merged into them.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum MergePolicy {
+    /// Keeps every occurrence, the merged ones last, so they win wherever the last value
+    /// does, as in [`Matches::value_of`].
+    ///
+    /// [`Matches::value_of`]: crate::Matches::value_of
+    #[default]
+    Append,
+    /// Drops the occurrences from the base arguments in favor of the merged ones.
+    Override,
+    /// Drops the merged occurrences in favor of the ones from the base arguments.
+    Keep,
+}
+
+
+/// Wraps a list of arguments, with no `--` separator recorded.
+impl<'a> From<Vec<Argument<'a>>> for Args<'a> {
+    fn from(args: Vec<Argument<'a>>) -> Self {
+        Args(args, None, Vec::new())
+    }
+}
+
+
+/// Shows the arguments with the values of [`sensitive`] ones redacted.
+///
+/// [`sensitive`]: crate::ArgSpec::sensitive
+impl fmt::Debug for Args<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args: Vec<_> = self.0.iter().map(|arg| redacted(arg, &self.2)).collect();
+        f.debug_tuple("Args").field(&args).field(&self.1).finish()
+    }
+}
+
+/// Unwraps the list of arguments, dropping the position of the `--` separator.
+impl<'a> From<Args<'a>> for Vec<Argument<'a>> {
+    fn from(args: Args<'a>) -> Self {
+        args.0
+    }
+}
 
 
 /// Allows read-only access to the underlying `Vec<Argument<'a>>` using deref.
@@ -106,7 +183,7 @@ impl<'a> Args<'a> {
     /// # Type Parameters
     ///
     /// * `T` - A type that implements [`FromArgument`], which defines how to extract the desired
-    ///         data from the raw [`Argument`] enum.
+    ///   data from the raw [`Argument`] enum.
     ///
     /// # Examples
     ///
@@ -116,7 +193,7 @@ impl<'a> Args<'a> {
     ///
     /// let options = args.find_all::<OptionArg>();
     /// let expected = OptionArg {
-    ///     name: &ArgName::Long("some"),
+    ///     name: &ArgName::Long("some".into()),
     ///     value: "args"
     /// };
     ///
@@ -158,6 +235,37 @@ impl<'a> Args<'a> {
     }
 
 
+    /// Returns an iterator over the flags and options matching the given [`ArgDef`], in
+    /// command-line order.
+    ///
+    /// Unlike [`iter_all`], which filters by kind only, this yields every occurrence of one
+    /// argument, whatever its kind, for streaming processing of repeated options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Args, Argument};
+    /// let args = Args::parse_all(&["-D", "debug", "--other", "x", "--define=fast"]).unwrap();
+    /// let define = ArgDef::ShortAndLong { short: 'D', long: "define" };
+    ///
+    /// let values: Vec<_> = args.iter_matching(define).filter_map(|arg| match arg {
+    ///     Argument::Option { value, .. } => Some(value.as_ref()),
+    ///     _ => None,
+    /// }).collect();
+    /// assert_eq!(values, ["debug", "fast"]);
+    /// ```
+    ///
+    /// [`ArgDef`]: crate::ArgDef
+    /// [`iter_all`]: Args::iter_all
+    pub fn iter_matching<'s>(&'s self, def: ArgDef) -> impl Iterator<Item = &'s Argument<'a>> {
+        self.iter()
+            .filter(move |&arg| match arg {
+                Argument::Flag { name } | Argument::Option { name, .. } => def.matches(name),
+                _ => false,
+            })
+    }
+
+
     /// Finds a single argument matching the given [`ArgDef`], and parses it into type `T`.
     ///
     /// If an argument with a matching name is found and can be parsed into type `T`
@@ -185,19 +293,137 @@ impl<'a> Args<'a> {
     /// [`ArgDef`]: crate::ArgDef
     /// [`FromArgument`]: crate::FromArgument
     pub fn find<T: FromArgument<'a>>(&'a self, def: ArgDef) -> Option<T> {
-        self.iter()
-            .find(|&arg| match arg {
-                Argument::Flag { name } | Argument::Option { name, .. } => def.matches(name),
-                _ => false,
-            })
+        self.iter_matching(def)
+            .next()
             .and_then(|arg| T::from_argument(arg))
     }
 
 
+    /// Finds the last argument matching the given [`ArgDef`], and parses it into type `T`.
+    ///
+    /// This gives override semantics, where a later occurrence of an option replaces the
+    /// earlier ones (e.g., an alias expanding to `--color=auto` followed by `--color=never`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, ArgDef, OptionArg};
+    /// let args = Args::parse_all(&["--color=auto", "--color=never"]).unwrap();
+    ///
+    /// let opt: OptionArg = args.find_last(ArgDef::Long("color")).unwrap();
+    /// assert_eq!(opt.value, "never");
+    /// ```
+    ///
+    /// [`ArgDef`]: crate::ArgDef
+    pub fn find_last<T: FromArgument<'a>>(&'a self, def: ArgDef) -> Option<T> {
+        self.iter_matching(def)
+            .last()
+            .and_then(|arg| T::from_argument(arg))
+    }
+
+
+    /// Finds every argument matching the given [`ArgDef`] that can be parsed into type `T`,
+    /// in command-line order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, ArgDef, OptionArg};
+    /// let args = Args::parse_all(&["-I", "src", "--verbose", "--include", "lib"]).unwrap();
+    /// let include = ArgDef::ShortAndLong { short: 'I', long: "include" };
+    ///
+    /// let dirs: Vec<&str> = args.find_all_for::<OptionArg>(include)
+    ///     .iter()
+    ///     .map(|opt| opt.value)
+    ///     .collect();
+    /// assert_eq!(dirs, ["src", "lib"]);
+    /// ```
+    ///
+    /// [`ArgDef`]: crate::ArgDef
+    pub fn find_all_for<T: FromArgument<'a>>(&'a self, def: ArgDef) -> Vec<T> {
+        self.iter_matching(def)
+            .filter_map(|arg| T::from_argument(arg))
+            .collect()
+    }
+
+
+    /// Finds the first argument matching the given [`ArgDef`] like [`find`], converting it
+    /// with [`TryFromArgument`] so that a value that cannot be converted is reported.
+    ///
+    /// Returns `Ok(None)` if no argument matches or the first one is of another kind. See
+    /// [`Parsed`] for an example.
+    ///
+    /// [`ArgDef`]: crate::ArgDef
+    /// [`find`]: Args::find
+    /// [`TryFromArgument`]: crate::TryFromArgument
+    /// [`Parsed`]: crate::Parsed
+    pub fn try_find<T>(&'a self, def: ArgDef) -> Result<Option<T>, ValueError>
+    where
+        T: TryFromArgument<'a>,
+    {
+        self.iter_matching(def).next().map_or(Ok(None), T::try_from_argument)
+    }
+
+
+    /// Finds the last argument matching the given [`ArgDef`] like [`find_last`], converting
+    /// it with [`TryFromArgument`] so that a value that cannot be converted is reported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, ArgDef, Parsed};
+    /// let args = Args::parse_all(&["-j", "2", "-j", "eight"]).unwrap();
+    ///
+    /// let err = args.try_find_last::<Parsed<u32>>(ArgDef::Short('j')).unwrap_err();
+    /// assert_eq!(err.value, "eight");
+    /// assert_eq!(err.expected, "u32");
+    /// ```
+    ///
+    /// [`ArgDef`]: crate::ArgDef
+    /// [`find_last`]: Args::find_last
+    /// [`TryFromArgument`]: crate::TryFromArgument
+    pub fn try_find_last<T>(&'a self, def: ArgDef) -> Result<Option<T>, ValueError>
+    where
+        T: TryFromArgument<'a>,
+    {
+        self.iter_matching(def).last().map_or(Ok(None), T::try_from_argument)
+    }
+
+
+    /// Finds every argument matching the given [`ArgDef`] like [`find_all_for`], converting
+    /// them with [`TryFromArgument`] and stopping at the first value that cannot be
+    /// converted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, ArgDef, Parsed};
+    /// let args = Args::parse_all(&["--retry", "1", "--retry", "5"]).unwrap();
+    ///
+    /// let delays = args.try_find_all_for::<Parsed<u64>>(ArgDef::Long("retry")).unwrap();
+    /// assert_eq!(delays, [Parsed(1), Parsed(5)]);
+    /// ```
+    ///
+    /// [`ArgDef`]: crate::ArgDef
+    /// [`find_all_for`]: Args::find_all_for
+    /// [`TryFromArgument`]: crate::TryFromArgument
+    pub fn try_find_all_for<T>(&'a self, def: ArgDef) -> Result<Vec<T>, ValueError>
+    where
+        T: TryFromArgument<'a>,
+    {
+        self.iter_matching(def)
+            .filter_map(|arg| T::try_from_argument(arg).transpose())
+            .collect()
+    }
+
+
     /// Checks if an argument matching the given [`ArgDef`] is present.
     ///
     /// Returns `true` if any flag or option matches the definition,
     ///
+    /// The values of flags are not interpreted, so `--follow=false` counts as present; use
+    /// [`flag`] or [`Matches::has`] for the state of a flag given an explicit value.
+    ///
     /// # Arguments
     ///
     /// * `def` - The definition of the argument to look for.
@@ -213,12 +439,495 @@ impl<'a> Args<'a> {
     /// ```
     ///
     /// [`ArgDef`]: crate::ArgDef
+    /// [`flag`]: Args::flag
+    /// [`Matches::has`]: crate::Matches::has
     pub fn has(&self, def: ArgDef) -> bool {
+        self.iter_matching(def).next().is_some()
+    }
+
+
+    /// Checks if an argument matching any of the given [`ArgDef`]s is present.
+    ///
+    /// Returns `false` if `defs` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, ArgDef};
+    /// let formats = [ArgDef::Long("json"), ArgDef::Long("yaml"), ArgDef::Long("toml")];
+    ///
+    /// assert!(Args::parse_all(&["--yaml"]).unwrap().has_any(&formats));
+    /// assert!(!Args::parse_all(&["--verbose"]).unwrap().has_any(&formats));
+    /// ```
+    ///
+    /// [`ArgDef`]: crate::ArgDef
+    pub fn has_any(&self, defs: &[ArgDef]) -> bool {
+        defs.iter().any(|&def| self.has(def))
+    }
+
+
+    /// Checks if arguments matching all of the given [`ArgDef`]s are present.
+    ///
+    /// Returns `true` if `defs` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, ArgDef};
+    /// let login = [ArgDef::Long("user"), ArgDef::Long("password")];
+    ///
+    /// assert!(Args::parse_all(&["--user", "me", "--password", "secret"]).unwrap().has_all(&login));
+    /// assert!(!Args::parse_all(&["--user", "me"]).unwrap().has_all(&login));
+    /// ```
+    ///
+    /// [`ArgDef`]: crate::ArgDef
+    pub fn has_all(&self, defs: &[ArgDef]) -> bool {
+        defs.iter().all(|&def| self.has(def))
+    }
+
+
+    /// Returns the positional at `index` (counting positionals only), converted to type `T`.
+    ///
+    /// Returns `Ok(None)` if there are not that many positionals, and a [`ValueError`]
+    /// naming the position, the raw value and the expected type if the conversion fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::Args;
+    /// let args = Args::parse_all(&["--verbose", "--", "8080", "eight"]).unwrap();
+    ///
+    /// assert_eq!(args.positional::<u16>(0).unwrap(), Some(8080));
+    /// assert_eq!(args.positional::<u16>(2).unwrap(), None);
+    ///
+    /// let err = args.positional::<u16>(1).unwrap_err();
+    /// assert_eq!(err.value, "eight");
+    /// assert_eq!(err.expected, "u16");
+    /// ```
+    ///
+    /// [`ValueError`]: crate::ValueError
+    pub fn positional<T>(&self, index: usize) -> Result<Option<T>, ValueError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
         self.iter()
-            .any(|arg| match arg {
-                Argument::Flag { name } | Argument::Option { name, .. } => def.matches(name),
-                _ => false,
+            .filter_map(|arg| match arg {
+                Argument::Positional { value } => Some(value),
+                _ => None,
             })
+            .nth(index)
+            .map(|value| ValueError::parse(format!("positional #{}", index), value))
+            .transpose()
+    }
+
+
+    /// Returns the state of the flag `def`, as given last on the command line.
+    ///
+    /// The flag alone is `true`, and an explicit value such as `--follow=false` is parsed
+    /// as a `bool`. Returns `false` when the flag is absent. Unlike [`has`], which only
+    /// looks for the flag, this interprets its value, as [`Matches::has`] does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Args, ParserContext};
+    /// let follow = ArgDef::ShortAndLong { short: 'f', long: "follow" };
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('f', "follow");
+    ///
+    /// let flag = |tokens: &[&str]| Args::parse_with_context(tokens, &ctx).unwrap().flag(follow);
+    /// assert!(flag(&["--follow"]).unwrap());
+    /// assert!(!flag(&["--follow=false"]).unwrap());
+    /// assert!(flag(&["--follow=false", "-f"]).unwrap());
+    /// assert!(!flag(&[]).unwrap());
+    ///
+    /// let args = Args::parse_with_context(&["--follow=false"], &ctx).unwrap();
+    /// assert!(args.has(follow) && !args.flag(follow).unwrap());
+    ///
+    /// let err = Args::parse_with_context(&["--follow=maybe"], &ctx).unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "invalid value 'maybe' for --follow [possible values: true, false]"
+    /// );
+    /// ```
+    ///
+    /// [`has`]: Args::has
+    /// [`Matches::has`]: crate::Matches::has
+    pub fn flag(&self, def: ArgDef) -> Result<bool, ValueError> {
+        let arg = match def {
+            ArgDef::Short(short) => format!("-{}", short),
+            ArgDef::Long(long) | ArgDef::ShortAndLong { long, .. } => format!("--{}", long),
+        };
+        match self.iter_matching(def).last() {
+            Some(Argument::Option { value, .. }) => ValueError::parse(arg, value),
+            Some(_) => Ok(true),
+            None => Ok(false),
+        }
+    }
+
+
+    /// Returns the state of the tri-state flag `def`, as given last on the command line.
+    ///
+    /// The flag alone is [`TriState::Always`], a value is parsed as a [`TriState`], and
+    /// [`TriState::Auto`] is returned when the flag is absent. See
+    /// [`ParserContext::tri_state`] for an example.
+    ///
+    /// [`TriState`]: crate::TriState
+    /// [`TriState::Always`]: crate::TriState::Always
+    /// [`TriState::Auto`]: crate::TriState::Auto
+    /// [`ParserContext::tri_state`]: crate::ParserContext::tri_state
+    pub fn tri_state(&self, def: ArgDef) -> Result<TriState, ValueError> {
+        let arg = match def {
+            ArgDef::Short(short) => format!("-{}", short),
+            ArgDef::Long(long) | ArgDef::ShortAndLong { long, .. } => format!("--{}", long),
+        };
+        match self.iter_matching(def).last() {
+            Some(Argument::Option { value, .. }) => ValueError::parse(arg, value),
+            Some(_) => Ok(TriState::Always),
+            None => Ok(TriState::Auto),
+        }
+    }
+
+
+    /// Groups the values of the flags and options by the name they were given under.
+    ///
+    /// Flags map to an empty list, and options to their values in command-line order.
+    /// Positionals are left out. This suits quick scripts and exporting the result to a
+    /// templating engine; names are kept as typed, so `-v` and `--verbose` are separate keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgName, Args};
+    /// let args = Args::parse_all(&["-I", "src", "-I", "lib", "--release", "--", "x"]).unwrap();
+    /// let map = args.to_map();
+    ///
+    /// assert_eq!(map[&ArgName::Short('I')], ["src", "lib"]);
+    /// assert!(map[&ArgName::Long("release".into())].is_empty());
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn to_map(&self) -> HashMap<ArgName<'a>, Vec<&str>> {
+        let mut map: HashMap<_, Vec<_>> = HashMap::new();
+        for arg in self.iter() {
+            match arg {
+                Argument::Flag { name } => {
+                    map.entry(name.clone()).or_default();
+                }
+                Argument::Option { name, value } => {
+                    map.entry(name.clone()).or_default().push(value.as_ref());
+                }
+                Argument::Positional { .. } => {}
+            }
+        }
+        map
+    }
+
+
+    /// Returns exactly the arguments that appeared after the `--` separator.
+    ///
+    /// These are positionals like any other for [`find_all`], but are kept apart here so
+    /// wrapper tools can forward them untouched to a child process. Returns an empty slice
+    /// if there was no separator. A context may use a different separator, see
+    /// [`ParserContext::set_terminator`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, Argument};
+    /// let args = Args::parse_all(&["build", "--release", "--", "--nocapture", "x"]).unwrap();
+    /// let forwarded: Vec<_> = args.trailing().iter().map(|arg| match arg {
+    ///     Argument::Positional { value } => value.as_ref(),
+    ///     _ => unreachable!(),
+    /// }).collect();
+    ///
+    /// assert_eq!(forwarded, ["--nocapture", "x"]);
+    /// assert!(Args::parse_all(&["build"]).unwrap().trailing().is_empty());
+    /// ```
+    ///
+    /// [`find_all`]: Args::find_all
+    /// [`ParserContext::set_terminator`]: crate::ParserContext::set_terminator
+    pub fn trailing(&self) -> &[Argument<'a>] {
+        self.1.and_then(|start| self.0.get(start..)).unwrap_or(&[])
+    }
+
+
+    /// Renders the arguments as stable, line-oriented text, for snapshot tests.
+    ///
+    /// Each argument takes one line: its kind, its name and value, and in brackets where it
+    /// came from, `cli` or `trailing` for arguments after the `--` separator. Values are
+    /// quoted and escaped. Unlike the [`Debug`] output, this format is part of the API and
+    /// only changes in a major release.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::Args;
+    /// let args = Args::parse_all(&["-v", "--output", "out.txt", "in.txt", "--", "-x"]).unwrap();
+    ///
+    /// assert_eq!(args.snapshot(), "\
+    /// flag -v [cli]
+    /// option --output=\"out.txt\" [cli]
+    /// positional \"in.txt\" [cli]
+    /// positional \"-x\" [trailing]
+    /// ");
+    /// ```
+    pub fn snapshot(&self) -> String {
+        let mut out = String::new();
+        for (index, arg) in self.0.iter().enumerate() {
+            let trailing = self.1.is_some_and(|start| index >= start);
+            let arg = redacted(arg, &self.2);
+            snapshot_line(&mut out, &arg, if trailing { "trailing" } else { "cli" });
+        }
+        out
+    }
+
+
+    /// Returns a command line equivalent to these arguments, in their original order.
+    ///
+    /// Flags and options keep the name they were given, and values are attached with `=`.
+    /// The arguments after the `--` separator follow one. Quote the tokens for a shell with
+    /// the [`shell_quote`] module; see [`canonicalize`] for a normalized command line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::Args;
+    /// let args = Args::parse_all(&["-o", "out.txt", "build", "--jobs", "4", "--", "-x"]).unwrap();
+    ///
+    /// assert_eq!(args.to_tokens(), ["-o=out.txt", "build", "--jobs=4", "--", "-x"]);
+    /// ```
+    ///
+    /// [`shell_quote`]: crate::shell_quote
+    /// [`canonicalize`]: Args::canonicalize
+    pub fn to_tokens(&self) -> Vec<String> {
+        let mut tokens = Vec::new();
+        for (index, arg) in self.0.iter().enumerate() {
+            if self.1 == Some(index) {
+                tokens.push("--".to_string());
+            }
+            tokens.push(match arg {
+                Argument::Flag { name } => types::display_name(name),
+                Argument::Option { name, value } => {
+                    format!("{}={}", types::display_name(name), value)
+                }
+                Argument::Positional { value } => value.to_string(),
+            });
+        }
+        if self.1 == Some(self.0.len()) {
+            tokens.push("--".to_string());
+        }
+        tokens
+    }
+
+
+    /// Returns a normalized command line equivalent to these arguments, for cache keys,
+    /// reproducible logs or spotting equivalent invocations.
+    ///
+    /// Flags and options come first, sorted by name, each in its own token: short names are
+    /// replaced by the long name of their definition in `ctx` when it has one, and values are
+    /// always attached with `=`. Repeated options keep their relative order, since it can
+    /// matter. Operands follow in their original order, after a `--` separator when one of
+    /// them starts with a dash or some came after the separator. Arguments `ctx` does not
+    /// define keep the name they were given.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose");
+    /// ctx.option('o', "output");
+    ///
+    /// let args = Args::parse_with_context(&["in.txt", "-o", "out.txt", "-v"], &ctx).unwrap();
+    /// let same = Args::parse_with_context(&["--verbose", "--output=out.txt", "in.txt"], &ctx)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(args.canonicalize(&ctx), ["--output=out.txt", "--verbose", "in.txt"]);
+    /// assert_eq!(args.canonicalize(&ctx), same.canonicalize(&ctx));
+    /// ```
+    pub fn canonicalize(&self, ctx: &ParserContext) -> Vec<String> {
+        let canonical = |name: &ArgName| match name {
+            ArgName::Short(_) => match ctx.spec_of(name).and_then(|spec| spec.def.long()) {
+                Some(long) => types::display_name(&ArgName::Long(long.into())),
+                None => types::display_name(name),
+            },
+            ArgName::Long(_) => types::display_name(name),
+        };
+
+        let mut options = Vec::new();
+        let mut operands = Vec::new();
+        for arg in self.iter() {
+            match arg {
+                Argument::Flag { name } => options.push((canonical(name), None)),
+                Argument::Option { name, value } => options.push((canonical(name), Some(value))),
+                Argument::Positional { value } => operands.push(value.to_string()),
+            }
+        }
+        // A stable sort, so repeated options stay in order
+        options.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut argv: Vec<String> = options.into_iter()
+            .map(|(name, value)| match value {
+                Some(value) => format!("{}={}", name, value),
+                None => name,
+            })
+            .collect();
+        if self.1.is_some() || operands.iter().any(|operand| operand.starts_with('-')) {
+            argv.push("--".to_string());
+        }
+        argv.extend(operands);
+        argv
+    }
+
+
+    /// Merges `other` into these arguments, for extending an invocation with tokens from
+    /// another source, such as a profile, after the fact.
+    ///
+    /// Flags and options given on both sides are resolved according to `policy`, looking up
+    /// their definitions in `ctx` so that short and long names of the same definition
+    /// collide. Arguments `ctx` does not define collide by name. The merged positionals
+    /// follow the base ones, and so do the arguments after the `--` separator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Args, MergePolicy, OptionArg, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose").option('o', "output");
+    ///
+    /// let base = Args::parse_with_context(&["-o", "out.txt", "in.txt"], &ctx).unwrap();
+    /// let profile = Args::parse_with_context(&["--output", "profile.txt", "-v"], &ctx).unwrap();
+    ///
+    /// let mut args = base;
+    /// args.merge(profile, &ctx, MergePolicy::Keep);
+    ///
+    /// let output: Vec<OptionArg> =
+    ///     args.find_all_for(ArgDef::ShortAndLong { short: 'o', long: "output" });
+    /// assert_eq!(output.len(), 1);
+    /// assert_eq!(output[0].value, "out.txt");
+    /// assert!(args.has(ArgDef::Short('v')));
+    /// ```
+    pub fn merge(&mut self, other: Args<'a>, ctx: &ParserContext, policy: MergePolicy) {
+        let same = |a: &ArgName, b: &ArgName| match (ctx.id_of(a), ctx.id_of(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => a == b,
+        };
+        let given = |args: &[Argument], name: &ArgName| {
+            args.iter().any(|arg| arg.name().is_some_and(|other| same(other, name)))
+        };
+
+        let split = |args: Args<'a>| {
+            let mut list = args.0;
+            let trailing = list.split_off(args.1.map_or(list.len(), |start| start.min(list.len())));
+            (list, trailing, args.1.is_some())
+        };
+        let redacted = std::mem::take(&mut self.2);
+        let (mut base, base_trailing, base_separated) = split(std::mem::take(self));
+        let (mut merged, merged_trailing, merged_separated) = split(other);
+
+        match policy {
+            MergePolicy::Append => {}
+            MergePolicy::Override => {
+                base.retain(|arg| arg.name().is_none_or(|name| !given(&merged, name)));
+            }
+            MergePolicy::Keep => {
+                merged.retain(|arg| arg.name().is_none_or(|name| !given(&base, name)));
+            }
+        }
+
+        base.append(&mut merged);
+        let separated = base_separated || merged_separated;
+        self.1 = separated.then_some(base.len());
+        base.extend(base_trailing);
+        base.extend(merged_trailing);
+        self.0 = base;
+        self.2 = redacted;
+        self.redact(ctx);
+    }
+
+
+    /// Records the names of the options defined [`sensitive`] in `ctx`, or in one of its
+    /// nested contexts, to redact their values when shown.
+    ///
+    /// [`sensitive`]: crate::ArgSpec::sensitive
+    pub(crate) fn redact(&mut self, ctx: &ParserContext) {
+        let names: Vec<_> = self.0.iter()
+            .filter_map(|arg| match arg {
+                Argument::Option { name, .. } if ctx.is_sensitive(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+        for name in names {
+            self.redact_name(&name);
+        }
+    }
+
+
+    /// Records `name` as given to a [`sensitive`] option, to redact its values when shown.
+    ///
+    /// [`sensitive`]: crate::ArgSpec::sensitive
+    pub(crate) fn redact_name(&mut self, name: &ArgName) {
+        if !self.2.contains(name) {
+            self.2.push(name.clone().into_owned());
+        }
+    }
+
+
+    /// Appends an argument.
+    pub(crate) fn push(&mut self, arg: Argument<'a>) {
+        self.0.push(arg);
+    }
+
+
+    /// Records that the arguments from position `start` on came after the `--` separator.
+    pub(crate) fn set_trailing(&mut self, start: Option<usize>) {
+        self.1 = start;
+    }
+
+
+    /// Returns the arguments that belong to the namespace `prefix`, with the prefix removed.
+    ///
+    /// Only long flags and options named `--<prefix>-<name>` are kept, and each is renamed
+    /// to `--<name>`. This is the view a plugin nested with [`ParserContext::nest`] should
+    /// query, so it never has to know the prefix the host chose for it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Args};
+    /// let args = Args::parse_all(&["--debug", "--fmt-width", "80", "input.txt"]).unwrap();
+    /// let fmt = args.scope("fmt");
+    ///
+    /// assert_eq!(fmt.len(), 1);
+    /// assert!(fmt.has(ArgDef::Long("width")));
+    /// ```
+    ///
+    /// [`ParserContext::nest`]: crate::ParserContext::nest
+    pub fn scope(&self, prefix: &str) -> Args<'a> {
+        let strip = |name: &Cow<'a, str>| -> Option<Cow<'a, str>> {
+            let rest = name.strip_prefix(prefix)?.strip_prefix('-')?;
+            Some(match name {
+                // Keep borrowing from the command line when possible
+                Cow::Borrowed(name) => Cow::Borrowed(&name[name.len() - rest.len()..]),
+                Cow::Owned(_) => Cow::Owned(rest.to_string()),
+            })
+        };
+
+        Args::from(self.iter()
+            .filter_map(|arg| match arg {
+                Argument::Flag { name: ArgName::Long(name) } => {
+                    strip(name).map(|name| Argument::Flag { name: ArgName::Long(name) })
+                }
+                Argument::Option { name: ArgName::Long(name), value } => {
+                    strip(name).map(|name| Argument::Option {
+                        name: ArgName::Long(name),
+                        value: value.clone(),
+                    })
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>())
     }
 
 
@@ -232,6 +941,29 @@ impl<'a> Args<'a> {
     ///
     /// * `args` - A slice of command-line argument strings.
     ///
+    /// # Values given with `=`
+    ///
+    /// In `--name=value` and `-n=value`, everything after the first `=` is the value, taken
+    /// verbatim: it may start with dashes, contain further `=` signs, or be empty, and it is
+    /// never mistaken for another argument. The token that follows is left untouched.
+    ///
+    /// This is also how to pass the `--` separator itself as a value, as in `--separator=--`.
+    /// Options defined with [`ArgAction::Value`] accept it as a separate token too.
+    ///
+    /// ```
+    /// # use argsparse::{ArgName, Args, Argument};
+    /// let args = Args::parse_all(&["--pattern=-x*", "--env=A=1", "-p=-x", "input.txt"]).unwrap();
+    ///
+    /// assert_eq!(args[0], Argument::Option { name: ArgName::Long("pattern".into()), value: "-x*".into() });
+    /// assert_eq!(args[1], Argument::Option { name: ArgName::Long("env".into()), value: "A=1".into() });
+    /// assert_eq!(args[2], Argument::Option { name: ArgName::Short('p'), value: "-x".into() });
+    /// assert_eq!(args[3], Argument::Positional { value: "input.txt".into() });
+    ///
+    /// let args = Args::parse_all(&["--separator=--", "--", "-v"]).unwrap();
+    /// assert_eq!(args[0], Argument::Option { name: ArgName::Long("separator".into()), value: "--".into() });
+    /// assert_eq!(args[1], Argument::Positional { value: "-v".into() });
+    /// ```
+    ///
     /// # Examples
     ///
     /// ```
@@ -241,6 +973,7 @@ impl<'a> Args<'a> {
     ///
     /// [`Args`]: crate::Args
     /// [`ParseArgError`]: crate::ParseArgError
+    /// [`ArgAction::Value`]: crate::ArgAction::Value
     pub fn parse_all(args: &'a [&str]) -> Result<Args<'a>, ParseArgError<'a>> {
         parser::parse(args)
     }
@@ -273,7 +1006,230 @@ impl<'a> Args<'a> {
     /// args.unwrap().find::<FlagArg>(def).expect("Something went wrong");
     /// ```
     pub fn parse_with_context(args: &'a [&str], ctx: &ParserContext) -> Result<Args<'a>, ParseArgError<'a>> {
-        parser::parse_with_ctx(args, ctx)
+        parser::parse_with_ctx(args, ctx).map(Matches::into_args)
+    }
+
+    /// Parses any sequence of string-like command-line arguments.
+    ///
+    /// Unlike [`parse_all`], the input does not need to be a slice of `&str`: owned strings
+    /// such as `Vec<String>` or [`std::env::args`] work directly. The returned [`Args`] owns
+    /// its data, so it is not tied to the lifetime of the input.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - Any iterator of string-like command-line arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Args};
+    /// let argv: Vec<String> = vec!["--debug".into(), "input.txt".into()];
+    /// let args = Args::parse_from(argv).unwrap();
+    ///
+    /// assert!(args.has(ArgDef::Long("debug")));
+    ///
+    /// // Skip the program name when parsing the real command line
+    /// let args = Args::parse_from(std::env::args().skip(1));
+    /// ```
+    ///
+    /// [`parse_all`]: Args::parse_all
+    /// [`Args`]: crate::Args
+    pub fn parse_from<I>(args: I) -> Result<Args<'static>, ParseArgError<'static>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let argv: Vec<String> = args.into_iter().map(|arg| arg.as_ref().to_owned()).collect();
+        let argv: Vec<&str> = argv.iter().map(String::as_str).collect();
+
+        match parser::parse(&argv) {
+            Ok(args) => Ok(args.into_owned()),
+            Err(err) => Err(err.into_owned()),
+        }
+    }
+
+    /// Parses any sequence of string-like command-line arguments using a custom
+    /// [`ParserContext`].
+    ///
+    /// This is the owning counterpart of [`parse_with_context`]; see [`parse_from`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Args, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose");
+    ///
+    /// let args = Args::parse_from_with_context(vec![String::from("-v")], &ctx).unwrap();
+    /// assert!(args.has(ArgDef::Short('v')));
+    /// ```
+    ///
+    /// [`parse_with_context`]: Args::parse_with_context
+    /// [`parse_from`]: Args::parse_from
+    pub fn parse_from_with_context<I>(
+        args: I,
+        ctx: &ParserContext,
+    ) -> Result<Args<'static>, ParseArgError<'static>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let argv: Vec<String> = args.into_iter().map(|arg| arg.as_ref().to_owned()).collect();
+        let argv: Vec<&str> = argv.iter().map(String::as_str).collect();
+
+        match parser::parse_with_ctx(&argv, ctx) {
+            Ok(matches) => Ok(matches.into_args().into_owned()),
+            Err(err) => Err(err.into_owned()),
+        }
+    }
+
+    /// Parses the process command line into an `Args<'static>`.
+    ///
+    /// The command line (without the program name) is collected once and cached for the
+    /// rest of the process, so the result borrows from it for `'static` and can be stored in
+    /// globals or lazy statics. Arguments that are not valid Unicode are converted lossily.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::Args;
+    /// # use std::sync::OnceLock;
+    /// static ARGS: OnceLock<Args<'static>> = OnceLock::new();
+    ///
+    /// let args = ARGS.get_or_init(|| Args::parse_env_static().unwrap_or_default());
+    /// ```
+    pub fn parse_env_static() -> Result<Args<'static>, ParseArgError<'static>> {
+        parser::parse(static_argv())
     }
 
+    /// Parses the process command line into an `Args<'static>` using a custom
+    /// [`ParserContext`].
+    ///
+    /// See [`parse_env_static`].
+    ///
+    /// [`parse_env_static`]: Args::parse_env_static
+    pub fn parse_env_static_with_context(
+        ctx: &ParserContext,
+    ) -> Result<Args<'static>, ParseArgError<'static>> {
+        Args::parse_with_context(static_argv(), ctx)
+    }
+
+    /// Converts the arguments into ones that own all of their strings.
+    ///
+    /// This detaches the result from the lifetime of the parsed input.
+    pub fn into_owned(self) -> Args<'static> {
+        Args(self.0.into_iter().map(Argument::into_owned).collect(), self.1, self.2)
+    }
+
+    /// Lazily parses command-line arguments, yielding one [`Argument`] at a time.
+    ///
+    /// Unlike [`parse_all`], no `Vec` is built: tokens are pulled from `args` only as the
+    /// returned [`ParseIter`] is advanced, so very long argument lists can be processed in
+    /// constant memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - Any iterator of command-line argument strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgName, Args, Argument};
+    /// let mut iter = Args::parse_iter(["input.txt", "-v"]);
+    ///
+    /// assert_eq!(iter.next().unwrap().unwrap(), Argument::Positional { value: "input.txt".into() });
+    /// assert_eq!(iter.next().unwrap().unwrap(), Argument::Flag { name: ArgName::Short('v') });
+    /// assert!(iter.next().is_none());
+    /// ```
+    ///
+    /// [`Argument`]: crate::Argument
+    /// [`parse_all`]: Args::parse_all
+    /// [`ParseIter`]: crate::ParseIter
+    pub fn parse_iter<I>(args: I) -> ParseIter<'a, 'static, I::IntoIter>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        ParseIter::new(args.into_iter(), None)
+    }
+
+    /// Lazily parses command-line arguments using a custom [`ParserContext`].
+    ///
+    /// Unknown names are reported as they are reached. Checks that need the whole command
+    /// line, such as required definitions, are not performed.
+    /// The values of [`sensitive`] options are yielded as given; [`ParseIter::shown`] redacts
+    /// them for display.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, ParseArgError, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose");
+    ///
+    /// let results: Vec<_> = Args::parse_iter_with_context(["-v", "--nope", "-v"], &ctx).collect();
+    ///
+    /// assert!(results[0].is_ok());
+    /// assert!(matches!(results[1], Err(ParseArgError::UnknownLong(_))));
+    /// assert_eq!(results.len(), 2);
+    /// ```
+    ///
+    /// [`sensitive`]: crate::ArgSpec::sensitive
+    /// [`ParseIter::shown`]: crate::ParseIter::shown
+    pub fn parse_iter_with_context<'c, I>(
+        args: I,
+        ctx: &'c ParserContext<'c>,
+    ) -> ParseIter<'a, 'c, I::IntoIter>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        ParseIter::new(args.into_iter(), Some(ctx))
+    }
+
+}
+
+
+/// Returns the process command line without the program name, collected once and cached.
+fn static_argv() -> &'static [&'static str] {
+    static ARGV: OnceLock<Vec<String>> = OnceLock::new();
+    static REFS: OnceLock<Vec<&'static str>> = OnceLock::new();
+
+    REFS.get_or_init(|| {
+        ARGV.get_or_init(|| {
+            std::env::args_os()
+                .skip(1)
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect()
+        })
+        .iter()
+        .map(String::as_str)
+        .collect()
+    })
+}
+
+
+/// Returns `arg` as shown to people, with its value redacted if it is an option given under
+/// one of the `redacted` names.
+pub(crate) fn redacted<'s, 'a>(
+    arg: &'s Argument<'a>,
+    redacted: &[ArgName],
+) -> Cow<'s, Argument<'a>> {
+    match arg {
+        Argument::Option { name, .. } if redacted.contains(name) => {
+            Cow::Owned(Argument::Option { name: name.clone(), value: REDACTED.into() })
+        }
+        arg => Cow::Borrowed(arg),
+    }
+}
+
+
+/// Appends the snapshot line of `arg`, which came from `source`, to `out`.
+pub(crate) fn snapshot_line(out: &mut String, arg: &Argument, source: &str) {
+    let line = match arg {
+        Argument::Flag { name } => format!("flag {}", types::display_name(name)),
+        Argument::Option { name, value } => {
+            format!("option {}={:?}", types::display_name(name), value)
+        }
+        Argument::Positional { value } => format!("positional {:?}", value),
+    };
+    out.push_str(&format!("{} [{}]\n", line, source));
 }
@@ -0,0 +1,495 @@
+//! A subset of the [clap](https://docs.rs/clap) builder API, mapped onto a [`ParserContext`].
+//!
+//! This lets a project trial this parser without rewriting its definitions: replace
+//! `clap::` with `argsparse::clap::` and keep the `Command::new(..).arg(Arg::new(..))` chains.
+//! Only the common surface is covered: names, help, value names, required and default
+//! values, the basic [`ArgAction`]s, positionals, and the automatic `--help` and `--version`
+//! flags. Values are kept as `String`s, so typed value parsers are not available.
+//!
+//! # Examples
+//!
+//! ```
+//! use argsparse::clap::{Arg, ArgAction, Command};
+//!
+//! let matches = Command::new("myapp")
+//!     .arg(Arg::new("verbose").short('v').long("verbose").action(ArgAction::SetTrue))
+//!     .arg(Arg::new("output").short('o').long("output").default_value("out.txt"))
+//!     .arg(Arg::new("input").required(true))
+//!     .get_matches_from(["myapp", "-v", "in.txt"]);
+//!
+//! assert!(matches.get_flag("verbose"));
+//! assert_eq!(matches.get_one::<String>("output").unwrap(), "out.txt");
+//! assert_eq!(matches.get_one::<String>("input").unwrap(), "in.txt");
+//! ```
+//!
+//! [`ParserContext`]: crate::ParserContext
+
+use crate::defs::{ArgDef, Argument, ParseArgError};
+use crate::matches::Matches;
+use crate::parser::ParserContext;
+use crate::spec::{self, ArgSpec, PositionalSpec};
+use std::any::Any;
+use std::error;
+use std::fmt;
+use std::process;
+
+/// What an argument does when it is encountered.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum ArgAction {
+    /// Stores the value, later occurrences overriding earlier ones.
+    Set,
+    /// Stores every value.
+    Append,
+    /// A flag that is `true` when present.
+    SetTrue,
+    /// A flag that is `false` when present.
+    SetFalse,
+    /// A flag counting its occurrences.
+    Count,
+    /// A flag printing the help.
+    Help,
+    /// A flag printing the version.
+    Version,
+}
+
+/// The definition of an argument: an option when it has a short or long name, and a
+/// positional otherwise.
+#[derive(Debug, Clone)]
+pub struct Arg {
+    id: String,
+    short: Option<char>,
+    long: Option<String>,
+    help: Option<String>,
+    value_name: Option<String>,
+    required: bool,
+    default: Option<String>,
+    action: Option<ArgAction>,
+    hidden: bool,
+}
+
+/// A program and the arguments it accepts.
+#[derive(Debug, Clone)]
+pub struct Command {
+    name: String,
+    about: Option<String>,
+    version: Option<String>,
+    args: Vec<Arg>,
+}
+
+/// The arguments found on a command line, looked up by id.
+#[derive(Debug, Clone, Default)]
+pub struct ArgMatches {
+    /// The id, action, values and number of occurrences of each argument.
+    args: Vec<(String, ArgAction, Vec<String>, usize)>,
+}
+
+/// The kind of an [`Error`].
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The help was requested; the error message is the help text.
+    DisplayHelp,
+    /// The version was requested; the error message is the version line.
+    DisplayVersion,
+    /// An argument that is not defined was given.
+    UnknownArgument,
+    /// A required argument was not given.
+    MissingRequiredArgument,
+    /// A value was not accepted.
+    InvalidValue,
+}
+
+/// A command line that could not be parsed, or a request for help or version information.
+#[derive(Debug, Clone)]
+pub struct Error {
+    kind: ErrorKind,
+    message: String,
+}
+
+impl Arg {
+    /// Creates an argument identified by `id`.
+    pub fn new(id: impl Into<String>) -> Arg {
+        Arg {
+            id: id.into(),
+            short: None,
+            long: None,
+            help: None,
+            value_name: None,
+            required: false,
+            default: None,
+            action: None,
+            hidden: false,
+        }
+    }
+
+    /// Sets the short name.
+    pub fn short(mut self, short: char) -> Arg {
+        self.short = Some(short);
+        self
+    }
+
+    /// Sets the long name.
+    pub fn long(mut self, long: impl Into<String>) -> Arg {
+        self.long = Some(long.into());
+        self
+    }
+
+    /// Sets the help text.
+    pub fn help(mut self, help: impl Into<String>) -> Arg {
+        self.help = Some(help.into());
+        self
+    }
+
+    /// Sets the value placeholder used in help output.
+    pub fn value_name(mut self, name: impl Into<String>) -> Arg {
+        self.value_name = Some(name.into());
+        self
+    }
+
+    /// Marks the argument as required or optional.
+    pub fn required(mut self, required: bool) -> Arg {
+        self.required = required;
+        self
+    }
+
+    /// Sets the value assumed when the argument is absent.
+    pub fn default_value(mut self, value: impl Into<String>) -> Arg {
+        self.default = Some(value.into());
+        self
+    }
+
+    /// Sets what the argument does when encountered. Defaults to [`ArgAction::Set`].
+    pub fn action(mut self, action: ArgAction) -> Arg {
+        self.action = Some(action);
+        self
+    }
+
+    /// Hides the argument from help output.
+    pub fn hide(mut self, hidden: bool) -> Arg {
+        self.hidden = hidden;
+        self
+    }
+
+    fn get_action(&self) -> ArgAction {
+        self.action.unwrap_or(ArgAction::Set)
+    }
+
+    fn def(&self) -> Option<ArgDef<'_>> {
+        match (self.short, self.long.as_deref()) {
+            (Some(short), Some(long)) => Some(ArgDef::ShortAndLong { short, long }),
+            (Some(short), None) => Some(ArgDef::Short(short)),
+            (None, Some(long)) => Some(ArgDef::Long(long)),
+            (None, None) => None,
+        }
+    }
+}
+
+impl Command {
+    /// Creates a command for the program `name`.
+    pub fn new(name: impl Into<String>) -> Command {
+        Command { name: name.into(), about: None, version: None, args: Vec::new() }
+    }
+
+    /// Sets the description shown at the top of the help.
+    pub fn about(mut self, about: impl Into<String>) -> Command {
+        self.about = Some(about.into());
+        self
+    }
+
+    /// Sets the version, enabling the automatic `--version` flag.
+    pub fn version(mut self, version: impl Into<String>) -> Command {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Adds an argument.
+    pub fn arg(mut self, arg: Arg) -> Command {
+        self.args.push(arg);
+        self
+    }
+
+    /// Adds several arguments.
+    pub fn args(mut self, args: impl IntoIterator<Item = Arg>) -> Command {
+        self.args.extend(args);
+        self
+    }
+
+    /// Renders the help text.
+    pub fn render_help(&self) -> String {
+        self.context().render_help(&self.name)
+    }
+
+    /// Parses the process arguments, exiting with a message on error or on a request for
+    /// help or version information.
+    pub fn get_matches(self) -> ArgMatches {
+        self.get_matches_from(std::env::args())
+    }
+
+    /// Parses `args`, whose first item is the program name, exiting with a message on error
+    /// or on a request for help or version information.
+    pub fn get_matches_from<I, T>(self, args: I) -> ArgMatches
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.try_get_matches_from(args).unwrap_or_else(|err| err.exit())
+    }
+
+    /// Parses `args`, whose first item is the program name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use argsparse::clap::{Arg, Command, ErrorKind};
+    ///
+    /// let cmd = Command::new("myapp").version("1.2.0").arg(Arg::new("name").required(true));
+    ///
+    /// let err = cmd.clone().try_get_matches_from(["myapp"]).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::MissingRequiredArgument);
+    ///
+    /// let err = cmd.try_get_matches_from(["myapp", "--version"]).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::DisplayVersion);
+    /// assert_eq!(err.to_string(), "myapp 1.2.0\n");
+    /// ```
+    pub fn try_get_matches_from<I, T>(self, args: I) -> Result<ArgMatches, Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let args: Vec<String> = args.into_iter().skip(1).map(Into::into).collect();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let ctx = self.context();
+
+        // Help and version win over any other error, as long as they are defined
+        let requested = |action| {
+            let defs: Vec<ArgDef> = self.builtin(action).collect();
+            crate::Args::parse_iter_with_context(args.iter().copied(), &ctx)
+                .map_while(Result::ok)
+                .any(|arg| arg.name().is_some_and(|name| defs.iter().any(|def| def.matches(name))))
+        };
+        if requested(ArgAction::Help) {
+            let message = ctx.render_help(&self.name);
+            return Err(Error { kind: ErrorKind::DisplayHelp, message });
+        }
+        if requested(ArgAction::Version) {
+            let version = self.version.as_deref().unwrap_or_default();
+            let message = format!("{} {}\n", self.name, version);
+            return Err(Error { kind: ErrorKind::DisplayVersion, message });
+        }
+
+        let matches = Matches::parse(&args, &ctx)
+            .map_err(|err| Error::from_parse(&ctx, &self.name, &err))?;
+        Ok(self.collect(&matches))
+    }
+
+    /// Returns the definitions of the help or version flags, built in or user defined.
+    fn builtin(&self, action: ArgAction) -> impl Iterator<Item = ArgDef<'_>> {
+        let user = self
+            .args
+            .iter()
+            .filter(move |arg| arg.get_action() == action)
+            .filter_map(Arg::def);
+        let automatic = match action {
+            ArgAction::Help if self.is_free('h', "help") => {
+                Some(ArgDef::ShortAndLong { short: 'h', long: "help" })
+            }
+            ArgAction::Version if self.version.is_some() && self.is_free('V', "version") => {
+                Some(ArgDef::ShortAndLong { short: 'V', long: "version" })
+            }
+            _ => None,
+        };
+        user.chain(automatic)
+    }
+
+    /// Returns `true` if no argument uses the short name `short` or the long name `long`.
+    fn is_free(&self, short: char, long: &str) -> bool {
+        !self.args.iter().any(|arg| arg.short == Some(short) || arg.long.as_deref() == Some(long))
+    }
+
+    /// Builds the context equivalent to the definitions.
+    fn context(&self) -> ParserContext<'_> {
+        let mut ctx = ParserContext::new();
+        if let Some(about) = &self.about {
+            ctx.before_help(about);
+        }
+
+        for arg in &self.args {
+            let Some(def) = arg.def() else {
+                let mut spec = PositionalSpec::new(&arg.id).required(arg.required);
+                spec = spec.variadic(arg.get_action() == ArgAction::Append);
+                if let Some(help) = &arg.help {
+                    spec = spec.help(help);
+                }
+                ctx.positional(spec);
+                continue;
+            };
+
+            let mut spec = ArgSpec::new(def).required(arg.required).hidden(arg.hidden);
+            spec = match arg.get_action() {
+                ArgAction::Set | ArgAction::Append => {
+                    let name = arg.value_name.as_deref().unwrap_or(&arg.id);
+                    spec.action(spec::ArgAction::Value).value_name(name)
+                }
+                _ => spec.action(spec::ArgAction::Flag),
+            };
+            if let Some(help) = &arg.help {
+                spec = spec.help(help);
+            }
+            if let Some(default) = &arg.default {
+                spec = spec.default(default);
+            }
+            // Conflicting names are a programming error, which clap reports by panicking
+            if let Err(err) = ctx.register(spec) {
+                panic!("argument '{}': {}", arg.id, err);
+            }
+        }
+
+        for def in self.builtin(ArgAction::Help).chain(self.builtin(ArgAction::Version)) {
+            if ctx.specs().iter().all(|spec| spec.def != def) {
+                let help = if def.long() == Some("help") { "Print help" } else { "Print version" };
+                ctx.register(ArgSpec::new(def).action(spec::ArgAction::Flag).help(help)).unwrap();
+            }
+        }
+
+        ctx
+    }
+
+    fn collect(&self, matches: &Matches) -> ArgMatches {
+        let args = self.args.iter().map(|arg| {
+            let action = arg.get_action();
+            let (values, count): (Vec<String>, usize) = match arg.def() {
+                Some(def) => {
+                    let values = matches.occurrences(def).filter_map(|arg| match arg {
+                        Argument::Option { value, .. } => Some(value.to_string()),
+                        _ => None,
+                    });
+                    (values.collect(), matches.count(def))
+                }
+                None => {
+                    let values = matches.positional_values(&arg.id);
+                    let count = values.len();
+                    (values.into_iter().map(str::to_string).collect(), count)
+                }
+            };
+
+            let values = match (values.is_empty(), &arg.default) {
+                (true, Some(default)) => vec![default.clone()],
+                _ => values,
+            };
+            (arg.id.clone(), action, values, count)
+        });
+
+        ArgMatches { args: args.collect() }
+    }
+}
+
+impl ArgMatches {
+    /// Returns the state of the flag `id`: whether a [`SetTrue`] flag was given, or whether
+    /// a [`SetFalse`] flag was not.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not defined.
+    ///
+    /// [`SetTrue`]: ArgAction::SetTrue
+    /// [`SetFalse`]: ArgAction::SetFalse
+    pub fn get_flag(&self, id: &str) -> bool {
+        let (_, action, _, count) = self.get(id);
+        (*count > 0) != (*action == ArgAction::SetFalse)
+    }
+
+    /// Returns the number of occurrences of the argument `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not defined.
+    pub fn get_count(&self, id: &str) -> u8 {
+        let (_, _, _, count) = self.get(id);
+        (*count).min(u8::MAX as usize) as u8
+    }
+
+    /// Returns the value of the argument `id` (the last one, for an option given several
+    /// times), falling back to its default value.
+    ///
+    /// Values are stored as `String`: any other `T` yields `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not defined.
+    pub fn get_one<T: Any>(&self, id: &str) -> Option<&T> {
+        let (_, _, values, _) = self.get(id);
+        values.last().and_then(|value| (value as &dyn Any).downcast_ref())
+    }
+
+    /// Returns every value of the argument `id`, or `None` if it has none.
+    ///
+    /// Values are stored as `String`: any other `T` yields `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not defined.
+    pub fn get_many<T: Any>(&self, id: &str) -> Option<impl Iterator<Item = &T>> {
+        let (_, _, values, _) = self.get(id);
+        let values: Option<Vec<&T>> =
+            values.iter().map(|value| (value as &dyn Any).downcast_ref()).collect();
+        values.filter(|values| !values.is_empty()).map(Vec::into_iter)
+    }
+
+    /// Returns `true` if the argument `id` was given or has a default value.
+    pub fn contains_id(&self, id: &str) -> bool {
+        self.args
+            .iter()
+            .any(|(name, _, values, count)| name == id && (*count > 0 || !values.is_empty()))
+    }
+
+    fn get(&self, id: &str) -> &(String, ArgAction, Vec<String>, usize) {
+        self.args
+            .iter()
+            .find(|(name, ..)| name == id)
+            .unwrap_or_else(|| panic!("argument '{}' is not defined", id))
+    }
+}
+
+impl Error {
+    fn from_parse(ctx: &ParserContext, name: &str, err: &ParseArgError) -> Error {
+        let kind = match err {
+            ParseArgError::MissingRequired(_) | ParseArgError::MissingPositional(_) => {
+                ErrorKind::MissingRequiredArgument
+            }
+            ParseArgError::InvalidValue { .. } | ParseArgError::RejectedValue { .. } => {
+                ErrorKind::InvalidValue
+            }
+            _ => ErrorKind::UnknownArgument,
+        };
+        Error { kind, message: format!("error: {}", ctx.render_error_with_usage(name, err)) }
+    }
+
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Prints the error and exits the process: help and version information go to the
+    /// standard output with status 0, errors to the standard error with status 2.
+    pub fn exit(&self) -> ! {
+        match self.kind {
+            ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
+                print!("{}", self.message);
+                process::exit(0)
+            }
+            _ => {
+                eprint!("{}", self.message);
+                process::exit(2)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl error::Error for Error {}
@@ -0,0 +1,184 @@
+use crate::defs::ArgName;
+use crate::parser::ParserContext;
+use crate::types;
+use std::fmt;
+
+/// A change between two versions of a command-line interface that can break the scripts
+/// written against the older one, reported by [`ParserContext::breaking_changes`].
+///
+/// Arguments are named as typed (e.g., `--output`). The [`Display`] implementation
+/// describes the change in English.
+///
+/// [`ParserContext::breaking_changes`]: crate::ParserContext::breaking_changes
+/// [`Display`]: std::fmt::Display
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub enum BreakingChange {
+    /// A name that is no longer accepted.
+    Removed {
+        /// The name.
+        arg: String,
+    },
+    /// An argument that took a value and no longer does, or the other way around.
+    ArityChanged {
+        /// The argument.
+        arg: String,
+        /// Whether the argument takes a value now.
+        takes_value: bool,
+    },
+    /// An option that accepts fewer values than before.
+    PossibleValuesTightened {
+        /// The option.
+        arg: String,
+        /// The values no longer accepted, or none if the option accepted any value before.
+        removed: Vec<String>,
+    },
+    /// An argument that must now be given, and has no value to fall back to.
+    NowRequired {
+        /// The argument, or the name of a positional in angle brackets.
+        arg: String,
+    },
+    /// Fewer positionals are accepted than before.
+    FewerPositionals {
+        /// The number accepted before, or `None` if there was no limit.
+        before: Option<usize>,
+        /// The number accepted now.
+        after: usize,
+    },
+}
+
+impl fmt::Display for BreakingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BreakingChange::Removed { arg } => write!(f, "{} was removed", arg),
+            BreakingChange::ArityChanged { arg, takes_value: true } => {
+                write!(f, "{} now takes a value", arg)
+            }
+            BreakingChange::ArityChanged { arg, takes_value: false } => {
+                write!(f, "{} no longer takes a value", arg)
+            }
+            BreakingChange::PossibleValuesTightened { arg, removed } if removed.is_empty() => {
+                write!(f, "{} now only accepts some values", arg)
+            }
+            BreakingChange::PossibleValuesTightened { arg, removed } => {
+                write!(f, "{} no longer accepts {}", arg, removed.join(", "))
+            }
+            BreakingChange::NowRequired { arg } => write!(f, "{} is now required", arg),
+            BreakingChange::FewerPositionals { before: Some(before), after } => {
+                write!(f, "at most {} positionals are accepted, down from {}", after, before)
+            }
+            BreakingChange::FewerPositionals { before: None, after } => {
+                write!(f, "at most {} positionals are accepted, down from any number", after)
+            }
+        }
+    }
+}
+
+impl ParserContext<'_> {
+    /// Compares the context with `previous`, the one of an earlier release, and returns the
+    /// changes that can break command lines written for it, for release tooling to guard
+    /// the stability of the interface.
+    ///
+    /// Removed names, changes of arity, values no longer accepted, newly required arguments
+    /// and a lower number of positionals are reported, in the order the definitions of
+    /// `previous` were registered. Old names kept with [`ArgSpec::renamed_from`] are still
+    /// accepted, so renaming an argument is not a breaking change. Nested contexts are not
+    /// compared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, BreakingChange, ParserContext};
+    /// let mut previous = ParserContext::new();
+    /// previous.flag('v', "verbose").option('o', "output");
+    /// previous.register(ArgSpec::new(ArgDef::Long("color"))
+    ///     .possible_values(&["auto", "always", "never"]))
+    ///     .unwrap();
+    ///
+    /// let mut current = ParserContext::new();
+    /// current.register(ArgSpec::new(ArgDef::Long("verbose")).action(ArgAction::Flag)).unwrap();
+    /// current.register(ArgSpec::new(ArgDef::Long("out"))
+    ///     .action(ArgAction::Value)
+    ///     .renamed_from(&["output"]))
+    ///     .unwrap();
+    /// current.register(ArgSpec::new(ArgDef::Long("color"))
+    ///     .possible_values(&["auto", "never"]))
+    ///     .unwrap();
+    ///
+    /// let changes = current.breaking_changes(&previous);
+    /// let changes: Vec<String> = changes.iter().map(ToString::to_string).collect();
+    /// let expected = ["-v was removed", "-o was removed", "--color no longer accepts always"];
+    /// assert_eq!(changes, expected);
+    /// ```
+    ///
+    /// [`ArgSpec::renamed_from`]: crate::ArgSpec::renamed_from
+    pub fn breaking_changes(&self, previous: &ParserContext) -> Vec<BreakingChange> {
+        let mut changes = Vec::new();
+        for old in previous.specs() {
+            let names = old.def.short().map(ArgName::Short).into_iter()
+                .chain(old.def.long().map(|long| ArgName::Long(long.into())));
+            let mut current = None;
+            for name in names {
+                match self.spec_of(&name) {
+                    Some(spec) => current = current.or(Some((spec, name))),
+                    None => {
+                        changes.push(BreakingChange::Removed { arg: types::display_name(&name) })
+                    }
+                }
+            }
+            let Some((new, name)) = current else {
+                continue;
+            };
+            let arg = types::display_name(&name);
+
+            if old.takes_value() != new.takes_value() {
+                let takes_value = new.takes_value();
+                changes.push(BreakingChange::ArityChanged { arg: arg.clone(), takes_value });
+            }
+            if !new.possible_values.is_empty() {
+                let removed: Vec<String> = old.possible_values.iter()
+                    .filter(|value| !new.possible_values.contains(value))
+                    .map(|value| value.to_string())
+                    .collect();
+                if old.possible_values.is_empty() || !removed.is_empty() {
+                    let arg = arg.clone();
+                    changes.push(BreakingChange::PossibleValuesTightened { arg, removed });
+                }
+            }
+            let required = |ctx: &ParserContext, spec| {
+                spec_required(spec) && ctx.fallback(spec).is_none()
+            };
+            if required(self, new) && !required(previous, old) {
+                changes.push(BreakingChange::NowRequired { arg });
+            }
+        }
+
+        let (old, new) = (previous.positionals(), self.positionals());
+        for spec in new.iter().skip(old.len()).filter(|spec| spec.required) {
+            changes.push(BreakingChange::NowRequired { arg: format!("<{}>", spec.name) });
+        }
+        for (old, new) in old.iter().zip(new) {
+            if new.required && !old.required {
+                changes.push(BreakingChange::NowRequired { arg: format!("<{}>", new.name) });
+            }
+        }
+        let limit = |specs: &[crate::PositionalSpec]| match specs.last() {
+            Some(last) if last.variadic => None,
+            _ => Some(specs.len()),
+        };
+        // Contexts without positionals leave operands unchecked
+        if !new.is_empty()
+            && let Some(after) = limit(new)
+            && limit(old).is_none_or(|before| after < before)
+        {
+            changes.push(BreakingChange::FewerPositionals { before: limit(old), after });
+        }
+
+        changes
+    }
+}
+
+/// Returns `true` if `spec` must be given in some way.
+fn spec_required(spec: &crate::ArgSpec) -> bool {
+    spec.required || spec.occurrences.0 > 0
+}
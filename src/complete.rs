@@ -0,0 +1,720 @@
+use crate::defs::ArgName;
+use crate::parser::ParserContext;
+use crate::spec::{ArgAction, ArgSpec, ValueHint};
+use std::env;
+use std::fmt::{self, Write};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// The environment variable through which completion scripts ask for provided values.
+const COMPLETE_VALUES_ENV: &str = "ARGSPARSE_COMPLETE_VALUES";
+
+/// A shell for which a completion script can be generated.
+///
+/// Scripts are static: they are generated once (e.g., at build or install time) from the
+/// definitions of a [`ParserContext`], and complete option names as well as option values
+/// and operands according to their [`ValueHint`]. The values of options with a
+/// [`values_from`] provider are the exception: the script asks the program for them, see
+/// [`ParserContext::complete_values_from_env`].
+///
+/// # Examples
+///
+/// ```
+/// # use argsparse::{ArgAction, ArgDef, ArgSpec, ParserContext, Shell, ValueHint};
+/// let mut ctx = ParserContext::new();
+/// ctx.flag('v', "verbose");
+/// ctx.register(ArgSpec::new(ArgDef::ShortAndLong { short: 'C', long: "directory" })
+///     .action(ArgAction::Value)
+///     .value_hint(ValueHint::DirPath))
+///     .unwrap();
+///
+/// let script = Shell::Bash.generate("myapp", &ctx);
+///
+/// assert!(script.contains("complete -F _myapp myapp"));
+/// assert!(script.contains("compgen -d"));
+/// ```
+///
+/// [`ParserContext`]: crate::ParserContext
+/// [`ValueHint`]: crate::ValueHint
+/// [`values_from`]: crate::ArgSpec::values_from
+/// [`ParserContext::complete_values_from_env`]: crate::ParserContext::complete_values_from_env
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Shell {
+    /// GNU Bash, through `complete -F`.
+    Bash,
+    /// Zsh, through `_arguments`.
+    Zsh,
+    /// The fish shell, through `complete -c`.
+    Fish,
+}
+
+/// A visible option, as completion scripts see it.
+pub(crate) struct Entry<'c> {
+    short: Option<char>,
+    long: Option<String>,
+    help: Option<&'c str>,
+    value_name: Option<&'c str>,
+    /// The hint for the value, or `None` if the option is a plain flag.
+    value: Option<ValueHint>,
+    /// The values offered instead of the hint, if the definition restricts them.
+    possible: &'c [&'c str],
+    /// Whether the values are asked from the program instead.
+    dynamic: bool,
+    /// Whether the option must be given.
+    required: bool,
+}
+
+impl Shell {
+    /// Generates a completion script for the program `name`, defined by `ctx`.
+    ///
+    /// Hidden definitions are left out. Options of nested contexts are completed under
+    /// their prefixed long names.
+    pub fn generate(self, name: &str, ctx: &ParserContext) -> String {
+        let mut entries = Vec::new();
+        collect(ctx, "", &mut entries);
+
+        let mut out = String::new();
+        match self {
+            Shell::Bash => bash(&mut out, name, ctx, &entries),
+            Shell::Zsh => zsh(&mut out, name, ctx, &entries),
+            Shell::Fish => fish(&mut out, name, &entries),
+        }
+        .expect("writing to a String never fails");
+        out
+    }
+
+    /// Detects the user's shell from the `SHELL` environment variable.
+    pub fn detect() -> Option<Shell> {
+        let shell = env::var_os("SHELL")?;
+        match Path::new(&shell).file_name()?.to_str()? {
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            _ => None,
+        }
+    }
+
+    /// Returns where the completion script of the program `name` conventionally goes for the
+    /// current user, or `None` if the home directory is unknown.
+    ///
+    /// * Bash: `$XDG_DATA_HOME/bash-completion/completions/<name>`, loaded on demand by
+    ///   bash-completion.
+    /// * Zsh: `~/.zfunc/_<name>`; `~/.zfunc` must be in `fpath` before `compinit` runs.
+    /// * Fish: `$XDG_CONFIG_HOME/fish/completions/<name>.fish`.
+    ///
+    /// `XDG_DATA_HOME` and `XDG_CONFIG_HOME` default to `~/.local/share` and `~/.config`.
+    pub fn install_path(self, name: &str) -> Option<PathBuf> {
+        let home = env::var_os("HOME").filter(|home| !home.is_empty()).map(PathBuf::from);
+        let xdg = |var: &str, default: &str| {
+            env::var_os(var)
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| home.as_ref().map(|home| home.join(default)))
+        };
+
+        Some(match self {
+            Shell::Bash => xdg("XDG_DATA_HOME", ".local/share")?
+                .join("bash-completion/completions")
+                .join(name),
+            Shell::Zsh => home.as_ref()?.join(".zfunc").join(format!("_{}", name)),
+            Shell::Fish => xdg("XDG_CONFIG_HOME", ".config")?
+                .join("fish/completions")
+                .join(format!("{}.fish", name)),
+        })
+    }
+
+    /// Generates the completion script of the program `name` and writes it to its
+    /// [`install_path`], creating the missing directories. Returns the path written to.
+    ///
+    /// With `dry_run`, nothing is written and the path is only resolved, so a program can
+    /// tell the user what would happen.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use argsparse::{ParserContext, Shell};
+    /// let home = std::env::temp_dir().join("argsparse-doc-install");
+    /// unsafe {
+    ///     std::env::set_var("HOME", &home);
+    ///     std::env::remove_var("XDG_CONFIG_HOME");
+    /// }
+    ///
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose");
+    ///
+    /// let path = Shell::Fish.install("myapp", &ctx, true).unwrap();
+    /// assert_eq!(path, home.join(".config/fish/completions/myapp.fish"));
+    /// assert!(!path.exists());
+    ///
+    /// Shell::Fish.install("myapp", &ctx, false).unwrap();
+    /// assert!(std::fs::read_to_string(&path).unwrap().contains("complete -c myapp"));
+    /// # std::fs::remove_dir_all(&home).unwrap();
+    /// ```
+    ///
+    /// [`install_path`]: Shell::install_path
+    pub fn install(self, name: &str, ctx: &ParserContext, dry_run: bool) -> io::Result<PathBuf> {
+        let path = self.install_path(name).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "the home directory is unknown")
+        })?;
+        if dry_run {
+            return Ok(path);
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, self.generate(name, ctx))?;
+        Ok(path)
+    }
+}
+
+/// Collects the visible options of `ctx` and its nested contexts.
+pub(crate) fn collect<'c>(ctx: &ParserContext<'c>, prefix: &str, out: &mut Vec<Entry<'c>>) {
+    for spec in ctx.specs().iter().filter(|spec| !spec.hidden) {
+        // A nested context only exposes its long names
+        let short = if prefix.is_empty() { spec.def.short() } else { None };
+        let long = spec.def.long().map(|long| format!("{}{}", prefix, long));
+        if short.is_none() && long.is_none() {
+            continue;
+        }
+
+        let value = match spec.action {
+            ArgAction::Flag => None,
+            ArgAction::Value | ArgAction::Capture => Some(spec.value_hint),
+            // Without a hint there is no telling whether a value follows
+            ArgAction::Infer if spec.possible_values.is_empty() && spec.values_from.is_none() => {
+                Some(spec.value_hint).filter(|&hint| hint != ValueHint::Unknown)
+            }
+            ArgAction::Infer => Some(spec.value_hint),
+        };
+
+        out.push(Entry {
+            short,
+            long,
+            help: spec.help,
+            value_name: spec.value_name,
+            value,
+            possible: spec.possible_values,
+            dynamic: spec.values_from.is_some(),
+            required: spec.required,
+        });
+    }
+
+    for (name, child) in ctx.namespaces() {
+        collect(child, &format!("{}{}-", prefix, name), out);
+    }
+}
+
+/// Returns the hint used to complete operands.
+pub(crate) fn operand_hint(ctx: &ParserContext) -> ValueHint {
+    match ctx.positionals().first() {
+        Some(spec) if spec.value_hint != ValueHint::Unknown => spec.value_hint,
+        // Operands are most often files
+        _ => ValueHint::AnyPath,
+    }
+}
+
+/// Returns the names of an entry as typed on the command line.
+pub(crate) fn names(entry: &Entry) -> Vec<String> {
+    entry.short.map(|short| format!("-{}", short))
+        .into_iter()
+        .chain(entry.long.iter().map(|long| format!("--{}", long)))
+        .collect()
+}
+
+/// Returns the name under which the program is asked for the values of an entry.
+fn query(entry: &Entry) -> String {
+    match (&entry.long, entry.short) {
+        (Some(long), _) => format!("--{}", long),
+        (None, Some(short)) => format!("-{}", short),
+        (None, None) => unreachable!("entries have at least one name"),
+    }
+}
+
+/// Returns the name of the option typed as `typed` (e.g., `--profile` or `-p`).
+pub(crate) fn typed_name(typed: &str) -> Option<ArgName<'_>> {
+    match typed.strip_prefix("--") {
+        Some(long) => Some(ArgName::Long(long.into())),
+        None => {
+            let mut chars = typed.strip_prefix('-')?.chars();
+            match (chars.next(), chars.next()) {
+                (Some(short), None) => Some(ArgName::Short(short)),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Completes the word ending `before`, the arguments of a command line up to the cursor,
+/// returning where the word starts and the words that complete it, or `None` when paths
+/// are completed instead.
+///
+/// Option names, option values from their possible values or provider, and operands are
+/// completed. Arguments are split on whitespace, without shell quoting.
+pub(crate) fn complete_word(ctx: &ParserContext, before: &str) -> Option<(usize, Vec<String>)> {
+    let start = before.rfind(char::is_whitespace).map_or(0, |index| {
+        index + before[index..].chars().next().map_or(0, char::len_utf8)
+    });
+    let word = &before[start..];
+    let previous: Vec<&str> = before[..start].split_whitespace().collect();
+    let operands_only = previous.contains(&"--");
+
+    if operands_only {
+        None
+    } else if let Some(spec) = previous.last().and_then(|last| option(ctx, last)) {
+        values(spec, word).map(|values| (start, values))
+    } else if let Some((typed, prefix)) = word.split_once('=')
+        && let Some(spec) = option(ctx, typed)
+    {
+        let start = start + typed.len() + 1;
+        values(spec, prefix).map(|values| (start, values))
+    } else if word.starts_with('-') {
+        let mut entries = Vec::new();
+        collect(ctx, "", &mut entries);
+        let names = entries.iter().flat_map(names).filter(|name| name.starts_with(word));
+        Some((start, names.collect()))
+    } else if !is_path(operand_hint(ctx)) {
+        Some((start, Vec::new()))
+    } else {
+        None
+    }
+}
+
+/// Returns the option typed as `typed` if it takes a value.
+fn option<'s, 'c>(ctx: &'s ParserContext<'c>, typed: &str) -> Option<&'s ArgSpec<'c>> {
+    let spec = ctx.spec_of(&typed_name(typed)?)?;
+    let takes_value = spec.action == ArgAction::Value
+        || !spec.possible_values.is_empty()
+        || spec.values_from.is_some();
+    takes_value.then_some(spec)
+}
+
+/// Returns the values of `spec` starting with `prefix`, or `None` if paths are completed
+/// instead.
+fn values(spec: &ArgSpec, prefix: &str) -> Option<Vec<String>> {
+    let values: Vec<String> = match &spec.values_from {
+        _ if !spec.possible_values.is_empty() => {
+            spec.possible_values.iter().map(|value| value.to_string()).collect()
+        }
+        Some(provider) => provider(),
+        None if is_path(spec.value_hint) => return None,
+        None => Vec::new(),
+    };
+    Some(values.into_iter().filter(|value| value.starts_with(prefix)).collect())
+}
+
+/// Returns whether values hinted as `hint` are completed as paths.
+fn is_path(hint: ValueHint) -> bool {
+    matches!(hint, ValueHint::AnyPath | ValueHint::FilePath | ValueHint::DirPath)
+}
+
+/// Quotes `text` for inclusion in single quotes.
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+fn bash(out: &mut String, name: &str, ctx: &ParserContext, entries: &[Entry]) -> fmt::Result {
+    let compgen = |hint: ValueHint| match hint {
+        ValueHint::AnyPath | ValueHint::FilePath => "COMPREPLY=($(compgen -f -- \"$cur\"))",
+        ValueHint::DirPath => "COMPREPLY=($(compgen -d -- \"$cur\"))",
+        ValueHint::CommandName => "COMPREPLY=($(compgen -c -- \"$cur\"))",
+        ValueHint::Hostname => "COMPREPLY=($(compgen -A hostname -- \"$cur\"))",
+        ValueHint::Username => "COMPREPLY=($(compgen -u -- \"$cur\"))",
+        ValueHint::Unknown | ValueHint::Other | ValueHint::Url => "COMPREPLY=()",
+    };
+
+    let function: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    writeln!(out, "_{}() {{", function)?;
+    writeln!(out, "    local cur prev")?;
+    writeln!(out, "    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"")?;
+    writeln!(out, "    prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"")?;
+    writeln!(out)?;
+    writeln!(out, "    case \"$prev\" in")?;
+    for entry in entries {
+        if let Some(hint) = entry.value {
+            writeln!(out, "        {})", names(entry).join("|"))?;
+            if entry.dynamic {
+                let ask = format!(
+                    "$({}={} \"${{COMP_WORDS[0]}}\" 2>/dev/null)",
+                    COMPLETE_VALUES_ENV,
+                    query(entry)
+                );
+                writeln!(out, "            COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))", ask)?;
+            } else if entry.possible.is_empty() {
+                writeln!(out, "            {}", compgen(hint))?;
+            } else {
+                let words = quote(&entry.possible.join(" "));
+                writeln!(out, "            COMPREPLY=($(compgen -W {} -- \"$cur\"))", words)?;
+            }
+            writeln!(out, "            return 0")?;
+            writeln!(out, "            ;;")?;
+        }
+    }
+    writeln!(out, "    esac")?;
+    writeln!(out)?;
+    writeln!(out, "    if [[ \"$cur\" == -* ]]; then")?;
+    let words: Vec<String> = entries.iter().flat_map(names).collect();
+    let words = quote(&words.join(" "));
+    writeln!(out, "        COMPREPLY=($(compgen -W {} -- \"$cur\"))", words)?;
+    writeln!(out, "        return 0")?;
+    writeln!(out, "    fi")?;
+    writeln!(out)?;
+    writeln!(out, "    {}", compgen(operand_hint(ctx)))?;
+    writeln!(out, "}}")?;
+    writeln!(out, "complete -F _{} {}", function, name)
+}
+
+fn zsh(out: &mut String, name: &str, ctx: &ParserContext, entries: &[Entry]) -> fmt::Result {
+    let action = |hint: ValueHint| match hint {
+        ValueHint::AnyPath | ValueHint::FilePath => "_files",
+        ValueHint::DirPath => "_files -/",
+        ValueHint::CommandName => "_command_names -e",
+        ValueHint::Hostname => "_hosts",
+        ValueHint::Username => "_users",
+        ValueHint::Url => "_urls",
+        ValueHint::Unknown | ValueHint::Other => "",
+    };
+    // Brackets delimit descriptions, and colons delimit the fields of a value spec
+    let escape_help = |text: &str| {
+        text.replace('\\', r"\\")
+            .replace('[', r"\[")
+            .replace(']', r"\]")
+    };
+    let escape_field = |text: &str| text.replace('\\', r"\\").replace(':', r"\:");
+    // Values in a `(a b c)` list are separated by spaces
+    let escape_value = |text: &str| {
+        text.chars()
+            .flat_map(|c| {
+                let escape = matches!(c, '\\' | ' ' | '(' | ')' | ':');
+                escape.then_some('\\').into_iter().chain([c])
+            })
+            .collect::<String>()
+    };
+
+    let mut specs = Vec::new();
+    for entry in entries {
+        let names = names(entry);
+        let help = entry.help.map(|help| format!("[{}]", escape_help(help)));
+        let value = entry.value.map(|hint| {
+            let value_name = escape_field(entry.value_name.unwrap_or("VALUE"));
+            if entry.dynamic {
+                let ask = format!(
+                    "$({}={} $words[1] 2>/dev/null)",
+                    COMPLETE_VALUES_ENV,
+                    query(entry)
+                );
+                format!(":{}:{{compadd -- ${{(f)\"{}\"}}}}", value_name, ask)
+            } else if entry.possible.is_empty() {
+                format!(":{}:{}", value_name, action(hint))
+            } else {
+                let values: Vec<_> = entry.possible.iter().map(|v| escape_value(v)).collect();
+                format!(":{}:({})", value_name, values.join(" "))
+            }
+        });
+        let (help, value) = (help.unwrap_or_default(), value.unwrap_or_default());
+
+        if let [name] = names.as_slice() {
+            specs.push(quote(&format!("{}{}{}", name, help, value)));
+        } else {
+            // '(-v --verbose)'{-v,--verbose}'[help]:VALUE:action'
+            let rest = format!("{}{}", help, value);
+            let rest = if rest.is_empty() { rest } else { quote(&rest) };
+            let exclusions = quote(&format!("({})", names.join(" ")));
+            specs.push(format!("{}{{{}}}{}", exclusions, names.join(","), rest));
+        }
+    }
+
+    if ctx.positionals().is_empty() {
+        specs.push(quote(&format!("*:: :{}", action(operand_hint(ctx)))));
+    }
+    for spec in ctx.positionals() {
+        let position = match (spec.variadic, spec.required) {
+            (true, _) => "*:",
+            (false, true) => ":",
+            (false, false) => "::",
+        };
+        let name = escape_field(spec.name);
+        specs.push(quote(&format!("{}{}:{}", position, name, action(spec.value_hint))));
+    }
+
+    writeln!(out, "#compdef {}", name)?;
+    writeln!(out)?;
+    write!(out, "_arguments -s")?;
+    for spec in specs {
+        write!(out, " \\\n    {}", spec)?;
+    }
+    writeln!(out)
+}
+
+fn fish(out: &mut String, name: &str, entries: &[Entry]) -> fmt::Result {
+    for entry in entries {
+        write!(out, "complete -c {}", name)?;
+        if let Some(short) = entry.short {
+            write!(out, " -s {}", short)?;
+        }
+        if let Some(long) = &entry.long {
+            write!(out, " -l {}", long)?;
+        }
+        if let Some(help) = entry.help {
+            write!(out, " -d {}", quote(help))?;
+        }
+        match entry.value {
+            None => Ok(()),
+            Some(_) if entry.dynamic => {
+                let ask = format!(
+                    "(env {}={} (commandline -opc)[1] 2>/dev/null)",
+                    COMPLETE_VALUES_ENV,
+                    query(entry)
+                );
+                write!(out, " -r -f -a {}", quote(&ask))
+            }
+            Some(_) if !entry.possible.is_empty() => {
+                write!(out, " -r -f -a {}", quote(&entry.possible.join(" ")))
+            }
+            Some(ValueHint::AnyPath | ValueHint::FilePath) => write!(out, " -r -F"),
+            Some(ValueHint::DirPath) => write!(out, " -r -f -a '(__fish_complete_directories)'"),
+            Some(ValueHint::CommandName) => write!(out, " -r -f -a '(__fish_complete_command)'"),
+            Some(ValueHint::Hostname) => write!(out, " -r -f -a '(__fish_print_hostnames)'"),
+            Some(ValueHint::Username) => write!(out, " -r -f -a '(__fish_complete_users)'"),
+            Some(ValueHint::Unknown | ValueHint::Other | ValueHint::Url) => write!(out, " -r -f"),
+        }?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Quotes `text` as a double-quoted YAML string.
+fn yaml(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', r"\\").replace('"', r#"\""#))
+}
+
+/// Returns the YAML list completing values of the kind `hint`, if carapace has a macro for it.
+fn carapace_action(hint: ValueHint) -> Option<&'static str> {
+    match hint {
+        ValueHint::AnyPath | ValueHint::FilePath => Some("[\"$files\"]"),
+        ValueHint::DirPath => Some("[\"$directories\"]"),
+        _ => None,
+    }
+}
+
+fn carapace(out: &mut String, name: &str, ctx: &ParserContext, entries: &[Entry]) -> fmt::Result {
+    writeln!(out, "name: {}", yaml(name))?;
+    if let Some(description) = ctx.before_help {
+        writeln!(out, "description: {}", yaml(description))?;
+    }
+
+    if !entries.is_empty() {
+        writeln!(out, "flags:")?;
+    }
+    let mut flag_actions = Vec::new();
+    for entry in entries {
+        // `=` marks options taking a value, `!` required ones
+        let mut key = names(entry).join(", ");
+        if entry.value.is_some() {
+            key.push('=');
+        }
+        if entry.required {
+            key.push('!');
+        }
+        writeln!(out, "  {}: {}", yaml(&key), yaml(entry.help.unwrap_or_default()))?;
+
+        let action = match entry.value {
+            None => None,
+            Some(_) if entry.dynamic => {
+                let ask = format!("$({}={} {})", COMPLETE_VALUES_ENV, query(entry), name);
+                Some(format!("[{}]", yaml(&ask)))
+            }
+            Some(_) if !entry.possible.is_empty() => {
+                let values: Vec<String> = entry.possible.iter().map(|v| yaml(v)).collect();
+                Some(format!("[{}]", values.join(", ")))
+            }
+            Some(hint) => carapace_action(hint).map(String::from),
+        };
+        if let Some(action) = action {
+            let flag = query(entry);
+            flag_actions.push((flag.trim_start_matches('-').to_string(), action));
+        }
+    }
+
+    // A variadic positional completes every remaining operand
+    let positionals = ctx.positionals();
+    let (fixed, any) = match positionals.split_last() {
+        Some((last, rest)) if last.variadic => (rest, Some(last.value_hint)),
+        Some(_) => (positionals, None),
+        None => (positionals, Some(operand_hint(ctx))),
+    };
+    let fixed: Vec<&str> = fixed
+        .iter()
+        .map(|spec| carapace_action(spec.value_hint).unwrap_or("[]"))
+        .collect();
+    let any = any.and_then(carapace_action);
+
+    if flag_actions.is_empty() && fixed.is_empty() && any.is_none() {
+        return Ok(());
+    }
+    writeln!(out, "completion:")?;
+    if !flag_actions.is_empty() {
+        writeln!(out, "  flag:")?;
+        for (flag, action) in flag_actions {
+            writeln!(out, "    {}: {}", yaml(&flag), action)?;
+        }
+    }
+    if !fixed.is_empty() {
+        writeln!(out, "  positional:")?;
+        for action in fixed {
+            writeln!(out, "    - {}", action)?;
+        }
+    }
+    if let Some(action) = any {
+        writeln!(out, "  positionalany: {}", action)?;
+    }
+    Ok(())
+}
+
+impl ParserContext<'_> {
+    /// Exports the definitions as a [carapace] spec for the program `name`, a YAML file from
+    /// which carapace completes the program in every shell it supports.
+    ///
+    /// The spec covers the same options, values and operands as the scripts generated by
+    /// [`Shell::generate`]. Values with no carapace equivalent, such as host names, are left
+    /// uncompleted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, ParserContext, ValueHint};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.before_help("Copy files");
+    /// ctx.register(ArgSpec::new(ArgDef::ShortAndLong { short: 'v', long: "verbose" })
+    ///     .action(ArgAction::Flag)
+    ///     .help("Explain what is being done"))
+    ///     .unwrap();
+    /// ctx.register(ArgSpec::new(ArgDef::Long("color")).possible_values(&["auto", "never"]))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(ctx.carapace_spec("cp"), r#"name: "cp"
+    /// description: "Copy files"
+    /// flags:
+    ///   "-v, --verbose": "Explain what is being done"
+    ///   "--color=": ""
+    /// completion:
+    ///   flag:
+    ///     "color": ["auto", "never"]
+    ///   positionalany: ["$files"]
+    /// "#);
+    /// ```
+    ///
+    /// [carapace]: https://carapace.sh
+    /// [`Shell::generate`]: crate::Shell::generate
+    pub fn carapace_spec(&self, name: &str) -> String {
+        let mut entries = Vec::new();
+        collect(self, "", &mut entries);
+
+        let mut out = String::new();
+        carapace(&mut out, name, self, &entries).expect("writing to a String never fails");
+        out
+    }
+
+    /// Answers a completion script asking for the values of an option with a
+    /// [`values_from`] provider.
+    ///
+    /// Completion scripts run the program with the `ARGSPARSE_COMPLETE_VALUES` environment
+    /// variable set to the option, as typed (e.g., `--profile`). Call this before parsing:
+    /// when the variable is set, the provided values are printed one per line and the process
+    /// exits; otherwise nothing happens.
+    ///
+    /// [`values_from`]: crate::ArgSpec::values_from
+    pub fn complete_values_from_env(&self) {
+        let Some(name) = env::var_os(COMPLETE_VALUES_ENV) else {
+            return;
+        };
+        for value in self.provided_values(&name.to_string_lossy()) {
+            println!("{}", value);
+        }
+        process::exit(0);
+    }
+
+    /// Answers bash asking for completions as an external completer, set up with a single
+    /// `complete -o default -C myapp myapp` line instead of a generated script.
+    ///
+    /// Bash runs the program with the command line in `COMP_LINE` and the cursor position in
+    /// `COMP_POINT`. Call this before parsing: when both are set, the completions of
+    /// [`complete_line`] are printed one per line and the process exits; otherwise nothing
+    /// happens. When paths are to be completed, nothing is printed, and `-o default` makes
+    /// bash complete file names itself.
+    ///
+    /// [`complete_line`]: ParserContext::complete_line
+    pub fn complete_from_env(&self) {
+        let (Ok(line), Some(point)) = (env::var("COMP_LINE"), env::var_os("COMP_POINT")) else {
+            return;
+        };
+        let point = point.to_str().and_then(|point| point.parse().ok()).unwrap_or(line.len());
+        for word in self.complete_line(&line, point) {
+            println!("{}", word);
+        }
+        process::exit(0);
+    }
+
+    /// Returns the completions of the word under the cursor in `line`, a command line
+    /// starting with the name of the program, with the cursor `point` characters in.
+    ///
+    /// Option names, option values from their possible values or [`values_from`] provider,
+    /// and operands are completed, as with the `ReplHelper` of the `rustyline` feature.
+    /// Nothing is returned when paths are to be completed. The line is split on whitespace,
+    /// without shell quoting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ArgSpec, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose");
+    /// ctx.register(ArgSpec::new(ArgDef::Long("color")).possible_values(&["auto", "never"]))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(ctx.complete_line("myapp --ve", 10), ["--verbose"]);
+    /// assert_eq!(ctx.complete_line("myapp --color ", 14), ["auto", "never"]);
+    /// assert_eq!(ctx.complete_line("myapp --color=n", 15), ["never"]);
+    /// ```
+    ///
+    /// [`values_from`]: crate::ArgSpec::values_from
+    pub fn complete_line(&self, line: &str, point: usize) -> Vec<String> {
+        let end = line.char_indices().nth(point).map_or(line.len(), |(index, _)| index);
+        // The first word names the program
+        let line = &line[..end];
+        let Some(args) = line.find(char::is_whitespace).map(|index| &line[index..]) else {
+            return Vec::new();
+        };
+        complete_word(self, args).map_or_else(Vec::new, |(_, words)| words)
+    }
+
+    /// Returns the values provided for the option `name`, as typed (e.g., `--profile`), or
+    /// nothing if it is not defined or has no provider.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ArgSpec, ParserContext, Shell};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(ArgDef::ShortAndLong { short: 'p', long: "profile" })
+    ///     .values_from(|| vec!["dev".into(), "prod".into()]))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(ctx.provided_values("-p"), ["dev", "prod"]);
+    ///
+    /// let script = Shell::Bash.generate("myapp", &ctx);
+    /// assert!(script.contains("ARGSPARSE_COMPLETE_VALUES=--profile"));
+    /// ```
+    pub fn provided_values(&self, name: &str) -> Vec<String> {
+        typed_name(name)
+            .and_then(|name| self.spec_of(&name))
+            .and_then(|spec| spec.values_from.as_ref())
+            .map_or_else(Vec::new, |provider| provider())
+    }
+}
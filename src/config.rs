@@ -0,0 +1,263 @@
+use crate::defs::{ArgDef, ArgName};
+use crate::observe::{SourceKind, ValueSource};
+use crate::parser::ParserContext;
+use crate::spec::{ArgAction, ArgSpec, ValueHint};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The order in which the sources of values are consulted unless set otherwise.
+pub(crate) const DEFAULT_PRECEDENCE: &[SourceKind] =
+    &[SourceKind::CommandLine, SourceKind::Env, SourceKind::Config, SourceKind::Default];
+
+impl<'a> ParserContext<'a> {
+    /// Adds a layer of configuration values read from the file at `path`, keyed by the long
+    /// names of the definitions (e.g., `port`, not `--port`).
+    ///
+    /// The file is not read here: the program reads it in whatever format it likes and
+    /// passes the values, while `path` records where they came from. Options absent from the
+    /// command line and without a set environment variable take their value from the
+    /// configuration before their default, unless the [`precedence`] says otherwise; layers
+    /// added later take precedence over earlier ones, so add the system-wide file before the
+    /// user's. A value from the configuration satisfies a required option.
+    ///
+    /// Values are not checked against the definitions, and keys matching no long name are
+    /// ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ArgSpec, Matches, ParserContext, ValueSource};
+    /// # use std::path::Path;
+    /// let port = ArgDef::Long("port");
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(port).default("8080")).unwrap();
+    /// ctx.config_values("/etc/app.conf", [("port", "80")])
+    ///     .config_values("/home/me/.app.conf", [("port", "8000")]);
+    ///
+    /// let matches = Matches::parse(&[], &ctx).unwrap();
+    /// let (value, source) = matches.value_with_source(port).unwrap();
+    ///
+    /// assert_eq!(value, "8000");
+    /// assert_eq!(source, ValueSource::ConfigFile(Path::new("/home/me/.app.conf")));
+    /// ```
+    ///
+    /// [`precedence`]: ParserContext::precedence
+    pub fn config_values<K, V>(
+        &mut self,
+        path: impl Into<PathBuf>,
+        values: impl IntoIterator<Item = (K, V)>,
+    ) -> &mut Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let values = values.into_iter().map(|(key, value)| (key.into(), value.into())).collect();
+        self.configs.push((path.into(), values));
+        self
+    }
+
+    /// Reads the configuration file at `path` and adds its values as a layer, as with
+    /// [`config_values`].
+    ///
+    /// The file holds one `name = value` pair per line, where `name` is the long name of a
+    /// definition. Blank lines and lines starting with `#` or `;` are skipped, and values may
+    /// be wrapped in double quotes. Fails with [`io::ErrorKind::InvalidData`] on a line
+    /// without `=`.
+    ///
+    /// [`config_values`]: ParserContext::config_values
+    pub fn config_file(&mut self, path: impl AsRef<Path>) -> io::Result<&mut Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+
+        let mut values = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                let message = format!("{}:{}: expected `name = value`", path.display(), number + 1);
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+            };
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            values.push((key.trim().to_string(), value.to_string()));
+        }
+        Ok(self.config_values(path, values))
+    }
+
+    /// Returns where the configuration file `file` of the program `app` belongs by the
+    /// conventions of the platform, whether or not it exists.
+    ///
+    /// The file is looked for in a directory named `app` under:
+    ///
+    /// * `%APPDATA%` on Windows.
+    /// * `~/Library/Application Support` on macOS.
+    /// * `$XDG_CONFIG_HOME` elsewhere, or `~/.config` when it is unset or not absolute.
+    ///
+    /// Returns `None` if the variable the directory derives from is not set.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use argsparse::ParserContext;
+    /// # use std::path::Path;
+    /// # if cfg!(all(unix, not(target_os = "macos"))) {
+    /// unsafe { std::env::set_var("XDG_CONFIG_HOME", "/home/me/.config") };
+    ///
+    /// let path = ParserContext::config_path("myapp", "config.ini").unwrap();
+    /// assert_eq!(path, Path::new("/home/me/.config/myapp/config.ini"));
+    /// # }
+    /// ```
+    pub fn config_path(app: &str, file: &str) -> Option<PathBuf> {
+        let var = |name: &str| {
+            env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from)
+        };
+        let base = if cfg!(windows) {
+            var("APPDATA")?
+        } else if cfg!(target_os = "macos") {
+            var("HOME")?.join("Library/Application Support")
+        } else {
+            match var("XDG_CONFIG_HOME").filter(|dir| dir.is_absolute()) {
+                Some(dir) => dir,
+                None => var("HOME")?.join(".config"),
+            }
+        };
+        Some(base.join(app).join(file))
+    }
+
+    /// Adds the configuration file named by `--config` on the command line `args` as a
+    /// layer, or else the one at the standard location given by [`config_path`], as with
+    /// [`config_file`].
+    ///
+    /// The `--config FILE` option is registered unless a definition already claims the name,
+    /// so that parsing `args` afterwards accepts it. Call this before parsing, since the
+    /// configuration must be in place by then. A missing file is an error when named on the
+    /// command line, and is skipped at the standard location.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Matches, ParserContext};
+    /// let path = std::env::temp_dir().join("argsparse-doc-discover.conf");
+    /// std::fs::write(&path, "output = from-config.txt\n").unwrap();
+    ///
+    /// let args = ["--config", path.to_str().unwrap()];
+    /// let mut ctx = ParserContext::new();
+    /// ctx.option('o', "output");
+    /// ctx.discover_config(&args, "myapp", "config.ini").unwrap();
+    ///
+    /// let matches = Matches::parse(&args, &ctx).unwrap();
+    /// let output = matches.value_of(ArgDef::Long("output"));
+    /// assert_eq!(output.as_deref(), Some("from-config.txt"));
+    /// # std::fs::remove_file(path).unwrap();
+    /// ```
+    ///
+    /// [`config_path`]: ParserContext::config_path
+    /// [`config_file`]: ParserContext::config_file
+    pub fn discover_config<S: AsRef<str>>(
+        &mut self,
+        args: &[S],
+        app: &str,
+        file: &str,
+    ) -> io::Result<&mut Self> {
+        if self.spec_of(&ArgName::Long("config".into())).is_none() {
+            let spec = ArgSpec::new(ArgDef::Long("config"))
+                .action(ArgAction::Value)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath);
+            self.register(spec).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        }
+
+        // The last `--config` before the separator wins, like any other option
+        let mut given = None;
+        let mut tokens = args.iter().map(AsRef::as_ref).take_while(|&token| token != "--");
+        while let Some(token) = tokens.next() {
+            if token == "--config" {
+                given = tokens.next().map(PathBuf::from);
+            } else if let Some(path) = token.strip_prefix("--config=") {
+                given = Some(PathBuf::from(path));
+            }
+        }
+
+        match given.or_else(|| Self::config_path(app, file).filter(|path| path.is_file())) {
+            Some(path) => self.config_file(path),
+            None => Ok(self),
+        }
+    }
+
+    /// Sets the order in which the sources of option values are consulted, the first source
+    /// providing a value winning. Sources left out are never consulted.
+    ///
+    /// By default, the command line beats the environment variable, which beats the
+    /// configuration, which beats the default value. Some deployments want the environment
+    /// to have the final say instead. A definition can override the order with
+    /// [`ArgSpec::precedence`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use argsparse::{ArgDef, ArgSpec, Matches, ParserContext, SourceKind, ValueSource};
+    /// let port = ArgDef::Long("port");
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(port).env("MYAPP_DOC_PRECEDENCE_PORT")).unwrap();
+    /// ctx.precedence(&[SourceKind::Env, SourceKind::CommandLine]);
+    ///
+    /// unsafe { std::env::set_var("MYAPP_DOC_PRECEDENCE_PORT", "3000") };
+    /// let matches = Matches::parse(&["--port", "80"], &ctx).unwrap();
+    ///
+    /// let (value, source) = matches.value_with_source(port).unwrap();
+    /// assert_eq!(value, "3000");
+    /// assert_eq!(source, ValueSource::Env("MYAPP_DOC_PRECEDENCE_PORT"));
+    /// ```
+    ///
+    /// [`ArgSpec::precedence`]: crate::ArgSpec::precedence
+    pub fn precedence(&mut self, order: &'a [SourceKind]) -> &mut Self {
+        self.precedence = order;
+        self
+    }
+
+    /// Returns the order in which the sources of the value of `spec` are consulted.
+    pub(crate) fn precedence_of<'s>(&'s self, spec: &'s ArgSpec) -> &'s [SourceKind] {
+        spec.precedence.unwrap_or(self.precedence)
+    }
+
+    /// Returns the value `spec` takes when it is absent from the command line, along with
+    /// where it comes from, following the precedence of its sources.
+    pub(crate) fn fallback<'s>(&'s self, spec: &'s ArgSpec) -> Option<(String, ValueSource<'s>)> {
+        self.precedence_of(spec).iter().find_map(|&kind| self.value_from(spec, kind))
+    }
+
+    /// Returns the value `spec` takes from the source `kind`, other than the command line.
+    pub(crate) fn value_from<'s>(
+        &'s self,
+        spec: &'s ArgSpec,
+        kind: SourceKind,
+    ) -> Option<(String, ValueSource<'s>)> {
+        match kind {
+            SourceKind::CommandLine => None,
+            SourceKind::Env => {
+                let name = spec.env?;
+                let value = env::var(name).ok();
+                #[cfg(feature = "dotenv")]
+                let value = value.or_else(|| self.dotenv_var(name));
+                value.map(|value| (value, ValueSource::Env(name)))
+            }
+            SourceKind::Config => {
+                let long = spec.def.long()?;
+                self.configs.iter().rev().find_map(|(path, values)| {
+                    let (_, value) = values.iter().rev().find(|(key, _)| key == long)?;
+                    Some((value.clone(), ValueSource::ConfigFile(path.as_path())))
+                })
+            }
+            SourceKind::Default => {
+                spec.default.map(|value| (value.to_string(), ValueSource::Default))
+            }
+        }
+    }
+}
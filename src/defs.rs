@@ -1,5 +1,14 @@
+use crate::messages::Message;
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
 /// Defines the expected arguments the parser can recognize.
-#[derive(Debug, PartialEq, Copy, Clone)]
+///
+/// Definitions can be used as keys of a `HashMap` or a `BTreeMap`. They are ordered by
+/// variant (short, long, then both), and then by name.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone)]
 pub enum ArgDef<'a> {
     /// A short argument definition (e.g., `-h`).
     Short(char),
@@ -14,22 +23,93 @@ pub enum ArgDef<'a> {
     },
 }
 
+/// An [`ArgDef`] that owns its long name, for definitions only known at run time, such as
+/// the options of a plugin read from its manifest.
+///
+/// A context borrows the names of its definitions, so keep owned definitions alive as long
+/// as the context and pass it the views returned by [`as_def`]. See [`OwnedArgSpec`] for
+/// an example.
+///
+/// [`as_def`]: OwnedArgDef::as_def
+/// [`OwnedArgSpec`]: crate::OwnedArgSpec
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
+pub enum OwnedArgDef {
+    /// A short argument definition (e.g., `-h`).
+    Short(char),
+    /// A long argument definition (e.g., `--help`).
+    Long(String),
+    /// Defines both a short and long version of an argument.
+    ShortAndLong {
+        /// The short character.
+        short: char,
+        /// The long name.
+        long: String,
+    },
+}
+
+impl OwnedArgDef {
+    /// Returns the definition borrowing the name owned here.
+    pub fn as_def(&self) -> ArgDef<'_> {
+        match self {
+            OwnedArgDef::Short(short) => ArgDef::Short(*short),
+            OwnedArgDef::Long(long) => ArgDef::Long(long),
+            OwnedArgDef::ShortAndLong { short, long } => {
+                ArgDef::ShortAndLong { short: *short, long }
+            }
+        }
+    }
+}
+
+impl From<ArgDef<'_>> for OwnedArgDef {
+    fn from(def: ArgDef<'_>) -> Self {
+        match def {
+            ArgDef::Short(short) => OwnedArgDef::Short(short),
+            ArgDef::Long(long) => OwnedArgDef::Long(long.to_string()),
+            ArgDef::ShortAndLong { short, long } => {
+                OwnedArgDef::ShortAndLong { short, long: long.to_string() }
+            }
+        }
+    }
+}
+
 /// Represents the name of an argument, used for identification and matching.
-#[derive(Debug, PartialEq, Copy, Clone)]
+///
+/// Long names borrow from the command line when possible, but can also be owned, so
+/// arguments injected after parsing don't need to outlive the original input.
+///
+/// Names can be used as keys of a `HashMap` or a `BTreeMap`. Short names come before long
+/// ones, and each kind is ordered by code point, whether the name is borrowed or owned.
+///
+/// # Examples
+///
+/// ```
+/// # use argsparse::{ArgName, Args};
+/// use std::collections::BTreeSet;
+///
+/// let args = Args::parse_all(&["--verbose", "-x", "--all", "-x"]).unwrap();
+/// let names: BTreeSet<_> = args.iter().filter_map(|arg| arg.name()).collect();
+///
+/// let long = |name: &'static str| ArgName::Long(name.into());
+/// assert!(names.iter().copied().eq(&[ArgName::Short('x'), long("all"), long("verbose")]));
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
 pub enum ArgName<'a> {
     /// A short name, e.g., `-h`.
     Short(char),
     /// A long name, e.g., `--help`.
-    Long(&'a str),
+    Long(Cow<'a, str>),
 }
 
 /// A parsed command-line argument.
-#[derive(Debug, PartialEq, Copy, Clone)]
+///
+/// Values are stored as [`Cow`] strings: they borrow from the command line when parsed,
+/// and can be owned when produced by a later transformation (e.g., a default value).
+#[derive(Debug, PartialEq, Clone)]
 pub enum Argument<'a> {
     /// A raw positional value, e.g., a file path or input string.
     Positional {
         /// The string value.
-        value: &'a str,
+        value: Cow<'a, str>,
     },
     /// A flag that was present, e.g., `--verbose`.
     Flag {
@@ -41,7 +121,178 @@ pub enum Argument<'a> {
         /// The name of the option.
         name: ArgName<'a>,
         /// The associated value.
-        value: &'a str,
+        value: Cow<'a, str>,
+    },
+}
+
+/// Two definitions that claim the same short or long name.
+///
+/// Returned when building a [`ParserContext`] from a list of definitions in which a
+/// later definition collides with an earlier one.
+///
+/// [`ParserContext`]: crate::ParserContext
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct DefinitionConflict<'a> {
+    /// The definition that was registered first.
+    pub existing: ArgDef<'a>,
+    /// The definition that tried to reuse one of its names.
+    pub conflicting: ArgDef<'a>,
+}
+
+/// Represents possible errors when registering a definition with a [`ParserContext`].
+///
+/// [`ParserContext`]: crate::ParserContext
+#[derive(Debug, PartialEq, Clone)]
+pub enum RegisterError {
+    /// The short name is already claimed by another definition.
+    DuplicateShort(char),
+    /// The long name is already claimed by another definition.
+    DuplicateLong(String),
+    /// The short name cannot be typed as a standalone character (e.g., a combining mark,
+    /// whitespace, `-` or `=`).
+    InvalidShort(char),
+    /// The alias would expand into itself, directly or through other aliases.
+    RecursiveAlias(String),
+    /// The alias would expand through more than 16 nested aliases.
+    AliasTooDeep(String),
+    /// The positional would follow a variadic one, which captures every operand before it.
+    PositionalAfterVariadic(String),
+}
+
+/// A definition spec string that could not be parsed.
+///
+/// Returned by [`ArgDef::parse`] and [`ArgSpec::parse`].
+///
+/// [`ArgSpec::parse`]: crate::ArgSpec::parse
+#[derive(Debug, PartialEq, Clone)]
+pub struct SpecError {
+    /// The spec string.
+    pub spec: String,
+    /// What is wrong with it.
+    pub reason: &'static str,
+}
+
+/// A value that could not be converted into the requested type.
+///
+/// Produced by the typed accessors, such as [`Args::positional`]. It keeps everything
+/// needed for a helpful message: which argument the value belonged to, the raw value, the
+/// type that was expected and why the conversion failed.
+///
+/// [`Args::positional`]: crate::Args::positional
+#[derive(Debug, PartialEq, Clone)]
+pub struct ValueError {
+    /// The argument the value belonged to, as shown to users (e.g., `--port` or `<SRC>`).
+    pub arg: String,
+    /// The raw value.
+    pub value: String,
+    /// The name of the type the value was converted to.
+    pub expected: &'static str,
+    /// The reason reported by the conversion.
+    pub reason: String,
+}
+
+impl ValueError {
+    /// Builds the error for a failed `T::from_str(value)` of the argument `arg`.
+    pub(crate) fn new<T>(arg: impl Into<String>, value: &str, reason: impl fmt::Display) -> Self {
+        Self {
+            arg: arg.into(),
+            value: value.into(),
+            expected: std::any::type_name::<T>(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Converts `value`, the value of the argument `arg`, into type `T`.
+    pub(crate) fn parse<T>(arg: impl Into<String>, value: &str) -> Result<T, ValueError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        value.parse().map_err(|err| ValueError::new::<T>(arg, value, err))
+    }
+}
+
+impl ValueError {
+    /// Returns the message describing the error.
+    pub(crate) fn message(&self) -> Message<'_> {
+        Message::ConversionFailed {
+            arg: &self.arg,
+            value: &self.value,
+            expected: self.expected,
+            reason: &self.reason,
+        }
+    }
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl Error for ValueError {}
+
+/// The kind of a parsing or conversion error, used to pick its message template.
+///
+/// See [`ParserContext::error_template`].
+///
+/// [`ParserContext::error_template`]: crate::ParserContext::error_template
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// [`ParseArgError::MalformedArg`].
+    MalformedArg,
+    /// [`ParseArgError::UnknownLong`] and [`ParseArgError::UnknownShort`].
+    UnknownOption,
+    /// [`ParseArgError::InvalidShort`].
+    InvalidShort,
+    /// [`ParseArgError::MissingRequired`].
+    MissingRequired,
+    /// [`ParseArgError::MissingPositional`].
+    MissingPositional,
+    /// [`ParseArgError::UnexpectedPositional`].
+    UnexpectedPositional,
+    /// [`ParseArgError::InvalidValue`].
+    InvalidValue,
+    /// [`ParseArgError::RejectedValue`].
+    RejectedValue,
+    /// [`ParseArgError::RemovedArg`].
+    RemovedArg,
+    /// [`ParseArgError::OccurrenceCount`].
+    OccurrenceCount,
+    /// [`ParseArgError::LimitsExceeded`].
+    LimitsExceeded,
+    /// [`ParseArgError::ControlCharacter`].
+    ControlCharacter,
+    /// [`ParseArgError::ShortCluster`].
+    ShortCluster,
+    /// [`ParseArgError::SingleDashLong`].
+    SingleDashLong,
+    /// [`ParseArgError::MissingValue`].
+    MissingValue,
+    /// A [`ValueError`].
+    Conversion,
+}
+
+/// A resource limit of a context, exceeded by the command line.
+///
+/// See [`ParserContext::limits`].
+///
+/// [`ParserContext::limits`]: crate::ParserContext::limits
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub enum Limit {
+    /// The number of tokens on the command line.
+    Tokens,
+    /// The length in bytes of a single token.
+    TokenLength {
+        /// The position of the token among the parsed arguments, starting at 0.
+        index: usize,
+    },
+    /// The number of values given to a single option.
+    Values {
+        /// The option, as typed the time it went over the limit (e.g., `--include`).
+        arg: String,
     },
 }
 
@@ -49,15 +300,462 @@ pub enum Argument<'a> {
 #[derive(Debug)]
 pub enum ParseArgError<'a> {
     /// The argument is syntactically malformed or not valid.
-    MalformedArg(&'a str),
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, ParseArgError};
+    /// let err = Args::parse_all(&["build", "-"]).unwrap_err();
+    ///
+    /// assert!(matches!(err, ParseArgError::MalformedArg { index: 1, ref token } if token == "-"));
+    /// assert_eq!(err.to_string(), "malformed argument '-' at position 1");
+    /// ```
+    MalformedArg {
+        /// The position of the token among the parsed arguments, starting at 0.
+        index: usize,
+        /// The offending token.
+        token: Cow<'a, str>,
+    },
     /// The long argument name is not defined in the context.
     UnknownLong(String),
     /// The short argument name is not defined in the context.
     UnknownShort(String),
+    /// A short cluster contained a character made of several code points, such as a
+    /// letter followed by a combining accent, which cannot be a short name.
+    InvalidShort(String),
+    /// A definition marked as required did not appear on the command line.
+    MissingRequired(String),
+    /// A required positional, named by its definition, was not supplied.
+    MissingPositional(String),
+    /// A positional was supplied beyond the ones the context defines.
+    UnexpectedPositional(String),
+    /// A value outside the possible values of its definition.
+    InvalidValue {
+        /// The argument the value was given to, as typed (e.g., `--color`).
+        arg: String,
+        /// The rejected value.
+        value: String,
+        /// The values the definition accepts.
+        possible: Vec<String>,
+    },
+    /// An argument removed in the current version of the program was given.
+    RemovedArg {
+        /// The argument, as typed (e.g., `--legacy`).
+        arg: String,
+        /// The version that removed it.
+        version: String,
+    },
+    /// A value rejected by the range or the validator of its definition.
+    RejectedValue {
+        /// The argument the value was given to, as typed (e.g., `--port`).
+        arg: String,
+        /// The rejected value.
+        value: String,
+        /// Why the value was rejected.
+        reason: String,
+    },
+    /// An argument given fewer or more times than its definition allows.
+    OccurrenceCount {
+        /// The argument, named by its definition (e.g., `--verbose`).
+        arg: String,
+        /// The number of times it was given.
+        count: usize,
+        /// The number of times it must be given at least.
+        min: usize,
+        /// The number of times it may be given at most, if limited.
+        max: Option<usize>,
+    },
+    /// The command line exceeded a resource limit of the context.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, Limit, Limits, ParseArgError, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.limits(Limits::new().max_tokens(2));
+    ///
+    /// let err = Args::parse_with_context(&["a", "b", "c"], &ctx).unwrap_err();
+    /// assert!(matches!(err, ParseArgError::LimitsExceeded { limit: Limit::Tokens, max: 2 }));
+    /// assert_eq!(err.to_string(), "too many arguments: at most 2 are allowed");
+    /// ```
+    LimitsExceeded {
+        /// The limit that was exceeded.
+        limit: Limit,
+        /// The value of the limit.
+        max: usize,
+    },
+    /// A token contained a NUL byte or another C0 control character, with
+    /// [`ParserContext::reject_control_chars`] on.
+    ///
+    /// [`ParserContext::reject_control_chars`]: crate::ParserContext::reject_control_chars
+    ControlCharacter {
+        /// The position of the token among the parsed arguments, starting at 0.
+        index: usize,
+        /// The first control character of the token.
+        character: char,
+    },
+    /// Several short names were combined in one token in a way the [`Clustering`] of the
+    /// context does not allow.
+    ///
+    /// [`Clustering`]: crate::Clustering
+    ShortCluster {
+        /// The position of the token among the parsed arguments, starting at 0.
+        index: usize,
+        /// The offending token.
+        token: Cow<'a, str>,
+    },
+    /// A long name was given with a single dash (e.g., `-verbose`), which makes no sense as
+    /// a cluster of short names.
+    ///
+    /// See [`ParserContext::correct_single_dash_longs`] to take it as the long name instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, ParseArgError, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose");
+    ///
+    /// let err = Args::parse_with_context(&["-verbose"], &ctx).unwrap_err();
+    /// assert!(matches!(err, ParseArgError::SingleDashLong { index: 0, .. }));
+    /// assert_eq!(err.to_string(), "unknown option '-verbose', did you mean '--verbose'?");
+    /// ```
+    ///
+    /// [`ParserContext::correct_single_dash_longs`]:
+    ///     crate::ParserContext::correct_single_dash_longs
+    SingleDashLong {
+        /// The position of the token among the parsed arguments, starting at 0.
+        index: usize,
+        /// The long name, without dashes.
+        long: String,
+    },
+    /// An option with [`ArgAction::Value`] was given last, or right before another option,
+    /// without a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Args, ParseArgError, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose");
+    /// ctx.register(ArgSpec::new(ArgDef::ShortAndLong { short: 'o', long: "output" })
+    ///     .action(ArgAction::Value)
+    ///     .value_name("FILE"))
+    ///     .unwrap();
+    ///
+    /// let err = Args::parse_with_context(&["--output", "-v"], &ctx).unwrap_err();
+    /// assert!(matches!(err, ParseArgError::MissingValue { index: 0, .. }));
+    /// assert_eq!(err.to_string(), "missing value for '--output': expected <FILE>");
+    ///
+    /// let err = Args::parse_with_context(&["-v", "-o"], &ctx).unwrap_err();
+    /// assert_eq!(err.to_string(), "missing value for '-o': expected <FILE>");
+    /// ```
+    ///
+    /// [`ArgAction::Value`]: crate::ArgAction::Value
+    MissingValue {
+        /// The position of the token among the parsed arguments, starting at 0.
+        index: usize,
+        /// The option, as typed (e.g., `--output`).
+        arg: String,
+        /// The placeholder of the value expected, without its brackets (e.g., `FILE`).
+        expected: String,
+    },
 }
 
 
+impl fmt::Display for ArgDef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgDef::Short(s) => write!(f, "-{}", s),
+            ArgDef::Long(l) => write!(f, "--{}", l),
+            ArgDef::ShortAndLong { short, long } => write!(f, "-{}, --{}", short, long),
+        }
+    }
+}
+
+impl fmt::Display for DefinitionConflict<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "argument `{}` conflicts with previously defined `{}`",
+            self.conflicting, self.existing
+        )
+    }
+}
+
+impl Error for DefinitionConflict<'_> {}
+
+impl ParseArgError<'_> {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ParseArgError::MalformedArg { .. } => ErrorKind::MalformedArg,
+            ParseArgError::UnknownLong(_) | ParseArgError::UnknownShort(_) => {
+                ErrorKind::UnknownOption
+            }
+            ParseArgError::InvalidShort(_) => ErrorKind::InvalidShort,
+            ParseArgError::MissingRequired(_) => ErrorKind::MissingRequired,
+            ParseArgError::MissingPositional(_) => ErrorKind::MissingPositional,
+            ParseArgError::UnexpectedPositional(_) => ErrorKind::UnexpectedPositional,
+            ParseArgError::InvalidValue { .. } => ErrorKind::InvalidValue,
+            ParseArgError::RejectedValue { .. } => ErrorKind::RejectedValue,
+            ParseArgError::RemovedArg { .. } => ErrorKind::RemovedArg,
+            ParseArgError::OccurrenceCount { .. } => ErrorKind::OccurrenceCount,
+            ParseArgError::LimitsExceeded { .. } => ErrorKind::LimitsExceeded,
+            ParseArgError::ControlCharacter { .. } => ErrorKind::ControlCharacter,
+            ParseArgError::ShortCluster { .. } => ErrorKind::ShortCluster,
+            ParseArgError::SingleDashLong { .. } => ErrorKind::SingleDashLong,
+            ParseArgError::MissingValue { .. } => ErrorKind::MissingValue,
+        }
+    }
+
+    /// Returns the position among `args`, the parsed arguments, of the token at fault, or
+    /// `None` if the error is not about a token, such as a missing argument.
+    ///
+    /// - [`MalformedArg`], [`ControlCharacter`], [`ShortCluster`], [`SingleDashLong`],
+    ///   [`MissingValue`] and [`LimitsExceeded`] for a token too long record where they
+    ///   occurred.
+    /// - [`UnknownLong`], [`UnknownShort`], [`InvalidShort`], [`UnexpectedPositional`],
+    ///   [`RemovedArg`], [`InvalidValue`] and [`RejectedValue`] are looked up in `args`: the
+    ///   first token that spells the argument or value at fault.
+    /// - [`OccurrenceCount`] for too many occurrences and [`LimitsExceeded`] for too many
+    ///   values are looked up the same way, from the last token.
+    /// - [`LimitsExceeded`] for too many tokens points at the first token beyond the limit.
+    /// - [`MissingRequired`], [`MissingPositional`] and [`OccurrenceCount`] for too few
+    ///   occurrences return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose");
+    ///
+    /// let args = ["-v", "in.txt", "--verbos"];
+    /// let err = Args::parse_with_context(&args, &ctx).unwrap_err();
+    /// assert_eq!(err.position(&args), Some(2));
+    /// ```
+    ///
+    /// [`MalformedArg`]: ParseArgError::MalformedArg
+    /// [`ControlCharacter`]: ParseArgError::ControlCharacter
+    /// [`ShortCluster`]: ParseArgError::ShortCluster
+    /// [`SingleDashLong`]: ParseArgError::SingleDashLong
+    /// [`MissingValue`]: ParseArgError::MissingValue
+    /// [`LimitsExceeded`]: ParseArgError::LimitsExceeded
+    /// [`UnknownLong`]: ParseArgError::UnknownLong
+    /// [`UnknownShort`]: ParseArgError::UnknownShort
+    /// [`InvalidShort`]: ParseArgError::InvalidShort
+    /// [`UnexpectedPositional`]: ParseArgError::UnexpectedPositional
+    /// [`RemovedArg`]: ParseArgError::RemovedArg
+    /// [`InvalidValue`]: ParseArgError::InvalidValue
+    /// [`RejectedValue`]: ParseArgError::RejectedValue
+    /// [`OccurrenceCount`]: ParseArgError::OccurrenceCount
+    /// [`MissingRequired`]: ParseArgError::MissingRequired
+    /// [`MissingPositional`]: ParseArgError::MissingPositional
+    pub fn position<S: AsRef<str>>(&self, args: &[S]) -> Option<usize> {
+        let spells = |token: &str, arg: &str| {
+            token == arg || token.strip_prefix(arg).is_some_and(|rest| rest.starts_with('='))
+        };
+        let short = |token: &str, name: &str| {
+            !token.starts_with("--") && token.strip_prefix('-').is_some_and(|s| s.contains(name))
+        };
+        let tokens = args.iter().map(AsRef::as_ref);
+        match self {
+            ParseArgError::MalformedArg { index, .. }
+            | ParseArgError::ControlCharacter { index, .. }
+            | ParseArgError::ShortCluster { index, .. }
+            | ParseArgError::SingleDashLong { index, .. }
+            | ParseArgError::MissingValue { index, .. } => Some(*index),
+            ParseArgError::UnknownLong(name) => {
+                tokens.into_iter().position(|token| spells(token, &format!("--{}", name)))
+            }
+            ParseArgError::UnknownShort(name) | ParseArgError::InvalidShort(name) => {
+                tokens.into_iter().position(|token| short(token, name))
+            }
+            ParseArgError::UnexpectedPositional(value) => {
+                tokens.into_iter().position(|token| token == value)
+            }
+            ParseArgError::RemovedArg { arg, .. } => {
+                let name = arg.trim_start_matches('-');
+                tokens.into_iter().position(|token| match arg.starts_with("--") {
+                    true => spells(token, arg),
+                    false => short(token, name),
+                })
+            }
+            ParseArgError::InvalidValue { arg, value, .. }
+            | ParseArgError::RejectedValue { arg, value, .. } => {
+                // The argument, or the end of a short cluster, right before the value
+                let ends_with_arg = |head: &str| match arg.strip_prefix("--") {
+                    Some(_) => head == arg,
+                    None => short(head, "") && head.ends_with(&arg[1..]),
+                };
+                let tokens: Vec<&str> = tokens.collect();
+                tokens.iter().enumerate().position(|(index, token)| {
+                    let attached = token.strip_suffix(value.as_str()).is_some_and(|head| {
+                        let head = head.strip_suffix('=').unwrap_or(head);
+                        ends_with_arg(head)
+                    });
+                    attached || token == value && index > 0 && ends_with_arg(tokens[index - 1])
+                })
+            }
+            // The last occurrence is the one too many
+            ParseArgError::OccurrenceCount { arg, count, .. } if *count > 0 => {
+                tokens.collect::<Vec<_>>().iter().rposition(|token| spells(token, arg))
+            }
+            // The first token beyond the limit
+            ParseArgError::LimitsExceeded { limit: Limit::Tokens, max } => {
+                Some(*max).filter(|&max| max < tokens.len())
+            }
+            ParseArgError::LimitsExceeded { limit: Limit::TokenLength { index }, .. } => {
+                Some(*index)
+            }
+            ParseArgError::LimitsExceeded { limit: Limit::Values { arg }, .. } => {
+                tokens.collect::<Vec<_>>().iter().rposition(|token| spells(token, arg))
+            }
+            ParseArgError::MissingRequired(_)
+            | ParseArgError::MissingPositional(_)
+            | ParseArgError::OccurrenceCount { .. } => None,
+        }
+    }
+
+    /// Calls `f` with the message describing the error.
+    pub(crate) fn with_message<R>(&self, f: impl FnOnce(&Message) -> R) -> R {
+        match self {
+            ParseArgError::MalformedArg { index, token } => {
+                f(&Message::MalformedArg { index: *index, token })
+            }
+            ParseArgError::UnknownLong(name) => {
+                f(&Message::UnknownOption(&format!("--{}", name)))
+            }
+            ParseArgError::UnknownShort(name) => f(&Message::UnknownOption(&format!("-{}", name))),
+            ParseArgError::InvalidShort(name) => f(&Message::InvalidShort(name)),
+            ParseArgError::MissingRequired(name) => f(&Message::MissingRequired(name)),
+            ParseArgError::MissingPositional(name) => f(&Message::MissingPositional(name)),
+            ParseArgError::UnexpectedPositional(value) => f(&Message::UnexpectedPositional(value)),
+            ParseArgError::InvalidValue { arg, value, possible } => {
+                f(&Message::InvalidValue { arg, value, possible })
+            }
+            ParseArgError::RejectedValue { arg, value, reason } => {
+                f(&Message::RejectedValue { arg, value, reason })
+            }
+            ParseArgError::RemovedArg { arg, version } => {
+                f(&Message::RemovedArg { arg, version })
+            }
+            ParseArgError::OccurrenceCount { arg, count, min, max } => {
+                f(&Message::OccurrenceCount { arg, count: *count, min: *min, max: *max })
+            }
+            ParseArgError::LimitsExceeded { limit, max } => {
+                f(&Message::LimitsExceeded { limit, max: *max })
+            }
+            ParseArgError::ControlCharacter { index, character } => {
+                f(&Message::ControlCharacter { index: *index, character: *character })
+            }
+            ParseArgError::ShortCluster { index, token } => {
+                f(&Message::ShortCluster { index: *index, token })
+            }
+            ParseArgError::MissingValue { arg, expected, .. } => {
+                f(&Message::MissingValue { arg, expected })
+            }
+            ParseArgError::SingleDashLong { long, .. } => f(&Message::SingleDashLong {
+                arg: &format!("-{}", long),
+                long: &format!("--{}", long),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for ParseArgError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.with_message(|message| write!(f, "{}", message))
+    }
+}
+
+impl Error for ParseArgError<'_> {}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid argument spec '{}': {}", self.spec, self.reason)
+    }
+}
+
+impl Error for SpecError {}
+
+impl fmt::Display for RegisterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegisterError::DuplicateShort(s) => write!(f, "Short argument -{} already defined", s),
+            RegisterError::DuplicateLong(l) => write!(f, "Long argument --{} already defined", l),
+            RegisterError::InvalidShort(s) => {
+                write!(f, "Short argument {:?} is not a standalone character", s)
+            }
+            RegisterError::RecursiveAlias(name) => write!(f, "Alias {} expands into itself", name),
+            RegisterError::AliasTooDeep(name) => {
+                write!(f, "Alias {} expands through too many nested aliases", name)
+            }
+            RegisterError::PositionalAfterVariadic(name) => {
+                write!(f, "Positional {} follows a variadic positional", name)
+            }
+        }
+    }
+}
+
+impl Error for RegisterError {}
+
 impl<'a> ArgDef<'a> {
+    /// Parses a definition from its spec string, as written in help output: a short name, a
+    /// long name, or both separated by a comma or whitespace.
+    ///
+    /// # Example
+    /// ```
+    /// # use argsparse::ArgDef;
+    /// let verbose = ArgDef::ShortAndLong { short: 'v', long: "verbose" };
+    /// assert_eq!(ArgDef::parse("-v, --verbose"), Ok(verbose));
+    /// assert_eq!(ArgDef::parse("--dry-run"), Ok(ArgDef::Long("dry-run")));
+    /// assert_eq!(ArgDef::parse("-q"), Ok(ArgDef::Short('q')));
+    ///
+    /// assert!(ArgDef::parse("verbose").is_err());
+    /// assert!(ArgDef::parse("-v, -q").is_err());
+    /// ```
+    pub fn parse(spec: &'a str) -> Result<ArgDef<'a>, SpecError> {
+        ArgDef::from_names(spec, spec.split([',', ' ', '\t']).filter(|name| !name.is_empty()))
+    }
+
+    /// Builds a definition from the names of a spec string, such as `-v` and `--verbose`.
+    pub(crate) fn from_names(
+        spec: &str,
+        names: impl Iterator<Item = &'a str>,
+    ) -> Result<ArgDef<'a>, SpecError> {
+        let error = |reason| SpecError { spec: spec.to_string(), reason };
+
+        let (mut short, mut long) = (None, None);
+        for name in names {
+            if let Some(name) = name.strip_prefix("--") {
+                if name.is_empty() || name.contains('=') {
+                    return Err(error("a long name must be non-empty and cannot contain '='"));
+                }
+                if long.replace(name).is_some() {
+                    return Err(error("only one long name is allowed"));
+                }
+            } else if let Some(name) = name.strip_prefix('-') {
+                let mut chars = name.chars();
+                let (Some(c), None) = (chars.next(), chars.next()) else {
+                    return Err(error("a short name must be a single character"));
+                };
+                if short.replace(c).is_some() {
+                    return Err(error("only one short name is allowed"));
+                }
+            } else {
+                return Err(error("names must start with '-' or '--'"));
+            }
+        }
+
+        match (short, long) {
+            (Some(short), Some(long)) => Ok(ArgDef::ShortAndLong { short, long }),
+            (Some(short), None) => Ok(ArgDef::Short(short)),
+            (None, Some(long)) => Ok(ArgDef::Long(long)),
+            (None, None) => Err(error("no name was given")),
+        }
+    }
 
     /// Returns `true` if the `ArgDef` matches the given `ArgName`.
     ///
@@ -76,6 +774,105 @@ impl<'a> ArgDef<'a> {
             _ => false,
         }
     }
+
+    /// Returns the short name of the definition, if it has one.
+    ///
+    /// # Example
+    /// ```
+    /// # use argsparse::ArgDef;
+    /// assert_eq!(ArgDef::ShortAndLong { short: 'v', long: "verbose" }.short(), Some('v'));
+    /// assert_eq!(ArgDef::Long("verbose").short(), None);
+    /// ```
+    pub fn short(&self) -> Option<char> {
+        match *self {
+            ArgDef::Short(s) | ArgDef::ShortAndLong { short: s, .. } => Some(s),
+            ArgDef::Long(_) => None,
+        }
+    }
+
+    /// Returns the long name of the definition, if it has one.
+    ///
+    /// # Example
+    /// ```
+    /// # use argsparse::ArgDef;
+    /// assert_eq!(ArgDef::ShortAndLong { short: 'v', long: "verbose" }.long(), Some("verbose"));
+    /// assert_eq!(ArgDef::Short('v').long(), None);
+    /// ```
+    pub fn long(&self) -> Option<&'a str> {
+        match *self {
+            ArgDef::Long(l) | ArgDef::ShortAndLong { long: l, .. } => Some(l),
+            ArgDef::Short(_) => None,
+        }
+    }
+
+    /// Returns `true` if both definitions claim at least one common short or long name.
+    pub(crate) fn overlaps(&self, other: &ArgDef<'a>) -> bool {
+        match *other {
+            ArgDef::Short(s) => self.matches(&ArgName::Short(s)),
+            ArgDef::Long(l) => self.matches(&ArgName::Long(l.into())),
+            ArgDef::ShortAndLong { short, long } => {
+                self.matches(&ArgName::Short(short)) || self.matches(&ArgName::Long(long.into()))
+            }
+        }
+    }
+}
+
+impl ParseArgError<'_> {
+    /// Converts the error into one that owns all of its strings.
+    pub fn into_owned(self) -> ParseArgError<'static> {
+        match self {
+            ParseArgError::MalformedArg { index, token } => ParseArgError::MalformedArg {
+                index,
+                token: Cow::Owned(token.into_owned()),
+            },
+            ParseArgError::UnknownLong(name) => ParseArgError::UnknownLong(name),
+            ParseArgError::UnknownShort(name) => ParseArgError::UnknownShort(name),
+            ParseArgError::InvalidShort(name) => ParseArgError::InvalidShort(name),
+            ParseArgError::MissingRequired(name) => ParseArgError::MissingRequired(name),
+            ParseArgError::MissingPositional(name) => ParseArgError::MissingPositional(name),
+            ParseArgError::UnexpectedPositional(value) => {
+                ParseArgError::UnexpectedPositional(value)
+            }
+            ParseArgError::InvalidValue { arg, value, possible } => {
+                ParseArgError::InvalidValue { arg, value, possible }
+            }
+            ParseArgError::RejectedValue { arg, value, reason } => {
+                ParseArgError::RejectedValue { arg, value, reason }
+            }
+            ParseArgError::RemovedArg { arg, version } => {
+                ParseArgError::RemovedArg { arg, version }
+            }
+            ParseArgError::OccurrenceCount { arg, count, min, max } => {
+                ParseArgError::OccurrenceCount { arg, count, min, max }
+            }
+            ParseArgError::LimitsExceeded { limit, max } => {
+                ParseArgError::LimitsExceeded { limit, max }
+            }
+            ParseArgError::ControlCharacter { index, character } => {
+                ParseArgError::ControlCharacter { index, character }
+            }
+            ParseArgError::ShortCluster { index, token } => ParseArgError::ShortCluster {
+                index,
+                token: Cow::Owned(token.into_owned()),
+            },
+            ParseArgError::SingleDashLong { index, long } => {
+                ParseArgError::SingleDashLong { index, long }
+            }
+            ParseArgError::MissingValue { index, arg, expected } => {
+                ParseArgError::MissingValue { index, arg, expected }
+            }
+        }
+    }
+}
+
+impl ArgName<'_> {
+    /// Converts the name into one that owns its string.
+    pub fn into_owned(self) -> ArgName<'static> {
+        match self {
+            ArgName::Short(s) => ArgName::Short(s),
+            ArgName::Long(l) => ArgName::Long(Cow::Owned(l.into_owned())),
+        }
+    }
 }
 
 impl<'a> Argument<'a> {
@@ -89,11 +886,38 @@ impl<'a> Argument<'a> {
     ///
     /// use argsparse::{ArgName, Argument};
     /// let arg = Argument::Flag { name: ArgName::Short('v') };
-    /// assert_eq!(arg.name(), Some(ArgName::Short('v')));
-    pub fn name(&self) -> Option<ArgName> {
+    /// assert_eq!(arg.name(), Some(&ArgName::Short('v')));
+    pub fn name(&self) -> Option<&ArgName<'a>> {
         match self {
-            Argument::Flag { name } | Argument::Option { name, .. } => { Some(*name) },
+            Argument::Flag { name } | Argument::Option { name, .. } => { Some(name) },
             _ => None,
         }
     }
+
+    /// Converts the argument into one that owns all of its strings.
+    ///
+    /// # Example
+    /// ```
+    /// # use argsparse::{ArgName, Argument};
+    /// let owned: Argument<'static> = {
+    ///     let input = String::from("out.txt");
+    ///     Argument::Option { name: ArgName::Short('o'), value: input.as_str().into() }.into_owned()
+    /// };
+    ///
+    /// assert_eq!(owned, Argument::Option { name: ArgName::Short('o'), value: "out.txt".into() });
+    /// ```
+    pub fn into_owned(self) -> Argument<'static> {
+        match self {
+            Argument::Positional { value } => Argument::Positional {
+                value: Cow::Owned(value.into_owned()),
+            },
+            Argument::Flag { name } => Argument::Flag {
+                name: name.into_owned(),
+            },
+            Argument::Option { name, value } => Argument::Option {
+                name: name.into_owned(),
+                value: Cow::Owned(value.into_owned()),
+            },
+        }
+    }
 }
@@ -0,0 +1,210 @@
+use crate::defs::{ArgDef, RegisterError};
+use crate::parser::ParserContext;
+use crate::spec::{ArgAction, ArgSpec, PositionalSpec};
+use std::error::Error;
+use std::fmt;
+
+/// A usage text that could not be turned into a [`ParserContext`].
+///
+/// [`ParserContext`]: crate::ParserContext
+#[derive(Debug, PartialEq, Clone)]
+pub enum DocoptError {
+    /// A line of the text could not be understood.
+    Syntax {
+        /// The line number, starting at 1.
+        line: usize,
+        /// What is wrong with the line.
+        reason: &'static str,
+    },
+    /// An option could not be registered, e.g. because its name is listed twice.
+    Register(RegisterError),
+}
+
+impl<'a> ParserContext<'a> {
+    /// Builds a context from a docopt-style usage text.
+    ///
+    /// Every line of the text starting with `-` is an option description: its names,
+    /// separated by commas or spaces, then an optional value placeholder (`--speed=<kn>`,
+    /// `-o FILE`), then a description after at least two spaces. A `[default: ...]` in the
+    /// description sets the default value. Options with a placeholder take a value, the
+    /// others are flags.
+    ///
+    /// The first pattern after `Usage:` defines the positionals: `<name>` or `NAME` is
+    /// required, optional inside brackets, and variadic when followed by `...`. Options in
+    /// the pattern are taken from their descriptions. Subcommands are not supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Matches, ParserContext};
+    /// const USAGE: &str = "
+    /// Usage:
+    ///   cp [options] <src> <dest>
+    ///
+    /// Options:
+    ///   -h, --help          Show this screen.
+    ///   -b --backup=<ext>   Suffix of backup files [default: ~].
+    ///   -v                  Explain what is being done.
+    /// ";
+    ///
+    /// let ctx = ParserContext::from_docopt(USAGE).unwrap();
+    /// let matches = Matches::parse(&["-v", "a.txt", "b.txt"], &ctx).unwrap();
+    ///
+    /// assert!(matches.has(ArgDef::Short('v')));
+    /// assert_eq!(matches.positional_values("dest"), ["b.txt"]);
+    /// assert_eq!(matches.value_of(ArgDef::Long("backup")).as_deref(), Some("~"));
+    ///
+    /// let ctx = ParserContext::from_docopt("Usage: cat [-n] [<file>]...").unwrap();
+    /// let matches = Matches::parse(&["a.txt", "b.txt"], &ctx).unwrap();
+    /// assert_eq!(matches.positional_values("file"), ["a.txt", "b.txt"]);
+    ///
+    /// assert!(ParserContext::from_docopt("Usage: git commit <msg>").is_err());
+    /// ```
+    pub fn from_docopt(doc: &'a str) -> Result<ParserContext<'a>, DocoptError> {
+        let mut ctx = ParserContext::new();
+        let mut usage = None;
+
+        let mut lines = doc.lines().enumerate().map(|(index, line)| (index + 1, line));
+        while let Some((number, line)) = lines.next() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('-') {
+                let spec = parse_option(trimmed)
+                    .map_err(|reason| DocoptError::Syntax { line: number, reason })?;
+                ctx.register(spec).map_err(DocoptError::Register)?;
+            } else if usage.is_none()
+                && let Some(rest) = strip_prefix_ignore_case(trimmed, "usage:")
+            {
+                // The first pattern is on the same line, or on the next one
+                usage = match rest.trim() {
+                    "" => lines.next().map(|(number, line)| (number, line.trim())),
+                    rest => Some((number, rest)),
+                };
+            }
+        }
+
+        if let Some((number, pattern)) = usage {
+            for spec in parse_pattern(pattern)
+                .map_err(|reason| DocoptError::Syntax { line: number, reason })?
+            {
+                ctx.positional(spec);
+            }
+        }
+
+        Ok(ctx)
+    }
+}
+
+/// Parses an option description such as `-o FILE, --output=FILE  Where to write.`
+fn parse_option(line: &str) -> Result<ArgSpec<'_>, &'static str> {
+    let (names, description) = match line.find("  ").or_else(|| line.find('\t')) {
+        Some(index) => (&line[..index], line[index..].trim()),
+        None => (line, ""),
+    };
+
+    let mut value_name = None;
+    let mut words = Vec::new();
+    for word in names.split([',', ' ']).filter(|word| !word.is_empty()) {
+        let (word, value) = match word.split_once('=') {
+            Some((word, value)) => (word, Some(value)),
+            None if !word.starts_with('-') => ("", Some(word)),
+            None => (word, None),
+        };
+        if let Some(value) = value {
+            value_name = Some(value.trim_start_matches('<').trim_end_matches('>'));
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+    }
+
+    let def = ArgDef::from_names(line, words.into_iter()).map_err(|err| err.reason)?;
+    let mut spec = ArgSpec::new(def).action(ArgAction::Flag);
+    if let Some(name) = value_name {
+        spec = spec.value_name(name).action(ArgAction::Value);
+    }
+
+    let help = match find_ignore_case(description, "[default:") {
+        Some(start) => {
+            let rest = &description[start + "[default:".len()..];
+            let end = rest.find(']').ok_or("the default value is missing its closing bracket")?;
+            spec = spec.default(rest[..end].trim());
+            description[..start].trim_end()
+        }
+        None => description,
+    };
+    if !help.is_empty() {
+        spec = spec.help(help);
+    }
+
+    Ok(spec)
+}
+
+/// Parses the positionals of a usage pattern such as `prog [options] <src>... [<dest>]`.
+fn parse_pattern(pattern: &str) -> Result<Vec<PositionalSpec<'_>>, &'static str> {
+    let mut specs = Vec::new();
+    let mut depth = 0usize;
+
+    // The first word is the program name
+    for word in pattern.split_whitespace().skip(1) {
+        let opening = word.chars().take_while(|&c| c == '[' || c == '(').count();
+        depth += word[..opening].matches('[').count();
+
+        // Either the name or its group may be repeated, e.g. `<file>...` or `[<file>]...`
+        let (rest, repeated) = match word[opening..].strip_suffix("...") {
+            Some(rest) => (rest, true),
+            None => (&word[opening..], false),
+        };
+        let name = rest.trim_end_matches([']', ')']);
+        let closing = &rest[name.len()..];
+        let (name, variadic) = match name.strip_suffix("...") {
+            Some(name) => (name, true),
+            None => (name, repeated),
+        };
+
+        let uppercase = |c: char| c.is_ascii_uppercase() || c == '_';
+        let positional = match name.strip_prefix('<').and_then(|name| name.strip_suffix('>')) {
+            Some(name) => Some(name),
+            None if !name.is_empty() && name.chars().all(uppercase) => Some(name),
+            None => None,
+        };
+
+        match positional {
+            Some(name) => {
+                let required = depth == 0;
+                specs.push(PositionalSpec::new(name).required(required).variadic(variadic));
+            }
+            None if name.starts_with('-') || name == "options" || name.is_empty() => {}
+            None if name == "|" => return Err("alternative patterns are not supported"),
+            None => return Err("subcommands are not supported"),
+        }
+
+        depth = depth.saturating_sub(closing.matches(']').count());
+    }
+
+    // Only the last positional may capture the remaining operands
+    if specs.iter().rev().skip(1).any(|spec| spec.variadic) {
+        return Err("only the last positional may be repeated");
+    }
+
+    Ok(specs)
+}
+
+fn strip_prefix_ignore_case<'t>(text: &'t str, prefix: &str) -> Option<&'t str> {
+    let head = text.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix).then(|| &text[prefix.len()..])
+}
+
+fn find_ignore_case(text: &str, needle: &str) -> Option<usize> {
+    text.to_ascii_lowercase().find(needle)
+}
+
+impl fmt::Display for DocoptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DocoptError::Syntax { line, reason } => write!(f, "line {}: {}", line, reason),
+            DocoptError::Register(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for DocoptError {}
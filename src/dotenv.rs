@@ -0,0 +1,95 @@
+use crate::parser::ParserContext;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+impl ParserContext<'_> {
+    /// Reads the `.env` file of the working directory, if there is one, as with
+    /// [`dotenv_file`].
+    ///
+    /// [`dotenv_file`]: ParserContext::dotenv_file
+    pub fn dotenv(&mut self) -> io::Result<&mut Self> {
+        if !Path::new(".env").exists() {
+            return Ok(self);
+        }
+        self.dotenv_file(".env")
+    }
+
+    /// Reads the variables of the `.env` file at `path`, for options to fall back to when
+    /// their environment variable is not set, so that local development picks up values
+    /// without exporting them.
+    ///
+    /// The environment itself always wins, and files read later take precedence over earlier
+    /// ones. Values found this way are reported as coming from the environment variable.
+    ///
+    /// The file holds one `NAME=value` pair per line, optionally preceded by `export`.
+    /// Blank lines and lines starting with `#` are skipped. Values may be wrapped in single
+    /// quotes, taken verbatim, or double quotes, in which `\n`, `\"` and `\\` are escapes.
+    /// Fails with [`io::ErrorKind::InvalidData`] on a line without `=`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ArgSpec, Matches, ParserContext};
+    /// let path = std::env::temp_dir().join("argsparse-doc.env");
+    /// std::fs::write(&path, "# Local settings\nexport MYAPP_DOC_DOTENV_PORT=\"8000\"\n").unwrap();
+    ///
+    /// let port = ArgDef::Long("port");
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(port).env("MYAPP_DOC_DOTENV_PORT").default("80")).unwrap();
+    /// ctx.dotenv_file(&path).unwrap();
+    ///
+    /// let matches = Matches::parse(&[], &ctx).unwrap();
+    /// assert_eq!(matches.value_of(port).as_deref(), Some("8000"));
+    /// # std::fs::remove_file(path).unwrap();
+    /// ```
+    pub fn dotenv_file(&mut self, path: impl AsRef<Path>) -> io::Result<&mut Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let Some((name, value)) = line.split_once('=') else {
+                let message = format!("{}:{}: expected `NAME=value`", path.display(), number + 1);
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+            };
+            self.dotenv.insert(0, (name.trim().to_string(), unquote(value.trim())));
+        }
+        Ok(self)
+    }
+
+    /// Returns the value of the variable `name` read from `.env` files, if any.
+    pub(crate) fn dotenv_var(&self, name: &str) -> Option<String> {
+        self.dotenv.iter().find(|(var, _)| var == name).map(|(_, value)| value.clone())
+    }
+}
+
+/// Removes the quotes around a `.env` value, expanding the escapes of double quotes.
+fn unquote(value: &str) -> String {
+    if let Some(value) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        return value.to_string();
+    }
+    let Some(value) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) else {
+        // Unquoted values end at a comment
+        return value.split(" #").next().unwrap_or_default().trim_end().to_string();
+    };
+
+    let mut out = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => out.push('\n'),
+            ('\\', Some(escaped @ ('"' | '\\'))) => out.push(escaped),
+            _ => {
+                out.push(c);
+                continue;
+            }
+        }
+        chars.next();
+    }
+    out
+}
@@ -0,0 +1,226 @@
+use crate::defs::{Argument, ParseArgError};
+use crate::messages::Message;
+use crate::parser::{ParseIter, ParserContext};
+use std::error::Error;
+use std::fmt;
+
+/// A boolean expression of predicate arguments, in the style of find(1) and test(1).
+///
+/// Built by [`ParserContext::parse_expr`].
+///
+/// [`ParserContext::parse_expr`]: crate::ParserContext::parse_expr
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expr<'a> {
+    /// A single argument, e.g. `-name '*.rs'`.
+    Predicate(Argument<'a>),
+    /// The negation of an expression, written `-not expr` or `! expr`.
+    Not(Box<Expr<'a>>),
+    /// Both expressions, written `expr -and expr`, `expr -a expr` or `expr expr`.
+    And(Box<Expr<'a>>, Box<Expr<'a>>),
+    /// Either expression, written `expr -or expr` or `expr -o expr`.
+    Or(Box<Expr<'a>>, Box<Expr<'a>>),
+}
+
+impl<'a> Expr<'a> {
+    /// Evaluates the expression, deciding each predicate with `test`.
+    ///
+    /// As in find(1), `-and` and `-or` short-circuit: the right-hand side is only
+    /// evaluated when the left-hand side does not decide the result.
+    pub fn eval<F>(&self, test: &mut F) -> bool
+    where
+        F: FnMut(&Argument<'a>) -> bool,
+    {
+        match self {
+            Expr::Predicate(arg) => test(arg),
+            Expr::Not(expr) => !expr.eval(test),
+            Expr::And(lhs, rhs) => lhs.eval(test) && rhs.eval(test),
+            Expr::Or(lhs, rhs) => lhs.eval(test) || rhs.eval(test),
+        }
+    }
+}
+
+/// An error raised while parsing an expression with [`ParserContext::parse_expr`].
+///
+/// [`ParserContext::parse_expr`]: crate::ParserContext::parse_expr
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ExprError<'a> {
+    /// A predicate could not be parsed.
+    Parse(ParseArgError<'a>),
+    /// An operator is missing the expression it applies to, e.g. a trailing `-or`.
+    MissingOperand(String),
+    /// A parenthesis without its counterpart.
+    UnmatchedParen(String),
+}
+
+impl ExprError<'_> {
+    /// Returns the message of an error that is not a [`ParseArgError`].
+    fn message(&self) -> Option<Message<'_>> {
+        match self {
+            ExprError::Parse(_) => None,
+            ExprError::MissingOperand(operator) => Some(Message::MissingOperand(operator)),
+            ExprError::UnmatchedParen(paren) => Some(Message::UnmatchedParen(paren)),
+        }
+    }
+}
+
+impl fmt::Display for ExprError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self, self.message()) {
+            (ExprError::Parse(err), _) => write!(f, "{}", err),
+            (_, message) => write!(f, "{}", message.unwrap()),
+        }
+    }
+}
+
+impl Error for ExprError<'_> {}
+
+impl<'a> From<ParseArgError<'a>> for ExprError<'a> {
+    fn from(err: ParseArgError<'a>) -> Self {
+        ExprError::Parse(err)
+    }
+}
+
+/// The operators of the expression language.
+const OPERATORS: [&str; 8] = ["(", ")", "!", "-not", "-a", "-and", "-o", "-or"];
+
+/// A token of an expression: an operator or a predicate.
+enum Token<'a> {
+    Operator(&'a str),
+    Predicate(Argument<'a>),
+}
+
+impl ParserContext<'_> {
+    /// Parses `args` as a boolean expression of the defined arguments, in the style of
+    /// find(1), returning `None` when there is no expression at all.
+    ///
+    /// Every argument is a predicate, parsed as usual with the context. Predicates are
+    /// combined with `-not` (or `!`), `-and` (or `-a`), `-or` (or `-o`) and parentheses,
+    /// from the tightest to the loosest binding. Adjacent predicates are joined with
+    /// `-and`. Operators must be separate tokens, as shells require for `(` and `)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Argument, Expr, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.single_dash_longs(true);
+    /// ctx.register(ArgSpec::new(ArgDef::Long("name")).action(ArgAction::Value)).unwrap();
+    /// ctx.register(ArgSpec::new(ArgDef::Long("empty")).action(ArgAction::Flag)).unwrap();
+    ///
+    /// let tokens = ["-empty", "-or", "-not", "(", "-name", "*.rs", "-o", "-name", "*.md", ")"];
+    /// let expr = ctx.parse_expr(&tokens).unwrap().unwrap();
+    /// assert!(matches!(expr, Expr::Or(..)));
+    ///
+    /// // For a non-empty `main.rs`, only `-name '*.rs'` holds
+    /// let matched = expr.eval(&mut |arg| {
+    ///     matches!(arg, Argument::Option { value, .. } if value == "*.rs")
+    /// });
+    /// assert!(!matched);
+    ///
+    /// let err = ctx.parse_expr(&["(", "-empty"]).unwrap_err();
+    /// assert_eq!(err.to_string(), "unmatched '('");
+    /// ```
+    pub fn parse_expr<'s>(&self, args: &'s [&str]) -> Result<Option<Expr<'s>>, ExprError<'s>> {
+        let mut tokens = Vec::new();
+        let mut iter = ParseIter::new(args.iter().copied(), Some(self));
+        loop {
+            if let Some(operator) = iter.next_token_if(|token| OPERATORS.contains(&token)) {
+                tokens.push(Token::Operator(operator));
+                continue;
+            }
+            let Some(arg) = iter.next() else {
+                break;
+            };
+            let arg = arg?;
+            if let Argument::Option { name, value } = &arg {
+                self.check_value(name, value).map_err(ExprError::Parse)?;
+            }
+            tokens.push(Token::Predicate(arg));
+        }
+
+        let mut tokens = tokens.into_iter().peekable();
+        if tokens.peek().is_none() {
+            return Ok(None);
+        }
+        let expr = parse_or(&mut tokens, None)?;
+        match tokens.next() {
+            Some(Token::Operator(operator)) => Err(ExprError::UnmatchedParen(operator.into())),
+            _ => Ok(Some(expr)),
+        }
+    }
+
+    /// Renders `err` as a message, using the error templates and the catalog if set.
+    pub fn render_expr_error(&self, err: &ExprError) -> String {
+        match (err, err.message()) {
+            (ExprError::Parse(err), _) => self.render_error(err),
+            (_, message) => self.text(&message.unwrap()),
+        }
+    }
+}
+
+type Tokens<'a> = std::iter::Peekable<std::vec::IntoIter<Token<'a>>>;
+
+/// Parses expressions joined with `-or`, following the operator `after` if any.
+fn parse_or<'a>(
+    tokens: &mut Tokens<'a>,
+    after: Option<&str>,
+) -> Result<Expr<'a>, ExprError<'a>> {
+    let mut expr = parse_and(tokens, after)?;
+    while let Some(Token::Operator(operator @ ("-o" | "-or"))) = tokens.peek() {
+        let operator = *operator;
+        tokens.next();
+        expr = Expr::Or(Box::new(expr), Box::new(parse_and(tokens, Some(operator))?));
+    }
+    Ok(expr)
+}
+
+/// Parses expressions joined with `-and`, or simply adjacent, following the operator `after`
+/// if any.
+fn parse_and<'a>(
+    tokens: &mut Tokens<'a>,
+    after: Option<&str>,
+) -> Result<Expr<'a>, ExprError<'a>> {
+    let mut expr = parse_unary(tokens, after)?;
+    loop {
+        let operator = match tokens.peek() {
+            None | Some(Token::Operator(")" | "-o" | "-or")) => return Ok(expr),
+            Some(Token::Operator(operator @ ("-a" | "-and"))) => {
+                let operator = *operator;
+                tokens.next();
+                Some(operator)
+            }
+            Some(_) => None,
+        };
+        expr = Expr::And(Box::new(expr), Box::new(parse_unary(tokens, operator)?));
+    }
+}
+
+/// Parses a predicate, a negation or a parenthesized expression, following the operator
+/// `after` if any.
+fn parse_unary<'a>(
+    tokens: &mut Tokens<'a>,
+    after: Option<&str>,
+) -> Result<Expr<'a>, ExprError<'a>> {
+    match tokens.next() {
+        Some(Token::Predicate(arg)) => Ok(Expr::Predicate(arg)),
+        Some(Token::Operator(operator @ ("!" | "-not"))) => {
+            Ok(Expr::Not(Box::new(parse_unary(tokens, Some(operator))?)))
+        }
+        Some(Token::Operator("(")) => {
+            let expr = parse_or(tokens, Some("("))?;
+            match tokens.next() {
+                Some(Token::Operator(")")) => Ok(expr),
+                _ => Err(ExprError::UnmatchedParen("(".into())),
+            }
+        }
+        // Only a leading operator lacks an operand of its own
+        Some(Token::Operator(")")) if after.is_none() => {
+            Err(ExprError::UnmatchedParen(")".into()))
+        }
+        Some(Token::Operator(operator)) if after.is_none() => {
+            Err(ExprError::MissingOperand(operator.into()))
+        }
+        _ => Err(ExprError::MissingOperand(after.unwrap_or_default().into())),
+    }
+}
@@ -0,0 +1,376 @@
+//! A subset of the [getopts](https://docs.rs/getopts) API, implemented on top of this parser.
+//!
+//! Migrating from getopts is meant to be a matter of replacing `getopts::` with
+//! `argsparse::getopts::`. Options are defined with the familiar `optflag`/`optopt` family,
+//! parsed with [`Options::parse`], and queried by short or long name on the resulting
+//! [`Matches`].
+//!
+//! # Examples
+//!
+//! ```
+//! use argsparse::getopts::Options;
+//!
+//! let mut opts = Options::new();
+//! opts.optopt("o", "output", "set output file name", "NAME");
+//! opts.optflag("h", "help", "print this help menu");
+//!
+//! let matches = opts.parse(["-o", "out.txt", "input.txt"]).unwrap();
+//!
+//! assert!(!matches.opt_present("h"));
+//! assert_eq!(matches.opt_str("output").as_deref(), Some("out.txt"));
+//! assert_eq!(matches.free, ["input.txt"]);
+//! ```
+
+use crate::defs::{ArgDef, ArgName, Argument, ParseArgError};
+use crate::parser::{self, ParserContext};
+use crate::spec::{ArgAction, ArgSpec};
+use std::error::Error;
+use std::fmt;
+
+/// Whether an option takes a value.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum HasArg {
+    No,
+    Yes,
+}
+
+/// How many times an option must and may appear.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum Occur {
+    Optional,
+    Required,
+    Multi,
+}
+
+/// A registered option.
+#[derive(Debug, Clone)]
+struct OptGroup {
+    short: Option<char>,
+    long: Option<String>,
+    hint: String,
+    desc: String,
+    hasarg: HasArg,
+    occur: Occur,
+}
+
+/// A set of option definitions, the getopts counterpart of a [`ParserContext`].
+///
+/// [`ParserContext`]: crate::ParserContext
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    grps: Vec<OptGroup>,
+}
+
+/// The result of parsing a command line with [`Options::parse`].
+#[derive(Debug, Clone)]
+pub struct Matches {
+    /// The names of each option, in the order they were defined.
+    opts: Vec<(Option<char>, Option<String>)>,
+    /// The values of each occurrence of each option (`None` for flags).
+    vals: Vec<Vec<Option<String>>>,
+    /// The arguments that are not options.
+    pub free: Vec<String>,
+}
+
+/// The reasons parsing can fail, as reported by getopts.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Fail {
+    /// An option that takes a value was given none.
+    ArgumentMissing(String),
+    /// An option that is not defined was given.
+    UnrecognizedOption(String),
+    /// A required option was not given.
+    OptionMissing(String),
+    /// An option that may only appear once was given several times.
+    OptionDuplicated(String),
+    /// A value was given to an option that takes none.
+    UnexpectedArgument(String),
+}
+
+impl Options {
+    /// Creates an empty set of options.
+    pub fn new() -> Options {
+        Options::default()
+    }
+
+    /// Defines a flag that may appear at most once.
+    pub fn optflag(&mut self, short_name: &str, long_name: &str, desc: &str) -> &mut Options {
+        self.push(short_name, long_name, desc, "", HasArg::No, Occur::Optional)
+    }
+
+    /// Defines a flag that may appear any number of times (e.g., `-vvv`).
+    pub fn optflagmulti(
+        &mut self,
+        short_name: &str,
+        long_name: &str,
+        desc: &str,
+    ) -> &mut Options {
+        self.push(short_name, long_name, desc, "", HasArg::No, Occur::Multi)
+    }
+
+    /// Defines an option that takes a value and may appear at most once.
+    pub fn optopt(
+        &mut self,
+        short_name: &str,
+        long_name: &str,
+        desc: &str,
+        hint: &str,
+    ) -> &mut Options {
+        self.push(short_name, long_name, desc, hint, HasArg::Yes, Occur::Optional)
+    }
+
+    /// Defines an option that takes a value and must appear exactly once.
+    pub fn reqopt(
+        &mut self,
+        short_name: &str,
+        long_name: &str,
+        desc: &str,
+        hint: &str,
+    ) -> &mut Options {
+        self.push(short_name, long_name, desc, hint, HasArg::Yes, Occur::Required)
+    }
+
+    /// Defines an option that takes a value and may appear any number of times.
+    pub fn optmulti(
+        &mut self,
+        short_name: &str,
+        long_name: &str,
+        desc: &str,
+        hint: &str,
+    ) -> &mut Options {
+        self.push(short_name, long_name, desc, hint, HasArg::Yes, Occur::Multi)
+    }
+
+    /// Parses `args`, which should not include the program name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use argsparse::getopts::{Fail, Options};
+    ///
+    /// let mut opts = Options::new();
+    /// opts.reqopt("o", "output", "set output file name", "NAME");
+    ///
+    /// assert_eq!(opts.parse(["-x"]).unwrap_err(), Fail::UnrecognizedOption("-x".into()));
+    /// assert_eq!(opts.parse(["-v"; 0]).unwrap_err(), Fail::OptionMissing("output".into()));
+    /// assert_eq!(opts.parse(["-o"]).unwrap_err(), Fail::ArgumentMissing("o".into()));
+    /// ```
+    pub fn parse<C>(&self, args: C) -> Result<Matches, Fail>
+    where
+        C: IntoIterator,
+        C::Item: AsRef<str>,
+    {
+        let args: Vec<String> = args.into_iter().map(|arg| arg.as_ref().to_string()).collect();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let ctx = self.context();
+
+        let parsed = parser::parse_with_ctx(&args, &ctx).map_err(|err| match err {
+            ParseArgError::UnknownLong(name) => Fail::UnrecognizedOption(format!("--{}", name)),
+            ParseArgError::UnknownShort(name) => Fail::UnrecognizedOption(format!("-{}", name)),
+            ParseArgError::MalformedArg { token, .. } => Fail::UnrecognizedOption(token.into()),
+            ParseArgError::MissingValue { arg, .. } => {
+                Fail::ArgumentMissing(arg.trim_start_matches('-').into())
+            }
+            err => Fail::UnrecognizedOption(err.to_string()),
+        })?;
+
+        let mut vals = vec![Vec::new(); self.grps.len()];
+        let mut free = Vec::new();
+        for arg in parsed.iter() {
+            let (name, value) = match arg {
+                Argument::Positional { value } => {
+                    free.push(value.to_string());
+                    continue;
+                }
+                Argument::Flag { name } => (name, None),
+                Argument::Option { name, value } => (name, Some(value.to_string())),
+            };
+
+            let index = self.grps.iter().position(|grp| grp.matches(name)).unwrap();
+            let grp = &self.grps[index];
+            let typed = || match name {
+                ArgName::Short(s) => s.to_string(),
+                ArgName::Long(l) => l.to_string(),
+            };
+            match (grp.hasarg, &value) {
+                (HasArg::Yes, None) => return Err(Fail::ArgumentMissing(typed())),
+                (HasArg::No, Some(_)) => return Err(Fail::UnexpectedArgument(typed())),
+                _ => {}
+            }
+            if grp.occur != Occur::Multi && !vals[index].is_empty() {
+                return Err(Fail::OptionDuplicated(typed()));
+            }
+            vals[index].push(value);
+        }
+
+        let missing = self.grps.iter().zip(&vals).find(|(grp, vals)| {
+            grp.occur == Occur::Required && vals.is_empty()
+        });
+        if let Some((grp, _)) = missing {
+            return Err(Fail::OptionMissing(grp.name()));
+        }
+
+        let opts = self.grps.iter().map(|grp| (grp.short, grp.long.clone())).collect();
+        Ok(Matches { opts, vals, free })
+    }
+
+    /// Returns the `brief` text followed by the description of every option.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use argsparse::getopts::Options;
+    ///
+    /// let mut opts = Options::new();
+    /// opts.optopt("o", "output", "set output file name", "NAME");
+    /// opts.optflag("h", "help", "print this help menu");
+    ///
+    /// assert_eq!(opts.usage("Usage: prog [options]"), "\
+    /// Usage: prog [options]
+    ///
+    /// Options:
+    ///   -o, --output <NAME>   set output file name
+    ///   -h, --help            print this help menu
+    /// ");
+    /// ```
+    pub fn usage(&self, brief: &str) -> String {
+        let mut ctx = self.context();
+        ctx.help_template("Options:\n{options}\n");
+        format!("{}\n\n{}", brief, ctx.render_help(""))
+    }
+
+    fn push(
+        &mut self,
+        short_name: &str,
+        long_name: &str,
+        desc: &str,
+        hint: &str,
+        hasarg: HasArg,
+        occur: Occur,
+    ) -> &mut Options {
+        let mut chars = short_name.chars();
+        let short = chars.next();
+        assert!(chars.next().is_none(), "the short name must be a single character");
+        let long = (!long_name.is_empty()).then(|| long_name.to_string());
+        assert!(short.is_some() || long.is_some(), "an option needs a short or long name");
+
+        self.grps.push(OptGroup {
+            short,
+            long,
+            hint: hint.to_string(),
+            desc: desc.to_string(),
+            hasarg,
+            occur,
+        });
+        self
+    }
+
+    /// Builds the context equivalent to the definitions.
+    fn context(&self) -> ParserContext<'_> {
+        let mut ctx = ParserContext::new();
+        for grp in &self.grps {
+            let def = match (grp.short, grp.long.as_deref()) {
+                (Some(short), Some(long)) => ArgDef::ShortAndLong { short, long },
+                (Some(short), None) => ArgDef::Short(short),
+                (None, Some(long)) => ArgDef::Long(long),
+                (None, None) => unreachable!("options are defined with at least one name"),
+            };
+
+            let mut spec = ArgSpec::new(def).help(&grp.desc);
+            spec = match grp.hasarg {
+                HasArg::Yes if grp.hint.is_empty() => spec.action(ArgAction::Value),
+                HasArg::Yes => spec.action(ArgAction::Value).value_name(&grp.hint),
+                HasArg::No => spec.action(ArgAction::Flag),
+            };
+            // Duplicates are reported by getopts only when parsing, so the first one wins
+            let _ = ctx.register(spec);
+        }
+        ctx
+    }
+}
+
+impl OptGroup {
+    fn matches(&self, name: &ArgName) -> bool {
+        match name {
+            ArgName::Short(s) => self.short == Some(*s),
+            ArgName::Long(l) => self.long.as_deref() == Some(l.as_ref()),
+        }
+    }
+
+    /// Returns the name getopts reports for a missing option: the long name if any.
+    fn name(&self) -> String {
+        match (self.short, &self.long) {
+            (_, Some(long)) => long.clone(),
+            (Some(short), None) => short.to_string(),
+            (None, None) => unreachable!("options are defined with at least one name"),
+        }
+    }
+}
+
+impl Matches {
+    /// Returns `true` if the option `name` (short or long) was given.
+    pub fn opt_present(&self, name: &str) -> bool {
+        self.opt_count(name) > 0
+    }
+
+    /// Returns `true` if any of the options `names` was given.
+    pub fn opts_present(&self, names: &[String]) -> bool {
+        names.iter().any(|name| self.opt_present(name))
+    }
+
+    /// Returns the number of times the option `name` was given.
+    pub fn opt_count(&self, name: &str) -> usize {
+        self.values(name).map_or(0, Vec::len)
+    }
+
+    /// Returns the first value of the option `name`, if it was given.
+    pub fn opt_str(&self, name: &str) -> Option<String> {
+        self.values(name)?.iter().flatten().next().cloned()
+    }
+
+    /// Returns every value of the option `name`.
+    pub fn opt_strs(&self, name: &str) -> Vec<String> {
+        self.values(name).map_or_else(Vec::new, |vals| vals.iter().flatten().cloned().collect())
+    }
+
+    /// Returns the value of the option `name` if it was given with one, `def` if it was
+    /// given without, and `None` if it was not given.
+    pub fn opt_default(&self, name: &str, def: &str) -> Option<String> {
+        match self.values(name)?.first()? {
+            Some(value) => Some(value.clone()),
+            None => Some(def.to_string()),
+        }
+    }
+
+    fn values(&self, name: &str) -> Option<&Vec<Option<String>>> {
+        let mut chars = name.chars();
+        let short = match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        };
+
+        self.opts
+            .iter()
+            .position(|(s, l)| match short {
+                Some(c) => *s == Some(c),
+                None => l.as_deref() == Some(name),
+            })
+            .map(|index| &self.vals[index])
+    }
+}
+
+impl fmt::Display for Fail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fail::ArgumentMissing(name) => write!(f, "Argument to option '{}' missing", name),
+            Fail::UnrecognizedOption(name) => write!(f, "Unrecognized option: '{}'", name),
+            Fail::OptionMissing(name) => write!(f, "Required option '{}' missing", name),
+            Fail::OptionDuplicated(name) => write!(f, "Option '{}' given more than once", name),
+            Fail::UnexpectedArgument(name) => {
+                write!(f, "Option '{}' does not take an argument", name)
+            }
+        }
+    }
+}
+
+impl Error for Fail {}
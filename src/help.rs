@@ -0,0 +1,775 @@
+use crate::defs::{ArgDef, ParseArgError};
+use crate::matches::Matches;
+use crate::messages::{self, Message};
+use crate::parser::ParserContext;
+use crate::spec::ArgSpec;
+use crate::terminal::{self, Stream};
+use std::io::{self, Write};
+
+/// Turns the help of a command into output, such as HTML, JSON or widgets of a GUI.
+///
+/// The built-in help text is the [`TextRenderer`]; [`ParserContext::render_help_with`] renders
+/// the help with any other.
+///
+/// # Examples
+///
+/// ```
+/// # use argsparse::{ArgDef, ArgSpec, HelpModel, HelpRenderer, ParserContext};
+/// struct Markdown;
+///
+/// impl HelpRenderer for Markdown {
+///     fn render(&self, help: &HelpModel) -> String {
+///         let mut out = format!("**{}**\n\n`{}`\n\n", help.name, help.usage);
+///         for option in &help.options {
+///             let long = option.long.as_deref().unwrap_or_default();
+///             out.push_str(&format!("- `--{}`: {}\n", long, option.help.unwrap_or_default()));
+///         }
+///         out
+///     }
+/// }
+///
+/// let mut ctx = ParserContext::new();
+/// ctx.register(ArgSpec::new(ArgDef::Long("verbose")).help("Print more")).unwrap();
+///
+/// assert_eq!(ctx.render_help_with("app", &Markdown), "\
+/// **app**
+///
+/// `Usage: app [OPTIONS]`
+///
+/// - `--verbose`: Print more
+/// ");
+/// ```
+///
+/// [`ParserContext::render_help_with`]: crate::ParserContext::render_help_with
+pub trait HelpRenderer {
+    /// Renders `help`.
+    fn render(&self, help: &HelpModel) -> String;
+}
+
+/// The renderer of the built-in help text, as returned by [`ParserContext::render_help`].
+///
+/// [`ParserContext::render_help`]: crate::ParserContext::render_help
+#[derive(Debug, Default, Copy, Clone)]
+pub struct TextRenderer;
+
+impl HelpRenderer for TextRenderer {
+    fn render(&self, help: &HelpModel) -> String {
+        format_help(help, false)
+    }
+}
+
+/// A renderer of the help as a JSON object, for GUIs and documentation pipelines, as
+/// printed by [`ParserContext::handle_help`] for `--help=json`.
+///
+/// The object holds the `name` and `usage` of the program, its `arguments`, `options` and
+/// `examples`, and the `before_help`, `after_help` and `doc_url` texts when set. Every
+/// option holds its `short` and `long` names, `value` placeholder, `help`, `default`,
+/// `env`, `possible_values`, whether it is `required`, and its `doc_url`; absent ones are
+/// `null`.
+///
+/// # Examples
+///
+/// ```
+/// # use argsparse::{ArgDef, ArgSpec, JsonRenderer, ParserContext};
+/// let mut ctx = ParserContext::new();
+/// ctx.register(ArgSpec::new(ArgDef::Long("color"))
+///     .help("When to use colors")
+///     .default("auto")
+///     .possible_values(&["auto", "never"]))
+///     .unwrap();
+///
+/// let json = ctx.render_help_with("app", &JsonRenderer);
+/// assert!(json.starts_with(r#"{"name":"app","usage":"Usage: app [OPTIONS]","arguments":[]"#));
+/// assert!(json.contains(r#""long":"color","value":"<VALUE>","help":"When to use colors""#));
+/// assert!(json.contains(r#""default":"auto","env":null,"possible_values":["auto","never"]"#));
+/// ```
+///
+/// [`ParserContext::handle_help`]: crate::ParserContext::handle_help
+#[derive(Debug, Default, Copy, Clone)]
+pub struct JsonRenderer;
+
+impl HelpRenderer for JsonRenderer {
+    fn render(&self, help: &HelpModel) -> String {
+        let entries = |entries: &[HelpEntry]| {
+            let entries: Vec<String> = entries.iter().map(json_entry).collect();
+            format!("[{}]", entries.join(","))
+        };
+        let mut out = format!("{{\"name\":{}", json_string(help.name));
+        out.push_str(&format!(",\"usage\":{}", json_string(&help.usage)));
+        out.push_str(&format!(",\"arguments\":{}", entries(&help.arguments)));
+        out.push_str(&format!(",\"options\":{}", entries(&help.options)));
+        let examples: Vec<String> = help.examples.iter().map(|(command, description)| {
+            let (command, description) = (json_string(command), json_string(description));
+            format!("{{\"command\":{},\"description\":{}}}", command, description)
+        }).collect();
+        out.push_str(&format!(",\"examples\":[{}]", examples.join(",")));
+        out.push_str(&format!(",\"before_help\":{}", json_option(help.before_help)));
+        out.push_str(&format!(",\"after_help\":{}", json_option(help.after_help)));
+        out.push_str(&format!(",\"doc_url\":{}}}\n", json_option(help.doc_url)));
+        out
+    }
+}
+
+/// The help of a command, as given to a [`HelpRenderer`].
+///
+/// Texts shown to the user, such as the usage line, headings and annotations, are already
+/// translated by the [`Catalog`] of the context.
+///
+/// [`Catalog`]: crate::Catalog
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub struct HelpModel<'h> {
+    /// The name of the program.
+    pub name: &'h str,
+    /// The usage line (e.g., `Usage: app [OPTIONS] <FILE>`).
+    pub usage: String,
+    /// The text shown before the usage line, if any.
+    pub before_help: Option<&'h str>,
+    /// The text shown at the end, if any.
+    pub after_help: Option<&'h str>,
+    /// The heading of the positionals.
+    pub arguments_heading: String,
+    /// The positionals, in the order they are matched.
+    pub arguments: Vec<HelpEntry<'h>>,
+    /// The heading of the options.
+    pub options_heading: String,
+    /// The visible options, including those of nested contexts.
+    pub options: Vec<HelpEntry<'h>>,
+    /// The heading of the examples.
+    pub examples_heading: String,
+    /// The example invocations of the program and of its visible options, each with its
+    /// description.
+    pub examples: Vec<(&'h str, &'h str)>,
+    /// The documentation URL of the program, if any.
+    pub doc_url: Option<&'h str>,
+    /// The line pointing to the documentation (e.g., `Documentation: <url>`), if any.
+    pub documentation: Option<String>,
+}
+
+/// A positional or an option, as listed in a [`HelpModel`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub struct HelpEntry<'h> {
+    /// The short name of the option, if any.
+    pub short: Option<char>,
+    /// The long name of the option, prefixed with the names of its nested contexts, if any,
+    /// and with `[no-]` when it accepts a negation.
+    pub long: Option<String>,
+    /// The placeholder of the value the option takes (e.g., `<PORT>`), or how the positional
+    /// appears in the usage line (e.g., `[FILES]...`).
+    pub value: Option<String>,
+    /// The help text, if any.
+    pub help: Option<&'h str>,
+    /// The annotations following the help text (e.g., `[default: 8080]`).
+    pub notes: Vec<String>,
+    /// The documentation URL, if any.
+    pub doc_url: Option<&'h str>,
+    /// The default value of the option, if any.
+    pub default: Option<&'h str>,
+    /// The environment variable the option falls back to, if any.
+    pub env: Option<&'h str>,
+    /// The values the option accepts, or none if it accepts any.
+    pub possible_values: &'h [&'h str],
+    /// Whether the argument must be given.
+    pub required: bool,
+}
+
+impl HelpEntry<'_> {
+    /// Returns the names as shown in the first column of the help text.
+    fn names(&self) -> String {
+        let mut names = match (self.short, &self.long) {
+            (Some(short), Some(long)) => format!("-{}, --{}", short, long),
+            (Some(short), None) => format!("-{}", short),
+            // Keep long names aligned with the ones following a short name
+            (None, Some(long)) => format!("    --{}", long),
+            (None, None) => String::new(),
+        };
+        if let Some(value) = &self.value {
+            if !names.is_empty() {
+                names.push(' ');
+            }
+            names.push_str(value);
+        }
+        names
+    }
+
+    /// Returns the help text followed by the annotations.
+    fn description(&self) -> String {
+        let help = self.help.map(str::to_string);
+        help.into_iter().chain(self.notes.iter().cloned()).collect::<Vec<_>>().join(" ")
+    }
+}
+
+impl<'a> ParserContext<'a> {
+    /// Renders the usage line for the program `name`.
+    ///
+    /// Required options are spelled out, the others are summarized as `[OPTIONS]`, and
+    /// positionals follow in the order they are matched, in angle brackets when required
+    /// and square brackets otherwise. Hidden and removed options are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, ParserContext, PositionalSpec};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose")
+    ///     .positional(PositionalSpec::new("SRC").required(true))
+    ///     .positional(PositionalSpec::new("DEST"));
+    /// ctx.register(ArgSpec::new(ArgDef::Long("mode"))
+    ///     .required(true)
+    ///     .action(ArgAction::Value)
+    ///     .value_name("MODE"))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(ctx.usage("cp"), "Usage: cp [OPTIONS] --mode <MODE> <SRC> [DEST]");
+    /// ```
+    pub fn usage(&self, name: &str) -> String {
+        self.usage_line(name, false)
+    }
+
+    /// Renders `err` for the program `name`, followed by the usage line, as printed when the
+    /// command line is rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, ParserContext, PositionalSpec};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose").positional(PositionalSpec::new("FILE").required(true));
+    ///
+    /// let err = Args::parse_with_context(&["--verbos", "a.txt"], &ctx).unwrap_err();
+    /// assert_eq!(ctx.render_error_with_usage("cat", &err), "\
+    /// unknown option '--verbos'
+    ///
+    /// Usage: cat [OPTIONS] <FILE>
+    /// ");
+    /// ```
+    pub fn render_error_with_usage(&self, name: &str, err: &ParseArgError) -> String {
+        format!("{}\n\n{}\n", self.render_error(err), self.usage(name))
+    }
+
+    fn usage_line(&self, name: &str, links: bool) -> String {
+        let name = match self.doc_url {
+            Some(url) if links => hyperlink(name, url),
+            _ => name.to_string(),
+        };
+        let mut usage = format!("{}: {}", self.text(&Message::Usage), name);
+
+        let mut specs = Vec::new();
+        self.visible_specs("", &mut specs);
+        specs.retain(|(_, spec)| !spec.is_removed(self.version));
+        if specs.iter().any(|(_, spec)| !spec.required) {
+            usage.push(' ');
+            usage.push_str(&self.text(&Message::OptionsPlaceholder));
+        }
+        for (prefix, spec) in specs.iter().filter(|(_, spec)| spec.required) {
+            usage.push(' ');
+            usage.push_str(&typed_name(prefix, spec, self.shown_long(prefix, spec)));
+            if let Some(value) = value_placeholder(spec) {
+                usage.push(' ');
+                usage.push_str(&value);
+            }
+        }
+
+        for spec in self.positionals() {
+            usage.push(' ');
+            usage.push_str(&spec.to_string());
+        }
+
+        usage
+    }
+
+    /// Renders the help text for the program `name`: the usage line, followed by the
+    /// positionals and the options with their descriptions, surrounded by the
+    /// [`before_help`] and [`after_help`] texts if they are set. A [`help_template`]
+    /// replaces this layout altogether.
+    ///
+    /// Hidden definitions are left out, and options of nested contexts are listed under
+    /// their prefixed long names. The default value, environment variable and accepted
+    /// values of a definition are appended to its description.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ArgSpec, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(ArgDef::ShortAndLong { short: 'v', long: "verbose" })
+    ///     .help("Print more"))
+    ///     .unwrap();
+    /// ctx.register(ArgSpec::new(ArgDef::ShortAndLong { short: 'p', long: "port" })
+    ///     .help("Port to listen on")
+    ///     .value_name("PORT")
+    ///     .default("8080")
+    ///     .env("MYAPP_PORT"))
+    ///     .unwrap();
+    /// ctx.register(ArgSpec::new(ArgDef::Long("color"))
+    ///     .help("When to use colors")
+    ///     .value_name("WHEN")
+    ///     .possible_values(&["auto", "never"]))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(ctx.render_help("app"), "\
+    /// Usage: app [OPTIONS]
+    ///
+    /// Options:
+    ///   -v, --verbose        Print more
+    ///   -p, --port <PORT>    Port to listen on [default: 8080] [env: MYAPP_PORT]
+    ///       --color <WHEN>   When to use colors [possible values: auto, never]
+    /// ");
+    /// ```
+    ///
+    /// [`before_help`]: ParserContext::before_help
+    /// [`after_help`]: ParserContext::after_help
+    /// [`help_template`]: ParserContext::help_template
+    pub fn render_help(&self, name: &str) -> String {
+        self.render(name, false)
+    }
+
+    /// Renders the help text like [`render_help`], but with documentation URLs emitted as
+    /// terminal hyperlinks (OSC 8) on the names they belong to, rather than spelled out.
+    ///
+    /// Only use this output on terminals that support hyperlinks; others may show the
+    /// escape sequences verbatim.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ArgSpec, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(ArgDef::Long("jobs")).doc_url("https://example.com/jobs"))
+    ///     .unwrap();
+    ///
+    /// assert!(ctx.render_help("app").contains("--jobs   [docs: https://example.com/jobs]"));
+    /// assert!(ctx
+    ///     .render_help_with_links("app")
+    ///     .contains("\x1b]8;;https://example.com/jobs\x1b\\--jobs\x1b]8;;\x1b\\"));
+    /// ```
+    ///
+    /// [`render_help`]: ParserContext::render_help
+    pub fn render_help_with_links(&self, name: &str) -> String {
+        self.render(name, true)
+    }
+
+    /// Renders the help for the program `name` with `renderer`.
+    ///
+    /// Unlike [`render_help`], this ignores the [`help_template`], which only applies to the
+    /// built-in text.
+    ///
+    /// [`render_help`]: ParserContext::render_help
+    /// [`help_template`]: ParserContext::help_template
+    pub fn render_help_with(&self, name: &str, renderer: &dyn HelpRenderer) -> String {
+        renderer.render(&self.help_model(name))
+    }
+
+    /// Returns the help for the program `name`, as given to a [`HelpRenderer`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ArgSpec, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(ArgDef::ShortAndLong { short: 'p', long: "port" })
+    ///     .value_name("PORT")
+    ///     .default("8080"))
+    ///     .unwrap();
+    ///
+    /// let help = ctx.help_model("app");
+    /// assert_eq!(help.usage, "Usage: app [OPTIONS]");
+    /// assert_eq!(help.options[0].short, Some('p'));
+    /// assert_eq!(help.options[0].value.as_deref(), Some("<PORT>"));
+    /// assert_eq!(help.options[0].notes, ["[default: 8080]"]);
+    /// ```
+    pub fn help_model<'h>(&'h self, name: &'h str) -> HelpModel<'h> {
+        self.model(name, false)
+    }
+
+    /// Builds the help model, leaving out the URLs shown as hyperlinks if `links` is set.
+    fn model<'h>(&'h self, name: &'h str, links: bool) -> HelpModel<'h> {
+        HelpModel {
+            name,
+            usage: self.usage_line(name, links),
+            before_help: self.before_help,
+            after_help: self.after_help,
+            arguments_heading: self.text(&Message::ArgumentsHeading),
+            arguments: self.argument_entries(),
+            options_heading: self.text(&Message::OptionsHeading),
+            options: self.option_entries(links),
+            examples_heading: self.text(&Message::ExamplesHeading),
+            examples: self.examples(),
+            doc_url: self.doc_url,
+            documentation: self.doc_url
+                .filter(|_| !links)
+                .map(|url| self.text(&Message::Documentation(url))),
+        }
+    }
+
+    fn render(&self, name: &str, links: bool) -> String {
+        match self.help_template {
+            Some(template) => self.expand_template(template, name, links),
+            None => format_help(&self.model(name, links), links),
+        }
+    }
+
+    /// Prints the help text for the program `name` to the standard output.
+    ///
+    /// Documentation URLs are emitted as hyperlinks when the standard output takes escape
+    /// sequences, as decided by [`terminal::supports_escapes`].
+    ///
+    /// [`terminal::supports_escapes`]: crate::terminal::supports_escapes
+    pub fn print_help(&self, name: &str) -> io::Result<()> {
+        let help = self.render(name, terminal::supports_escapes(Stream::Stdout));
+        io::stdout().lock().write_all(help.as_bytes())
+    }
+
+    /// Prints the help for the program `name` if `matches` holds the flag registered by
+    /// [`help_flag`], returning whether it did, so that the program can exit then.
+    ///
+    /// `--help=json` prints the help as rendered by [`JsonRenderer`]; otherwise, the help
+    /// text is printed as with [`print_help`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use argsparse::{Matches, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.help_flag().flag('v', "verbose");
+    ///
+    /// let matches = Matches::parse(&["--help=json"], &ctx).unwrap();
+    /// if ctx.handle_help("app", &matches).unwrap() {
+    ///     std::process::exit(0);
+    /// }
+    /// ```
+    ///
+    /// [`help_flag`]: ParserContext::help_flag
+    /// [`JsonRenderer`]: crate::JsonRenderer
+    /// [`print_help`]: ParserContext::print_help
+    pub fn handle_help(&self, name: &str, matches: &Matches) -> io::Result<bool> {
+        let help = ArgDef::Long("help");
+        if !matches.has(help) {
+            return Ok(false);
+        }
+        match matches.value_of(help).as_deref() {
+            Some("json") => {
+                let json = self.render_help_with(name, &JsonRenderer);
+                io::stdout().lock().write_all(json.as_bytes())?;
+            }
+            _ => self.print_help(name)?,
+        }
+        Ok(true)
+    }
+
+    /// Prints the help text for the program `name` through a pager when the standard output
+    /// is a terminal.
+    ///
+    /// The pager is taken from `$PAGER`, then `less` and `more` are tried in turn. When the
+    /// standard output is not a terminal, or no pager can be started, the help is printed
+    /// as with [`print_help`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use argsparse::ParserContext;
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose");
+    ///
+    /// ctx.page_help("app").unwrap();
+    /// ```
+    ///
+    /// [`print_help`]: ParserContext::print_help
+    #[cfg(feature = "pager")]
+    pub fn page_help(&self, name: &str) -> io::Result<()> {
+        use std::process::{Command, Stdio};
+
+        if !terminal::is_terminal(Stream::Stdout) {
+            let help = self.render_help(name);
+            return io::stdout().lock().write_all(help.as_bytes());
+        }
+
+        let help = self.render(name, terminal::supports_escapes(Stream::Stdout));
+        let pagers = std::env::var("PAGER").ok();
+        let pagers = pagers.into_iter().chain(["less".to_string(), "more".to_string()]);
+        for pager in pagers {
+            let mut words = pager.split_whitespace();
+            let Some(program) = words.next() else {
+                continue;
+            };
+
+            let mut command = Command::new(program);
+            command.args(words).stdin(Stdio::piped());
+            // Quit at once if the help fits on one screen, and keep escape sequences
+            if std::env::var_os("LESS").is_none() {
+                command.env("LESS", "FRX");
+            }
+            let Ok(mut child) = command.spawn() else {
+                continue;
+            };
+
+            if let Some(mut stdin) = child.stdin.take() {
+                // The pager may quit before reading everything, which is not an error
+                match stdin.write_all(help.as_bytes()) {
+                    Err(err) if err.kind() != io::ErrorKind::BrokenPipe => return Err(err),
+                    _ => {}
+                }
+            }
+            child.wait()?;
+            return Ok(());
+        }
+
+        io::stdout().lock().write_all(help.as_bytes())
+    }
+
+    /// Renders `template` for the program `name`, replacing its placeholders.
+    fn expand_template(&self, template: &str, name: &str, links: bool) -> String {
+        messages::expand(template, |placeholder| {
+            let value = match placeholder {
+                "name" => name.to_string(),
+                "usage" => self.usage_line(name, links),
+                "before-help" => self.before_help.unwrap_or_default().to_string(),
+                "after-help" => self.after_help.unwrap_or_default().to_string(),
+                "arguments" => format_rows(&self.argument_entries(), links),
+                "options" => format_rows(&self.option_entries(links), links),
+                "examples" => format_examples(&self.examples()),
+                _ => return None,
+            };
+            Some(value.trim_end_matches('\n').to_string())
+        })
+    }
+
+    /// Returns the examples of the context, followed by those of its visible options.
+    fn examples(&self) -> Vec<(&str, &str)> {
+        let mut specs = Vec::new();
+        self.visible_specs("", &mut specs);
+        specs.retain(|(_, spec)| !spec.is_removed(self.version));
+
+        let mut examples = self.examples.clone();
+        examples.extend(specs.iter().flat_map(|(_, spec)| spec.examples));
+        examples
+    }
+
+    fn argument_entries(&self) -> Vec<HelpEntry<'_>> {
+        self.positionals()
+            .iter()
+            .map(|spec| HelpEntry {
+                short: None,
+                long: None,
+                value: Some(spec.to_string()),
+                help: spec.help,
+                notes: Vec::new(),
+                doc_url: None,
+                default: None,
+                env: None,
+                possible_values: &[],
+                required: spec.required,
+            })
+            .collect()
+    }
+
+    fn option_entries(&self, links: bool) -> Vec<HelpEntry<'_>> {
+        let mut specs = Vec::new();
+        self.visible_specs("", &mut specs);
+        specs.retain(|(_, spec)| !spec.is_removed(self.version));
+        specs.iter().map(|(prefix, spec)| self.option_entry(prefix, spec, links)).collect()
+    }
+
+    fn option_entry<'s>(&self, prefix: &str, spec: &ArgSpec<'s>, links: bool) -> HelpEntry<'s> {
+        let mut help = Vec::new();
+        if let Some(default) = spec.default {
+            help.push(self.text(&Message::Default(default)));
+        }
+        if let Some(env) = spec.env {
+            help.push(self.text(&Message::Env(env)));
+        }
+        if !spec.possible_values.is_empty() {
+            help.push(self.text(&Message::PossibleValues(spec.possible_values)));
+        }
+        if let Some(version) = spec.since {
+            help.push(self.text(&Message::Since(version)));
+        }
+        if spec.is_deprecated(self.version) || spec.removed_in.is_some() {
+            let (since, removal) = (spec.deprecated_in, spec.removed_in);
+            help.push(self.text(&Message::Deprecated { since, removal }));
+        }
+        // Without hyperlinks, the URL is spelled out
+        if let Some(url) = spec.doc_url.filter(|_| !links) {
+            help.push(self.text(&Message::Docs(url)));
+        }
+
+        HelpEntry {
+            short: spec.def.short().filter(|_| prefix.is_empty()),
+            long: self.shown_long(prefix, spec).map(|long| {
+                let negation = if spec.negation.is_some() { "[no-]" } else { "" };
+                format!("{}{}{}", negation, prefix, long)
+            }),
+            value: value_placeholder(spec),
+            help: spec.help,
+            notes: help,
+            doc_url: spec.doc_url,
+            default: spec.default,
+            env: spec.env,
+            possible_values: spec.possible_values,
+            required: spec.required,
+        }
+    }
+
+    /// Returns the long name `spec` is shown under: its spelling for the locale of the
+    /// context, if any, unless it belongs to a nested context, which has its own locale.
+    fn shown_long<'s>(&self, prefix: &str, spec: &ArgSpec<'s>) -> Option<&'s str> {
+        match prefix.is_empty() {
+            true => self.localized_long(spec).or(spec.def.long()),
+            false => spec.def.long(),
+        }
+    }
+
+    /// Collects the visible definitions of this context and its nested contexts, along with
+    /// the prefix of their long names.
+    fn visible_specs<'s>(&'s self, prefix: &str, out: &mut Vec<(String, &'s ArgSpec<'a>)>) {
+        for spec in self.specs().iter().filter(|spec| !spec.hidden) {
+            // A nested context only exposes its long names
+            if prefix.is_empty() || spec.def.long().is_some() {
+                out.push((prefix.to_string(), spec));
+            }
+        }
+
+        for (name, child) in self.namespaces() {
+            child.visible_specs(&format!("{}{}-", prefix, name), out);
+        }
+    }
+}
+
+/// Returns the placeholder of the value `spec` takes, if any (e.g., `<FILE>`).
+fn value_placeholder(spec: &ArgSpec) -> Option<String> {
+    spec.takes_value()
+        .then(|| format!("<{}>", spec.value_name.unwrap_or("VALUE")))
+}
+
+/// Returns the name `spec` is shown under in a usage line, preferring its long name `long`.
+fn typed_name(prefix: &str, spec: &ArgSpec, long: Option<&str>) -> String {
+    match (spec.def.short(), long) {
+        (_, Some(long)) => format!("--{}{}", prefix, long),
+        (Some(short), None) => format!("-{}", short),
+        (None, None) => unreachable!("a definition always has a name"),
+    }
+}
+
+/// Formats `entry` as a JSON object.
+fn json_entry(entry: &HelpEntry) -> String {
+    let short = entry.short.map(|short| short.to_string());
+    let values: Vec<String> = entry.possible_values.iter().map(|v| json_string(v)).collect();
+    let fields = [
+        ("short", json_option(short.as_deref())),
+        ("long", json_option(entry.long.as_deref())),
+        ("value", json_option(entry.value.as_deref())),
+        ("help", json_option(entry.help)),
+        ("default", json_option(entry.default)),
+        ("env", json_option(entry.env)),
+        ("possible_values", format!("[{}]", values.join(","))),
+        ("required", entry.required.to_string()),
+        ("doc_url", json_option(entry.doc_url)),
+    ];
+    let fields: Vec<String> =
+        fields.iter().map(|(key, value)| format!("\"{}\":{}", key, value)).collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+/// Formats `text` as a JSON string, or `null`.
+fn json_option(text: Option<&str>) -> String {
+    text.map_or("null".to_string(), json_string)
+}
+
+/// Formats `text` as a JSON string.
+fn json_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c < ' ' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Formats `help` as the built-in help text, with hyperlinks if `links` is set.
+fn format_help(help: &HelpModel, links: bool) -> String {
+    let mut out = String::new();
+    if let Some(text) = help.before_help {
+        out.push_str(text);
+        out.push_str("\n\n");
+    }
+    out.push_str(&help.usage);
+    out.push('\n');
+
+    push_section(&mut out, &help.arguments_heading, &help.arguments, links);
+    push_section(&mut out, &help.options_heading, &help.options, links);
+    if !help.examples.is_empty() {
+        out.push('\n');
+        out.push_str(&help.examples_heading);
+        out.push_str(":\n");
+        out.push_str(&format_examples(&help.examples));
+    }
+
+    if let Some(documentation) = &help.documentation {
+        out.push('\n');
+        out.push_str(documentation);
+        out.push('\n');
+    }
+    if let Some(text) = help.after_help {
+        out.push('\n');
+        out.push_str(text);
+        out.push('\n');
+    }
+    out
+}
+
+/// Appends a titled section listing `entries`, unless it is empty.
+fn push_section(out: &mut String, title: &str, entries: &[HelpEntry], links: bool) {
+    if entries.is_empty() {
+        return;
+    }
+
+    out.push('\n');
+    out.push_str(title);
+    out.push_str(":\n");
+    out.push_str(&format_rows(entries, links));
+}
+
+/// Formats `examples` as their command lines, each followed by its indented description.
+fn format_examples(examples: &[(&str, &str)]) -> String {
+    let mut out = String::new();
+    for (command, description) in examples {
+        out.push_str(&format!("  {}\n      {}\n", command, description));
+    }
+    out
+}
+
+/// Formats `entries` in two aligned columns, one line per entry.
+fn format_rows(entries: &[HelpEntry], links: bool) -> String {
+    let rows: Vec<_> = entries.iter().map(|entry| (entry.names(), entry)).collect();
+    let width = rows.iter().map(|(names, _)| names.chars().count()).max().unwrap_or(0);
+
+    let mut out = String::new();
+    for (names, entry) in &rows {
+        // Pad after the hyperlink, as its escape sequences take no room on screen
+        let padding = " ".repeat(width - names.chars().count());
+        let names = match entry.doc_url {
+            Some(url) if links => {
+                let text = names.trim_start();
+                let indent = &names[..names.len() - text.len()];
+                format!("{}{}", indent, hyperlink(text, url))
+            }
+            _ => names.clone(),
+        };
+        let line = format!("  {}{}   {}", names, padding, entry.description());
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Wraps `text` in an OSC 8 hyperlink to `url`.
+fn hyperlink(text: &str, url: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
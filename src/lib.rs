@@ -1,9 +1,48 @@
 mod args;
+pub mod argparse;
+pub mod clap;
+mod compat;
+mod complete;
+mod config;
 mod defs;
+mod docopt;
+#[cfg(feature = "dotenv")]
+mod dotenv;
+#[cfg(feature = "expr")]
+mod expr;
+pub mod getopts;
+mod help;
+mod lookup;
+mod macros;
+mod matches;
+mod messages;
+mod observe;
 mod parser;
+#[cfg(feature = "prompt")]
+mod prompt;
+#[cfg(feature = "rustyline")]
+mod repl;
+mod report;
+pub mod shell_quote;
+mod spec;
+pub mod terminal;
 mod types;
+mod unicode;
+mod wizard;
 
-pub use args::Args;
-pub use defs::{ArgDef, ArgName, Argument, ParseArgError};
-pub use parser::ParserContext;
-pub use types::{FlagArg, OptionArg, PositionalArg, FromArgument};
+pub use args::{Args, MergePolicy};
+pub use compat::BreakingChange;
+pub use complete::Shell;
+pub use docopt::DocoptError;
+#[cfg(feature = "expr")]
+pub use expr::{Expr, ExprError};
+pub use help::{HelpEntry, HelpModel, HelpRenderer, JsonRenderer, TextRenderer};
+pub use defs::{ArgDef, OwnedArgDef, ArgName, Argument, DefinitionConflict, ErrorKind, Limit, ParseArgError, RegisterError, SpecError, ValueError};
+pub use matches::{Finding, Matches};
+pub use messages::{Catalog, Message};
+pub use observe::{Observer, SourceKind, ValueSource};
+pub use parser::{Clustering, FrozenContext, Limits, ParseIter, ParserContext, SubcommandSplit, TokenTransform};
+#[cfg(feature = "rustyline")]
+pub use repl::ReplHelper;
+pub use spec::{ArgAction, ArgSpec, OwnedArgSpec, PositionalSpec, Validator, ValueHint, ValueProvider};
+pub use types::{FlagArg, OptionArg, PositionalArg, FromArgument, Parsed, TriState, TryFromArgument};
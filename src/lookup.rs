@@ -0,0 +1,143 @@
+use std::borrow::{Borrow, Cow};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The number of keys up to which a [`Lookup`] scans a list rather than hashing.
+///
+/// Comparing a handful of short names is cheaper than hashing the one looked up, and most
+/// programs define fewer arguments than this.
+const LINEAR_LIMIT: usize = 16;
+
+/// A map from names to definition ids that adapts to its size.
+///
+/// Small maps are kept as a list scanned in order; once they grow past [`LINEAR_LIMIT`] keys,
+/// they switch to a [`HashMap`] for good.
+#[derive(Debug, Clone)]
+pub(crate) enum Lookup<K> {
+    Linear(Vec<(K, usize)>),
+    Hashed(HashMap<K, usize>),
+}
+
+impl<K: Eq + Hash> Lookup<K> {
+    pub(crate) const fn new() -> Self {
+        Lookup::Linear(Vec::new())
+    }
+
+    /// Returns the id stored under `key`, if any.
+    pub(crate) fn get<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        match self {
+            Lookup::Linear(entries) => entries
+                .iter()
+                .find(|(k, _)| k.borrow() == key)
+                .map(|&(_, index)| index),
+            Lookup::Hashed(map) => map.get(key).copied(),
+        }
+    }
+
+    pub(crate) fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Stores `index` under `key`, replacing the id stored before, if any.
+    pub(crate) fn insert(&mut self, key: K, index: usize) {
+        match self {
+            Lookup::Linear(entries) => {
+                if let Some(entry) = entries.iter_mut().find(|(k, _)| *k == key) {
+                    entry.1 = index;
+                } else if entries.len() < LINEAR_LIMIT {
+                    entries.push((key, index));
+                } else {
+                    let mut map: HashMap<K, usize> = entries.drain(..).collect();
+                    map.insert(key, index);
+                    *self = Lookup::Hashed(map);
+                }
+            }
+            Lookup::Hashed(map) => {
+                map.insert(key, index);
+            }
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        *self = Lookup::new();
+    }
+}
+
+/// A long name stored in an [`Interner`], standing for it in lookups.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub(crate) struct Symbol(usize);
+
+/// Stores each distinct long name once and hands out a [`Symbol`] for it.
+///
+/// Names borrowed from definitions stay borrowed; only the ones derived from them (e.g.,
+/// normalized spellings) are owned.
+#[derive(Debug, Clone)]
+pub(crate) struct Interner<'a> {
+    symbols: Lookup<Cow<'a, str>>,
+    len: usize,
+}
+
+impl<'a> Interner<'a> {
+    pub(crate) const fn new() -> Self {
+        Interner { symbols: Lookup::new(), len: 0 }
+    }
+
+    /// Returns the symbol of `name`, storing the name if it was not interned yet.
+    pub(crate) fn intern(&mut self, name: Cow<'a, str>) -> Symbol {
+        if let Some(symbol) = self.symbols.get(name.as_ref()) {
+            return Symbol(symbol);
+        }
+        let symbol = self.len;
+        self.symbols.insert(name, symbol);
+        self.len += 1;
+        Symbol(symbol)
+    }
+
+    /// Returns the symbol of `name`, if it was interned.
+    pub(crate) fn get(&self, name: &str) -> Option<Symbol> {
+        self.symbols.get(name).map(Symbol)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        *self = Interner::new();
+    }
+}
+
+/// A map from interned names to definition ids, indexed by [`Symbol`].
+#[derive(Debug, Clone)]
+pub(crate) struct SymbolMap(Vec<Option<usize>>);
+
+impl SymbolMap {
+    pub(crate) const fn new() -> Self {
+        SymbolMap(Vec::new())
+    }
+
+    /// Returns the id stored under `symbol`, if any.
+    pub(crate) fn get(&self, symbol: Symbol) -> Option<usize> {
+        self.0.get(symbol.0).copied().flatten()
+    }
+
+    pub(crate) fn contains_key(&self, symbol: Symbol) -> bool {
+        self.get(symbol).is_some()
+    }
+
+    /// Stores `index` under `symbol`, replacing the id stored before, if any.
+    pub(crate) fn insert(&mut self, symbol: Symbol, index: usize) {
+        if self.0.len() <= symbol.0 {
+            self.0.resize(symbol.0 + 1, None);
+        }
+        self.0[symbol.0] = Some(index);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.0.clear();
+    }
+}
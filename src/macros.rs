@@ -0,0 +1,294 @@
+/// Declares a struct whose fields are read from the command line, along with the
+/// definitions they are read from.
+///
+/// Every field is declared as `name: Type = kind(...)`, optionally followed by builder calls
+/// applied to its specification (e.g., `.default("8080")` or `.env("MYAPP_PORT")`). The
+/// kinds are:
+///
+/// - `flag("-v, --verbose")`: a `bool`, `true` if the flag was given.
+/// - `count("-v, --verbose")`: a `usize`, the number of times the flag was given.
+/// - `option("-o, --output <FILE> 'help'")`: an `Option<T>`, the value converted with
+///   [`FromStr`], falling back to the environment variable and default value.
+/// - `required("-o, --output <FILE>")`: like `option`, but the option must be given, or
+///   have an environment variable that is set or a default value, and the field is a plain
+///   `T`.
+/// - `positional("NAME")`: an `Option<T>`, the named positional.
+/// - `positionals("NAME")`: a `Vec<T>`, the remaining operands.
+/// - `flatten()`: a struct itself declared with `define_args!`, whose definitions are
+///   registered in the same context. This lets a group of options shared by several
+///   commands be declared once.
+///
+/// Flags and options are specified as accepted by [`ArgSpec::parse`]; positionals build a
+/// [`PositionalSpec`]. Invalid or conflicting specifications panic when the context is built.
+/// Values are validated while parsing by the builder calls that set up checks, such as
+/// [`range`], [`validator`] and [`possible_values`].
+///
+/// Doc comments double as help text: the first line of a field's doc comment is the help
+/// of its argument, unless the spec string or a `.help()` call sets one, and the first line
+/// of the struct's doc comment is shown at the top of the help output.
+///
+/// The struct gets three associated functions: `context()`, which returns a
+/// [`ParserContext`] with every definition registered, `register(&mut ParserContext)`, which
+/// registers them in an existing context, and `from_matches(&Matches)`, which reads the
+/// fields from arguments parsed with such a context and fails with a [`ValueError`] if a
+/// value does not convert.
+///
+/// # Examples
+///
+/// ```
+/// use argsparse::{define_args, Matches};
+///
+/// define_args! {
+///     /// The command line of a small server.
+///     #[derive(Debug)]
+///     pub struct Cli {
+///         /// Print more output.
+///         pub verbose: usize = count("-v, --verbose"),
+///         /// Show what would be served, then exit.
+///         pub dry_run: bool = flag("--dry-run 'Only list the files'"),
+///         pub port: Option<u16> = option("-p, --port <PORT>").default("8080").range(1..=65535),
+///         pub root: String = required("-r, --root <DIR> 'Directory to serve'"),
+///         pub files: Vec<String> = positionals("FILES").help("Files to preload"),
+///     }
+/// }
+///
+/// let ctx = Cli::context();
+/// let matches = Matches::parse(&["-vv", "--root", "www", "index.html"], &ctx).unwrap();
+/// let cli = Cli::from_matches(&matches).unwrap();
+///
+/// assert_eq!(cli.verbose, 2);
+/// assert!(!cli.dry_run);
+/// assert_eq!(cli.port, Some(8080));
+/// assert_eq!(cli.root, "www");
+/// assert_eq!(cli.files, ["index.html"]);
+///
+/// assert!(Matches::parse(&["--root", "www", "--port", "0"], &ctx).is_err());
+///
+/// let help = ctx.render_help("serve");
+/// assert!(help.starts_with("The command line of a small server."));
+/// assert!(help.contains("Print more output"));
+/// assert!(help.contains("Only list the files"));
+/// ```
+///
+/// The environment variable and default value of an option make up its whole resolution
+/// chain, so a required option with a default never fails to resolve:
+///
+/// ```no_run
+/// use argsparse::{define_args, Matches};
+///
+/// define_args! {
+///     pub struct Server {
+///         pub port: u16 = required("-p, --port <PORT>").env("MYAPP_DOC_PORT").default("8080"),
+///     }
+/// }
+///
+/// let ctx = Server::context();
+/// let server = Server::from_matches(&Matches::parse(&[], &ctx).unwrap()).unwrap();
+/// assert_eq!(server.port, 8080);
+///
+/// unsafe { std::env::set_var("MYAPP_DOC_PORT", "3000") };
+/// let server = Server::from_matches(&Matches::parse(&[], &ctx).unwrap()).unwrap();
+/// assert_eq!(server.port, 3000);
+///
+/// let server = Server::from_matches(&Matches::parse(&["-p", "80"], &ctx).unwrap()).unwrap();
+/// assert_eq!(server.port, 80);
+/// ```
+///
+/// Flattening a shared group of options:
+///
+/// ```
+/// use argsparse::{define_args, Matches};
+///
+/// define_args! {
+///     pub struct LoggingOpts {
+///         pub quiet: bool = flag("-q, --quiet"),
+///         pub log_file: Option<String> = option("--log-file <FILE>"),
+///     }
+/// }
+///
+/// define_args! {
+///     pub struct Build {
+///         pub release: bool = flag("--release"),
+///         pub logging: LoggingOpts = flatten(),
+///     }
+/// }
+///
+/// let ctx = Build::context();
+/// let matches = Matches::parse(&["--release", "-q"], &ctx).unwrap();
+/// let build = Build::from_matches(&matches).unwrap();
+///
+/// assert!(build.release);
+/// assert!(build.logging.quiet);
+/// assert_eq!(build.logging.log_file, None);
+/// ```
+///
+/// [`FromStr`]: std::str::FromStr
+/// [`ArgSpec::parse`]: crate::ArgSpec::parse
+/// [`PositionalSpec`]: crate::PositionalSpec
+/// [`range`]: crate::ArgSpec::range
+/// [`validator`]: crate::ArgSpec::validator
+/// [`possible_values`]: crate::ArgSpec::possible_values
+/// [`ParserContext`]: crate::ParserContext
+/// [`ValueError`]: crate::ValueError
+#[macro_export]
+macro_rules! define_args {
+    (
+        $(#[$($meta:tt)*])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$($field_meta:tt)*])*
+                $field_vis:vis $field:ident : $ty:ty =
+                    $kind:ident ( $($spec:tt)* ) $(. $method:ident ( $($arg:tt)* ))*
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$($meta)*])*
+        $vis struct $name {
+            $(
+                $(#[$($field_meta)*])*
+                $field_vis $field: $ty,
+            )*
+        }
+
+        impl $name {
+            /// Returns a context with the definitions of every field registered.
+            #[allow(dead_code)]
+            $vis fn context() -> $crate::ParserContext<'static> {
+                let mut ctx = $crate::ParserContext::new();
+                if let ::std::option::Option::Some(about) =
+                    $crate::define_args!(@help $(#[$($meta)*])*)
+                {
+                    ctx.before_help(about);
+                }
+                Self::register(&mut ctx);
+                ctx
+            }
+
+            /// Registers the definitions of every field in `ctx`.
+            #[allow(dead_code)]
+            $vis fn register(ctx: &mut $crate::ParserContext<'static>) {
+                $(
+                    $crate::define_args!(
+                        @register ctx, $ty, [$(#[$($field_meta)*])*],
+                        $kind($($spec)*) $(.$method($($arg)*))*
+                    );
+                )*
+            }
+
+            /// Reads every field from arguments parsed with [`context`](Self::context).
+            #[allow(dead_code)]
+            $vis fn from_matches(
+                matches: &$crate::Matches,
+            ) -> ::std::result::Result<Self, $crate::ValueError> {
+                ::std::result::Result::Ok(Self {
+                    $($field: $crate::define_args!(@get matches, $ty, $kind($($spec)*)),)*
+                })
+            }
+        }
+    };
+
+    (@register $ctx:ident, $ty:ty, [$($attr:tt)*], flag($spec:expr) $($builder:tt)*) => {
+        $crate::define_args!(
+            @option $ctx, $spec, Flag, false, $crate::define_args!(@help $($attr)*), $($builder)*
+        )
+    };
+    (@register $ctx:ident, $ty:ty, [$($attr:tt)*], count($spec:expr) $($builder:tt)*) => {
+        $crate::define_args!(
+            @option $ctx, $spec, Flag, false, $crate::define_args!(@help $($attr)*), $($builder)*
+        )
+    };
+    (@register $ctx:ident, $ty:ty, [$($attr:tt)*], option($spec:expr) $($builder:tt)*) => {
+        $crate::define_args!(
+            @option $ctx, $spec, Value, false, $crate::define_args!(@help $($attr)*), $($builder)*
+        )
+    };
+    (@register $ctx:ident, $ty:ty, [$($attr:tt)*], required($spec:expr) $($builder:tt)*) => {
+        $crate::define_args!(
+            @option $ctx, $spec, Value, true, $crate::define_args!(@help $($attr)*), $($builder)*
+        )
+    };
+    (@register $ctx:ident, $ty:ty, [$($attr:tt)*], positional($name:expr) $($builder:tt)*) => {
+        $crate::define_args!(@positional $ctx, $name, false, [$($attr)*], $($builder)*)
+    };
+    (@register $ctx:ident, $ty:ty, [$($attr:tt)*], positionals($name:expr) $($builder:tt)*) => {
+        $crate::define_args!(@positional $ctx, $name, true, [$($attr)*], $($builder)*)
+    };
+    (@register $ctx:ident, $ty:ty, [$($attr:tt)*], flatten()) => {
+        <$ty>::register($ctx)
+    };
+    (@register $ctx:ident, $ty:ty, [$($attr:tt)*], $kind:ident $($rest:tt)*) => {
+        ::std::compile_error!(::std::concat!(
+            "unknown argument kind `",
+            ::std::stringify!($kind),
+            "`"
+        ))
+    };
+
+    (
+        @option $ctx:ident, $spec:expr, $action:ident, $required:expr, $help:expr,
+        $($builder:tt)*
+    ) => {
+        let mut spec = $crate::ArgSpec::parse($spec)
+            .unwrap_or_else(|err| ::std::panic!("{}", err))
+            .action($crate::ArgAction::$action)
+            .required($required);
+        // The spec string's help text wins over the doc comment
+        spec.help = spec.help.or($help);
+        if let ::std::result::Result::Err(err) = $ctx.register(spec $($builder)*) {
+            ::std::panic!("{}", err);
+        }
+    };
+
+    (@positional $ctx:ident, $name:expr, $variadic:expr, [$($attr:tt)*], $($builder:tt)*) => {
+        let mut spec = $crate::PositionalSpec::new($name).variadic($variadic);
+        spec.help = $crate::define_args!(@help $($attr)*);
+        $ctx.positional(spec $($builder)*);
+    };
+
+    (@help $($attr:tt)*) => {
+        $crate::define_args!(@docs [] $($attr)*)
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+    };
+    (@docs [$($doc:tt)*] #[doc = $line:literal] $($rest:tt)*) => {
+        $crate::define_args!(@docs [$($doc)* $line] $($rest)*)
+    };
+    (@docs [$($doc:tt)*] #[$($other:tt)*] $($rest:tt)*) => {
+        $crate::define_args!(@docs [$($doc)*] $($rest)*)
+    };
+    (@docs [$($doc:tt)*]) => {
+        ::std::concat!($($doc, "\n"),*)
+    };
+
+    (@get $matches:ident, $ty:ty, flag($spec:expr)) => {
+        $matches.has($crate::define_args!(@def $spec))
+    };
+    (@get $matches:ident, $ty:ty, count($spec:expr)) => {
+        $matches.count($crate::define_args!(@def $spec))
+    };
+    (@get $matches:ident, $ty:ty, option($spec:expr)) => {
+        $matches.value_as($crate::define_args!(@def $spec))?
+    };
+    (@get $matches:ident, $ty:ty, required($spec:expr)) => {
+        $matches
+            .value_as($crate::define_args!(@def $spec))?
+            .expect("required options are checked when parsing")
+    };
+    (@get $matches:ident, $ty:ty, positional($name:expr)) => {
+        $matches.positional_named($name)?
+    };
+    (@get $matches:ident, $ty:ty, positionals($name:expr)) => {
+        $matches.positional_values_as($name)?
+    };
+    (@get $matches:ident, $ty:ty, flatten()) => {
+        <$ty>::from_matches($matches)?
+    };
+    (@get $matches:ident, $ty:ty, $($rest:tt)*) => {
+        ::std::unreachable!()
+    };
+
+    (@def $spec:expr) => {
+        $crate::ArgSpec::parse($spec).expect("checked when the context is built").def
+    };
+}
@@ -0,0 +1,666 @@
+use crate::args::{self, Args};
+use crate::defs::{ArgDef, ArgName, Argument, ParseArgError, ValueError};
+use crate::observe::{SourceKind, ValueSource};
+use crate::parser::{self, ParserContext};
+use crate::spec::ArgSpec;
+use crate::types::{FromArgument, TryFromArgument};
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// Parsed arguments indexed by the definition they matched.
+///
+/// `Matches` is produced by parsing with a [`ParserContext`]. While the command line is
+/// parsed, every flag and option is recorded under the id of its definition, so looking up
+/// a definition takes constant time instead of scanning all arguments like [`Args::find`]
+/// does. Any name of a definition finds every occurrence, whether it was given in short or
+/// long form.
+///
+/// Arguments of contexts nested with [`ParserContext::nest`] are not indexed; query them
+/// through [`Args::scope`].
+///
+/// `Matches` dereferences to [`Args`], so every other query remains available.
+///
+/// # Examples
+///
+/// ```
+/// # use argsparse::{ArgDef, Matches, OptionArg, ParserContext};
+/// let mut ctx = ParserContext::new();
+/// ctx.flag('v', "verbose").option('o', "output");
+///
+/// let matches = Matches::parse(&["-v", "--output", "out.txt", "--verbose"], &ctx).unwrap();
+///
+/// // Either name finds both occurrences of the flag
+/// assert_eq!(matches.count(ArgDef::Long("verbose")), 2);
+///
+/// let output = matches.find::<OptionArg>(ArgDef::Short('o')).unwrap();
+/// assert_eq!(output.value, "out.txt");
+/// ```
+///
+/// [`ParserContext`]: crate::ParserContext
+/// [`ParserContext::nest`]: crate::ParserContext::nest
+/// [`Args`]: crate::Args
+/// [`Args::find`]: crate::Args::find
+/// [`Args::scope`]: crate::Args::scope
+#[derive(Debug)]
+pub struct Matches<'a, 'c> {
+    /// The parsed arguments, in command-line order.
+    args: Args<'a>,
+    /// The context the arguments were parsed with.
+    ctx: &'c ParserContext<'c>,
+    /// For each definition id, the positions in `args` of its occurrences.
+    occurrences: Vec<Vec<usize>>,
+    /// The positions in `args` of the positionals, in command-line order.
+    positionals: Vec<usize>,
+    /// The warnings raised while parsing, such as uses of deprecated arguments.
+    warnings: Vec<String>,
+}
+
+/// Something about a parsed command line worth a second look, reported by
+/// [`Matches::audit`].
+///
+/// Positions are indices into the parsed arguments, so `matches[index]` is the argument
+/// at fault.
+#[derive(Debug, PartialEq, Clone)]
+#[non_exhaustive]
+pub enum Finding<'c> {
+    /// An occurrence made irrelevant by a later one: an earlier value of an option given
+    /// several times, or an occurrence dropped by [`ArgSpec::overrides_with`] or `=false`.
+    ///
+    /// [`ArgSpec::overrides_with`]: crate::ArgSpec::overrides_with
+    Overridden {
+        /// The position of the overridden argument.
+        index: usize,
+        /// The position of the argument that overrode it.
+        by: usize,
+    },
+    /// A value that another source ranked higher by the [`precedence`] won over. Default
+    /// values are not reported, as they are meant to be superseded.
+    ///
+    /// [`precedence`]: crate::ParserContext::precedence
+    Superseded {
+        /// The definition the value was for.
+        spec: &'c ArgSpec<'c>,
+        /// Where the superseded value came from.
+        source: ValueSource<'c>,
+        /// Where the value in effect came from.
+        by: ValueSource<'c>,
+    },
+    /// A use of a deprecated argument.
+    Deprecated {
+        /// The position of the argument.
+        index: usize,
+    },
+}
+
+impl<'a, 'c> Deref for Matches<'a, 'c> {
+    type Target = Args<'a>;
+
+    fn deref(&self) -> &Args<'a> {
+        &self.args
+    }
+}
+
+impl<'a, 'c> Matches<'a, 'c> {
+    /// Parses a list of command-line arguments with `ctx`, indexing them by definition.
+    ///
+    /// Performs the same validation as [`Args::parse_with_context`].
+    ///
+    /// [`Args::parse_with_context`]: crate::Args::parse_with_context
+    pub fn parse(args: &'a [&str], ctx: &'c ParserContext<'c>) -> Result<Self, ParseArgError<'a>> {
+        parser::parse_with_ctx(args, ctx)
+    }
+
+    /// Builds an empty index for `ctx`, to be filled with [`push`].
+    ///
+    /// [`push`]: Matches::push
+    pub(crate) fn new(ctx: &'c ParserContext<'c>) -> Self {
+        Self {
+            args: Args::default(),
+            ctx,
+            occurrences: vec![Vec::new(); ctx.len()],
+            positionals: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Converts the matches into ones that own all of their strings.
+    pub(crate) fn into_owned(self) -> Matches<'static, 'c> {
+        Matches {
+            args: self.args.into_owned(),
+            ctx: self.ctx,
+            occurrences: self.occurrences,
+            positionals: self.positionals,
+            warnings: self.warnings,
+        }
+    }
+
+    /// Appends a parsed argument, recording it under its definition id.
+    ///
+    /// A flag given `true` is recorded as the flag itself, and one given `false` drops its
+    /// earlier occurrences.
+    pub(crate) fn push(&mut self, mut arg: Argument<'a>) {
+        if let Argument::Option { name, .. } = &arg
+            && self.ctx.is_sensitive(name)
+        {
+            self.args.redact_name(name);
+        }
+        if let Some(id) = arg.name().and_then(|name| self.ctx.id_of(name)) {
+            if let Argument::Option { name, value } = &arg
+                && self.ctx.specs()[id].is_boolean()
+            {
+                if value == "false" {
+                    self.occurrences[id].clear();
+                    self.args.push(arg);
+                    return;
+                }
+                arg = Argument::Flag { name: name.clone() };
+            }
+            self.occurrences[id].push(self.args.len());
+        } else if let Argument::Positional { .. } = arg {
+            self.positionals.push(self.args.len());
+        }
+        self.args.push(arg);
+    }
+
+    /// Drops the occurrences of the definitions overridden by a later occurrence of one of
+    /// their [`overrides_with`] definitions.
+    ///
+    /// [`overrides_with`]: crate::ArgSpec::overrides_with
+    pub(crate) fn resolve_overrides(&mut self) {
+        for (id, spec) in self.ctx.specs().iter().enumerate() {
+            for &def in spec.overrides_with {
+                let Some(other) = self.ctx.id_of_def(def).filter(|&other| other != id) else {
+                    continue;
+                };
+                match (self.occurrences[id].last(), self.occurrences[other].last()) {
+                    (Some(last), Some(other_last)) if last < other_last => {
+                        self.occurrences[id].clear()
+                    }
+                    (Some(_), Some(_)) => self.occurrences[other].clear(),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Records a warning raised while parsing.
+    pub(crate) fn warn(&mut self, warning: String) {
+        self.warnings.push(warning);
+    }
+
+    /// Returns the warnings raised while parsing, such as uses of deprecated arguments, in
+    /// command-line order.
+    ///
+    /// Warnings do not fail the parse; show them to the user as the program sees fit.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Records where the arguments after the `--` separator begin.
+    pub(crate) fn set_trailing(&mut self, start: Option<usize>) {
+        self.args.set_trailing(start);
+    }
+
+    /// Returns the context the arguments were parsed with.
+    pub fn context(&self) -> &'c ParserContext<'c> {
+        self.ctx
+    }
+
+    /// Consumes the matches, returning the plain list of arguments.
+    pub fn into_args(self) -> Args<'a> {
+        self.args
+    }
+
+    /// Renders the arguments like [`Args::snapshot`], followed by the options that were
+    /// absent but resolve to a value, with their source: `env NAME`, `config PATH` or
+    /// `default`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ArgSpec, Matches, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose");
+    /// ctx.register(ArgSpec::new(ArgDef::Long("port")).default("8080")).unwrap();
+    ///
+    /// let matches = Matches::parse(&["-v"], &ctx).unwrap();
+    /// assert_eq!(matches.snapshot(), "\
+    /// flag -v [cli]
+    /// option --port=\"8080\" [default]
+    /// ");
+    /// ```
+    ///
+    /// [`Args::snapshot`]: crate::Args::snapshot
+    pub fn snapshot(&self) -> String {
+        let mut out = self.args.snapshot();
+        for (id, spec) in self.ctx.specs().iter().enumerate() {
+            if !self.occurrences[id].is_empty() {
+                continue;
+            }
+            let name = match spec.def {
+                ArgDef::Short(short) => ArgName::Short(short),
+                ArgDef::Long(long) | ArgDef::ShortAndLong { long, .. } => {
+                    ArgName::Long(long.into())
+                }
+            };
+            let Some((value, source)) = self.ctx.fallback(spec) else {
+                continue;
+            };
+            let source = match source {
+                ValueSource::Env(var) => format!("env {}", var),
+                ValueSource::ConfigFile(path) => format!("config {}", path.display()),
+                _ => "default".to_string(),
+            };
+            let value = if spec.sensitive { args::REDACTED.into() } else { value };
+            let arg = Argument::Option { name, value: value.into() };
+            args::snapshot_line(&mut out, &arg, &source);
+        }
+        out
+    }
+
+    /// Returns every occurrence of the definition `def`, in command-line order.
+    ///
+    /// Returns an empty iterator if `def` is not part of the context.
+    pub fn occurrences(&self, def: ArgDef) -> impl Iterator<Item = &Argument<'a>> {
+        self.ctx
+            .id_of_def(def)
+            .map_or(&[][..], |id| &self.occurrences[id][..])
+            .iter()
+            .map(|&index| &self.args[index])
+    }
+
+    /// Returns the number of times the definition `def` appeared.
+    pub fn count(&self, def: ArgDef) -> usize {
+        self.ctx
+            .id_of_def(def)
+            .map_or(0, |id| self.occurrences[id].len())
+    }
+
+    /// Returns `true` if the definition `def` appeared at least once.
+    pub fn has(&self, def: ArgDef) -> bool {
+        self.count(def) > 0
+    }
+
+    /// Returns the value of the definition `def`, falling back to its environment variable,
+    /// the configuration and then its default value when it is absent from the command line,
+    /// or in the order set by the [`precedence`].
+    ///
+    /// When the option appears several times, the last value wins. Returns `None` if no
+    /// source provides a value, or if `def` is not part of the context.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use argsparse::{ArgDef, ArgSpec, Matches, ParserContext};
+    /// let port = ArgDef::ShortAndLong { short: 'p', long: "port" };
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(port).env("MYAPP_DOC_PORT").default("8080")).unwrap();
+    ///
+    /// let matches = Matches::parse(&["--port", "80", "--port", "443"], &ctx).unwrap();
+    /// assert_eq!(matches.value_of(port).as_deref(), Some("443"));
+    ///
+    /// let matches = Matches::parse(&[], &ctx).unwrap();
+    /// assert_eq!(matches.value_of(port).as_deref(), Some("8080"));
+    ///
+    /// unsafe { std::env::set_var("MYAPP_DOC_PORT", "3000") };
+    /// assert_eq!(matches.value_of(port).as_deref(), Some("3000"));
+    /// ```
+    ///
+    /// [`precedence`]: crate::ParserContext::precedence
+    pub fn value_of(&self, def: ArgDef) -> Option<Cow<'a, str>> {
+        self.value_with_source(def).map(|(value, _)| value)
+    }
+
+    /// Returns the value of the definition `def` like [`value_of`], along with where it came
+    /// from.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use argsparse::{ArgDef, ArgSpec, Matches, ParserContext, ValueSource};
+    /// let port = ArgDef::ShortAndLong { short: 'p', long: "port" };
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(port).env("MYAPP_DOC_SOURCE_PORT").default("8080")).unwrap();
+    ///
+    /// let matches = Matches::parse(&["-p", "80"], &ctx).unwrap();
+    /// assert_eq!(matches.value_with_source(port), Some(("80".into(), ValueSource::CommandLine)));
+    ///
+    /// let matches = Matches::parse(&[], &ctx).unwrap();
+    /// let (value, source) = matches.value_with_source(port).unwrap();
+    /// let message = format!("port {} came from {}", value, source);
+    /// assert_eq!(message, "port 8080 came from the default value");
+    ///
+    /// unsafe { std::env::set_var("MYAPP_DOC_SOURCE_PORT", "3000") };
+    /// let source = ValueSource::Env("MYAPP_DOC_SOURCE_PORT");
+    /// assert_eq!(matches.value_with_source(port), Some(("3000".into(), source)));
+    /// ```
+    ///
+    /// [`value_of`]: Matches::value_of
+    pub fn value_with_source(&self, def: ArgDef) -> Option<(Cow<'a, str>, ValueSource<'c>)> {
+        let spec = &self.ctx.specs()[self.ctx.id_of_def(def)?];
+
+        let given = self.occurrences(def).filter_map(|arg| match arg {
+            Argument::Option { value, .. } => Some(value.clone()),
+            _ => None,
+        });
+        let mut given = given.last().map(|value| (value, ValueSource::CommandLine));
+        self.ctx.precedence_of(spec).iter().find_map(|&kind| match kind {
+            SourceKind::CommandLine => given.take(),
+            kind => {
+                let (value, source) = self.ctx.value_from(spec, kind)?;
+                Some((Cow::Owned(value), source))
+            }
+        })
+    }
+
+    /// Returns every option of the context that resolves to a value, with the value and
+    /// where it came from, in the order the options were defined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ArgSpec, Matches, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.option('o', "output");
+    /// ctx.register(ArgSpec::new(ArgDef::Long("jobs")).default("4")).unwrap();
+    /// ctx.register(ArgSpec::new(ArgDef::Long("color"))).unwrap();
+    /// ctx.config_values("app.conf", [("color", "never")]);
+    ///
+    /// let matches = Matches::parse(&["-o", "out.txt"], &ctx).unwrap();
+    /// let settings: Vec<_> = matches.resolved()
+    ///     .map(|(spec, value, source)| format!("{} = {} ({})", spec.def, value, source))
+    ///     .collect();
+    ///
+    /// assert_eq!(settings, [
+    ///     "-o, --output = out.txt (the command line)",
+    ///     "--jobs = 4 (the default value)",
+    ///     "--color = never (the config file app.conf)",
+    /// ]);
+    /// ```
+    pub fn resolved(
+        &self,
+    ) -> impl Iterator<Item = (&'c ArgSpec<'c>, Cow<'a, str>, ValueSource<'c>)> + '_ {
+        self.ctx.specs().iter().filter_map(|spec| {
+            let (value, source) = self.value_with_source(spec.def)?;
+            Some((spec, value, source))
+        })
+    }
+
+    /// Returns the value of the option `def` like [`value_of`], converted to type `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ArgSpec, Matches, ParserContext};
+    /// let port = ArgDef::ShortAndLong { short: 'p', long: "port" };
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(port).default("8080")).unwrap();
+    ///
+    /// let matches = Matches::parse(&[], &ctx).unwrap();
+    /// assert_eq!(matches.value_as::<u16>(port).unwrap(), Some(8080));
+    ///
+    /// let matches = Matches::parse(&["-p", "http"], &ctx).unwrap();
+    /// let err = matches.value_as::<u16>(port).unwrap_err();
+    /// assert_eq!(err.to_string(), "invalid value 'http' for --port: expected u16 (invalid digit found in string)");
+    /// ```
+    ///
+    /// [`value_of`]: Matches::value_of
+    pub fn value_as<T>(&self, def: ArgDef) -> Result<Option<T>, ValueError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let arg = match def {
+            ArgDef::Short(short) => format!("-{}", short),
+            ArgDef::Long(long) | ArgDef::ShortAndLong { long, .. } => format!("--{}", long),
+        };
+        let sensitive = self.ctx.id_of_def(def).is_some_and(|id| self.ctx.specs()[id].sensitive);
+        self.value_of(def)
+            .map(|value| ValueError::parse(arg, &value))
+            .transpose()
+            .map_err(|err| match sensitive {
+                true => ValueError { value: args::REDACTED.into(), ..err },
+                false => err,
+            })
+    }
+
+    /// Returns the values bound to the named positional `name`.
+    ///
+    /// This is a single value for an ordinary positional that was supplied, every remaining
+    /// operand for a [`variadic`] one, and nothing if it was not supplied or is not defined.
+    ///
+    /// [`variadic`]: crate::PositionalSpec::variadic
+    pub fn positional_values(&self, name: &str) -> Vec<&str> {
+        let specs = self.ctx.positionals();
+        let Some(index) = specs.iter().position(|spec| spec.name == name) else {
+            return Vec::new();
+        };
+
+        let values = self.positionals.iter().skip(index).map(|&i| match &self.args[i] {
+            Argument::Positional { value } => value.as_ref(),
+            _ => unreachable!("positionals only indexes positional arguments"),
+        });
+
+        if specs[index].variadic {
+            values.collect()
+        } else {
+            values.take(1).collect()
+        }
+    }
+
+    /// Returns the values bound to the named positional `name`, converted to type `T`.
+    ///
+    /// Fails with a [`ValueError`] for the first value that does not convert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Matches, ParserContext, PositionalSpec};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.positional(PositionalSpec::new("NUMBERS").variadic(true));
+    ///
+    /// let matches = Matches::parse(&["1", "2", "3"], &ctx).unwrap();
+    /// let numbers: Vec<u32> = matches.positional_values_as("NUMBERS").unwrap();
+    ///
+    /// assert_eq!(numbers, [1, 2, 3]);
+    /// ```
+    ///
+    /// [`ValueError`]: crate::ValueError
+    pub fn positional_values_as<T>(&self, name: &str) -> Result<Vec<T>, ValueError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.positional_values(name)
+            .into_iter()
+            .map(|value| ValueError::parse(format!("<{}>", name), value))
+            .collect()
+    }
+
+    /// Returns the value of the named positional `name`, converted to type `T`.
+    ///
+    /// This is the named counterpart of [`Args::positional`]. Returns `Ok(None)` if the
+    /// positional was not supplied or is not defined. For a variadic positional, the first
+    /// operand it captured is converted; use [`positional_values_as`] to get all of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Matches, ParserContext, PositionalSpec};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.positional(PositionalSpec::new("HOST").required(true))
+    ///     .positional(PositionalSpec::new("PORT"));
+    ///
+    /// let matches = Matches::parse(&["localhost", "http"], &ctx).unwrap();
+    ///
+    /// assert_eq!(matches.positional_named::<String>("HOST").unwrap().as_deref(), Some("localhost"));
+    ///
+    /// let err = matches.positional_named::<u16>("PORT").unwrap_err();
+    /// assert_eq!(err.to_string(), "invalid value 'http' for <PORT>: expected u16 (invalid digit found in string)");
+    /// ```
+    ///
+    /// [`Args::positional`]: crate::Args::positional
+    /// [`positional_values_as`]: Matches::positional_values_as
+    pub fn positional_named<T>(&self, name: &str) -> Result<Option<T>, ValueError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.positional_values(name)
+            .first()
+            .map(|value| ValueError::parse(format!("<{}>", name), value))
+            .transpose()
+    }
+
+    /// Returns the first occurrence of the definition `def`, parsed into type `T`.
+    ///
+    /// See [`Args::find`] for the unindexed equivalent.
+    ///
+    /// [`Args::find`]: crate::Args::find
+    pub fn find<T: FromArgument<'a>>(&'a self, def: ArgDef) -> Option<T> {
+        self.occurrences(def)
+            .next()
+            .and_then(|arg| T::from_argument(arg))
+    }
+
+    /// Returns the first occurrence of the definition `def`, converted with
+    /// [`TryFromArgument`] so that a value that cannot be converted is reported.
+    ///
+    /// See [`Args::try_find`] for the unindexed equivalent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Matches, Parsed, ParserContext};
+    /// let jobs = ArgDef::ShortAndLong { short: 'j', long: "jobs" };
+    /// let mut ctx = ParserContext::new();
+    /// ctx.option('j', "jobs");
+    ///
+    /// let matches = Matches::parse(&["--jobs", "4"], &ctx).unwrap();
+    /// assert_eq!(matches.try_find::<Parsed<u8>>(jobs).unwrap(), Some(Parsed(4)));
+    ///
+    /// let matches = Matches::parse(&["-j", "all"], &ctx).unwrap();
+    /// assert_eq!(matches.try_find::<Parsed<u8>>(jobs).unwrap_err().value, "all");
+    /// ```
+    ///
+    /// [`TryFromArgument`]: crate::TryFromArgument
+    /// [`Args::try_find`]: crate::Args::try_find
+    pub fn try_find<T>(&'a self, def: ArgDef) -> Result<Option<T>, ValueError>
+    where
+        T: TryFromArgument<'a>,
+    {
+        self.occurrences(def).next().map_or(Ok(None), T::try_from_argument)
+    }
+
+    /// Reviews the command line for occurrences that were overridden, values that lost to
+    /// another source, and deprecated arguments, such as when checking wrapper scripts in CI.
+    ///
+    /// Findings on the command line come first, in command-line order, followed by the
+    /// superseded values in the order the options were defined. Options read as lists with
+    /// [`Args::find_all_for`] are still reported as overridden when repeated, since only the
+    /// context knows how a value is read; filter them out as needed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Finding, Matches, ParserContext};
+    /// # use argsparse::{SourceKind, ValueSource};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.option('j', "jobs");
+    /// ctx.register(ArgSpec::new(ArgDef::Long("fast")).action(ArgAction::Flag).deprecated("-j"))
+    ///     .unwrap();
+    /// ctx.register(ArgSpec::new(ArgDef::Long("color")).env("MYAPP_DOC_AUDIT_COLOR")).unwrap();
+    /// ctx.precedence(&[SourceKind::Env, SourceKind::CommandLine]);
+    ///
+    /// unsafe { std::env::set_var("MYAPP_DOC_AUDIT_COLOR", "never") };
+    /// let args = ["-j", "2", "--fast", "--color", "always", "-j", "8"];
+    /// let matches = Matches::parse(&args, &ctx).unwrap();
+    ///
+    /// let findings = matches.audit();
+    /// assert_eq!(findings[..2], [
+    ///     Finding::Overridden { index: 0, by: 3 },
+    ///     Finding::Deprecated { index: 1 },
+    /// ]);
+    /// assert!(matches!(findings[2], Finding::Superseded {
+    ///     source: ValueSource::CommandLine,
+    ///     by: ValueSource::Env("MYAPP_DOC_AUDIT_COLOR"),
+    ///     ..
+    /// }));
+    /// ```
+    ///
+    /// [`Args::find_all_for`]: crate::Args::find_all_for
+    pub fn audit(&self) -> Vec<Finding<'c>> {
+        let specs = self.ctx.specs();
+        let ids: Vec<Option<usize>> = self
+            .args
+            .iter()
+            .map(|arg| arg.name().and_then(|name| self.ctx.id_of(name)))
+            .collect();
+        // The definitions whose occurrences drop those of definition `id`
+        let overriders = |id: usize| {
+            let def = specs[id].def;
+            let mut related = vec![id];
+            related.extend(specs[id].overrides_with.iter().filter_map(|&d| self.ctx.id_of_def(d)));
+            related.extend(
+                (0..specs.len()).filter(|&other| specs[other].overrides_with.contains(&def)),
+            );
+            related
+        };
+
+        let mut findings = Vec::new();
+        for (index, arg) in self.args.iter().enumerate() {
+            let Some(id) = ids[index] else {
+                continue;
+            };
+            let spec = &specs[id];
+            let occurrences = &self.occurrences[id];
+            let dropped = !occurrences.contains(&index);
+            let turned_off = dropped
+                && spec.is_boolean()
+                && matches!(arg, Argument::Option { value, .. } if value == "false");
+
+            let by = if turned_off {
+                None
+            } else if dropped {
+                let related = overriders(id);
+                (index + 1..ids.len())
+                    .find(|&later| ids[later].is_some_and(|other| related.contains(&other)))
+            } else if matches!(arg, Argument::Option { .. }) {
+                occurrences.last().copied().filter(|&last| last > index)
+            } else {
+                None
+            };
+            if let Some(by) = by {
+                findings.push(Finding::Overridden { index, by });
+            }
+            if spec.is_deprecated(self.ctx.version) {
+                findings.push(Finding::Deprecated { index });
+            }
+        }
+
+        for spec in specs {
+            let Some((_, by)) = self.value_with_source(spec.def) else {
+                continue;
+            };
+            let precedence = self.ctx.precedence_of(spec);
+            let lower = precedence.iter().skip_while(|&&kind| kind != by.kind()).skip(1);
+            for &kind in lower {
+                let source = match kind {
+                    SourceKind::CommandLine => self
+                        .occurrences(spec.def)
+                        .any(|arg| matches!(arg, Argument::Option { .. }))
+                        .then_some(ValueSource::CommandLine),
+                    SourceKind::Default => None,
+                    kind => self.ctx.value_from(spec, kind).map(|(_, source)| source),
+                };
+                if let Some(source) = source {
+                    findings.push(Finding::Superseded { spec, source, by });
+                }
+            }
+        }
+
+        findings
+    }
+}
@@ -0,0 +1,434 @@
+use crate::defs::Limit;
+use std::fmt;
+
+/// A piece of user-facing text, along with the values it is built from.
+///
+/// Every error message and help heading goes through a `Message`, so that a [`Catalog`]
+/// can translate it. The [`Display`] implementation renders the built-in English text.
+///
+/// [`Display`]: std::fmt::Display
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[non_exhaustive]
+pub enum Message<'m> {
+    /// The heading of the usage line (`Usage`).
+    Usage,
+    /// The placeholder for the optional options in the usage line (`[OPTIONS]`).
+    OptionsPlaceholder,
+    /// The heading of the positionals section (`Arguments`).
+    ArgumentsHeading,
+    /// The heading of the options section (`Options`).
+    OptionsHeading,
+    /// The heading of the examples section (`Examples`).
+    ExamplesHeading,
+    /// The default value of an option, appended to its description.
+    Default(&'m str),
+    /// The environment variable of an option, appended to its description.
+    Env(&'m str),
+    /// The possible values of an option, appended to its description.
+    PossibleValues(&'m [&'m str]),
+    /// The version that introduced an option, appended to its description.
+    Since(&'m str),
+    /// The deprecation of an option, appended to its description.
+    Deprecated {
+        /// The version from which the option is deprecated, if known.
+        since: Option<&'m str>,
+        /// The version in which the option will be removed, if planned.
+        removal: Option<&'m str>,
+    },
+    /// The documentation URL of an option, appended to its description.
+    Docs(&'m str),
+    /// The documentation URL of the program, shown after the options.
+    Documentation(&'m str),
+    /// The label in front of an error message (`error`).
+    Error,
+    /// A defined name or value close to the one at fault.
+    Suggestion(&'m str),
+    /// The hint closing an error report, pointing to the help.
+    TryHelp,
+    /// An operator of an expression without the expression it applies to.
+    MissingOperand(&'m str),
+    /// A parenthesis of an expression without its counterpart.
+    UnmatchedParen(&'m str),
+    /// A malformed argument.
+    MalformedArg {
+        /// The position of the token among the parsed arguments, starting at 0.
+        index: usize,
+        /// The offending token.
+        token: &'m str,
+    },
+    /// An option that is not defined, as typed (e.g., `--frobnicate`).
+    UnknownOption(&'m str),
+    /// A short cluster member that is not a standalone character.
+    InvalidShort(&'m str),
+    /// A required option that did not appear.
+    MissingRequired(&'m str),
+    /// A required positional that was not supplied, by name.
+    MissingPositional(&'m str),
+    /// A positional beyond the ones defined.
+    UnexpectedPositional(&'m str),
+    /// A value outside the possible values of its definition.
+    InvalidValue {
+        /// The argument the value was given to.
+        arg: &'m str,
+        /// The rejected value.
+        value: &'m str,
+        /// The values the definition accepts.
+        possible: &'m [String],
+    },
+    /// A value rejected by the range or the validator of its definition.
+    RejectedValue {
+        /// The argument the value was given to.
+        arg: &'m str,
+        /// The rejected value.
+        value: &'m str,
+        /// Why the value was rejected.
+        reason: &'m str,
+    },
+    /// A deprecated argument that was used.
+    DeprecatedArg {
+        /// The argument, as typed.
+        arg: &'m str,
+        /// The version from which the argument is deprecated, if known.
+        since: Option<&'m str>,
+        /// The version in which the argument will be removed, if planned.
+        removal: Option<&'m str>,
+        /// What to use instead, if said.
+        note: Option<&'m str>,
+    },
+    /// An argument given under the name it had before being renamed.
+    RenamedArg {
+        /// The old name, as typed.
+        arg: &'m str,
+        /// The current name.
+        new: &'m str,
+    },
+    /// An argument removed in the current version that was used.
+    RemovedArg {
+        /// The argument, as typed.
+        arg: &'m str,
+        /// The version that removed the argument.
+        version: &'m str,
+    },
+    /// An argument given fewer or more times than allowed.
+    OccurrenceCount {
+        /// The argument, as named by its definition.
+        arg: &'m str,
+        /// The number of times it was given.
+        count: usize,
+        /// The number of times it must be given at least.
+        min: usize,
+        /// The number of times it may be given at most, if limited.
+        max: Option<usize>,
+    },
+    /// A resource limit of the context that the command line exceeded.
+    LimitsExceeded {
+        /// The limit that was exceeded.
+        limit: &'m Limit,
+        /// The value of the limit.
+        max: usize,
+    },
+    /// A token holding a control character.
+    ControlCharacter {
+        /// The position of the token among the parsed arguments, starting at 0.
+        index: usize,
+        /// The control character.
+        character: char,
+    },
+    /// An option that takes a value given without one.
+    MissingValue {
+        /// The option, as typed.
+        arg: &'m str,
+        /// The placeholder of the value expected, without its brackets.
+        expected: &'m str,
+    },
+    /// A long option typed with a single dash.
+    SingleDashLong {
+        /// The option, as typed (e.g., `-verbose`).
+        arg: &'m str,
+        /// The long option meant (e.g., `--verbose`).
+        long: &'m str,
+    },
+    /// A long option typed with a single dash, taken as the long option, raised as a warning.
+    CorrectedLong {
+        /// The option, as typed.
+        arg: &'m str,
+        /// The long option it was taken as.
+        long: &'m str,
+    },
+    /// Short options combined in one token where that is not allowed.
+    ShortCluster {
+        /// The position of the token among the parsed arguments, starting at 0.
+        index: usize,
+        /// The offending token.
+        token: &'m str,
+    },
+    /// A value that could not be converted into the requested type.
+    ConversionFailed {
+        /// The argument the value belonged to.
+        arg: &'m str,
+        /// The raw value.
+        value: &'m str,
+        /// The name of the type the value was converted to.
+        expected: &'m str,
+        /// The reason reported by the conversion.
+        reason: &'m str,
+    },
+}
+
+/// A translation of the user-facing messages, set on a context with
+/// [`ParserContext::catalog`].
+///
+/// A catalog only needs to cover what it translates: returning `None` falls back to the
+/// built-in English text. Pick the catalog for the user's locale when building the context.
+///
+/// # Examples
+///
+/// ```
+/// # use argsparse::{Args, Catalog, Message, ParserContext};
+/// #[derive(Debug)]
+/// struct French;
+///
+/// impl Catalog for French {
+///     fn text(&self, message: &Message) -> Option<String> {
+///         match message {
+///             Message::Usage => Some("Utilisation".into()),
+///             Message::OptionsHeading => Some("Options".into()),
+///             Message::UnknownOption(name) => Some(format!("option inconnue '{}'", name)),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// let mut ctx = ParserContext::new();
+/// ctx.flag('v', "verbose").catalog(&French);
+///
+/// assert!(ctx.render_help("app").starts_with("Utilisation: app [OPTIONS]"));
+///
+/// let err = Args::parse_with_context(&["--verbos"], &ctx).unwrap_err();
+/// assert_eq!(ctx.render_error(&err), "option inconnue '--verbos'");
+/// ```
+///
+/// [`ParserContext::catalog`]: crate::ParserContext::catalog
+pub trait Catalog: fmt::Debug + Sync {
+    /// Returns the translation of `message`, or `None` to use the English text.
+    fn text(&self, message: &Message) -> Option<String>;
+}
+
+impl Message<'_> {
+    /// Returns the value of the `{placeholder}` named `key` in an error template.
+    pub(crate) fn placeholder(&self, key: &str) -> Option<String> {
+        let value = match (*self, key) {
+            (
+                Message::MalformedArg { index, .. }
+                | Message::ControlCharacter { index, .. }
+                | Message::ShortCluster { index, .. },
+                "index",
+            ) => return Some(index.to_string()),
+            (Message::ControlCharacter { character, .. }, "value") => {
+                return Some(character.escape_default().to_string());
+            }
+            (Message::OccurrenceCount { count, .. }, "count") => return Some(count.to_string()),
+            (Message::OccurrenceCount { min, .. }, "min") => return Some(min.to_string()),
+            (Message::OccurrenceCount { max, .. }, "max") => return max.map(|max| max.to_string()),
+            (Message::LimitsExceeded { max, .. }, "max") => return Some(max.to_string()),
+            (Message::LimitsExceeded { limit: Limit::TokenLength { index }, .. }, "index") => {
+                return Some(index.to_string());
+            }
+            (Message::LimitsExceeded { limit: Limit::Values { arg }, .. }, "arg") => arg.as_str(),
+            (
+                Message::UnknownOption(arg)
+                | Message::InvalidShort(arg)
+                | Message::MissingRequired(arg)
+                | Message::MissingPositional(arg)
+                | Message::InvalidValue { arg, .. }
+                | Message::RejectedValue { arg, .. }
+                | Message::DeprecatedArg { arg, .. }
+                | Message::RenamedArg { arg, .. }
+                | Message::SingleDashLong { arg, .. }
+                | Message::MissingValue { arg, .. }
+                | Message::RemovedArg { arg, .. }
+                | Message::OccurrenceCount { arg, .. }
+                | Message::ConversionFailed { arg, .. },
+                "arg",
+            ) => arg,
+            (
+                Message::MalformedArg { token: value, .. }
+                | Message::ShortCluster { token: value, .. }
+                | Message::UnexpectedPositional(value)
+                | Message::InvalidValue { value, .. }
+                | Message::RejectedValue { value, .. }
+                | Message::ConversionFailed { value, .. },
+                "value",
+            ) => value,
+            (Message::InvalidValue { possible, .. }, "possible") => {
+                return Some(possible.join(", "));
+            }
+            (
+                Message::ConversionFailed { expected, .. } | Message::MissingValue { expected, .. },
+                "expected",
+            ) => expected,
+            (Message::RemovedArg { version, .. }, "version") => version,
+            (Message::SingleDashLong { long, .. }, "long") => long,
+            (
+                Message::RejectedValue { reason, .. } | Message::ConversionFailed { reason, .. },
+                "reason",
+            ) => reason,
+            _ => return None,
+        };
+        Some(value.to_string())
+    }
+}
+
+impl fmt::Display for Message<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Message::Usage => write!(f, "Usage"),
+            Message::OptionsPlaceholder => write!(f, "[OPTIONS]"),
+            Message::ArgumentsHeading => write!(f, "Arguments"),
+            Message::OptionsHeading => write!(f, "Options"),
+            Message::ExamplesHeading => write!(f, "Examples"),
+            Message::Default(value) => write!(f, "[default: {}]", value),
+            Message::Env(name) => write!(f, "[env: {}]", name),
+            Message::PossibleValues(values) => {
+                write!(f, "[possible values: {}]", values.join(", "))
+            }
+            Message::Since(version) => write!(f, "[since {}]", version),
+            Message::Deprecated { since, removal } => {
+                write!(f, "[deprecated")?;
+                if let Some(since) = since {
+                    write!(f, " since {}", since)?;
+                }
+                if let Some(removal) = removal {
+                    write!(f, ", removed in {}", removal)?;
+                }
+                write!(f, "]")
+            }
+            Message::Docs(url) => write!(f, "[docs: {}]", url),
+            Message::Documentation(url) => write!(f, "Documentation: {}", url),
+            Message::Error => write!(f, "error"),
+            Message::Suggestion(value) => write!(f, "did you mean '{}'?", value),
+            Message::TryHelp => write!(f, "For more information, try '--help'."),
+            Message::MissingOperand(operator) => write!(f, "missing operand for '{}'", operator),
+            Message::UnmatchedParen(paren) => write!(f, "unmatched '{}'", paren),
+            Message::MalformedArg { index, token } => {
+                write!(f, "malformed argument '{}' at position {}", token, index)
+            }
+            Message::UnknownOption(name) => write!(f, "unknown option '{}'", name),
+            Message::InvalidShort(name) => write!(f, "'{}' is not a valid short option", name),
+            Message::MissingRequired(name) => write!(f, "missing required argument '{}'", name),
+            Message::MissingPositional(name) => {
+                write!(f, "missing required argument <{}>", name)
+            }
+            Message::UnexpectedPositional(value) => write!(f, "unexpected argument '{}'", value),
+            Message::InvalidValue { arg, value, possible } => write!(
+                f,
+                "invalid value '{}' for {} [possible values: {}]",
+                value,
+                arg,
+                possible.join(", ")
+            ),
+            Message::RejectedValue { arg, value, reason } => {
+                write!(f, "invalid value '{}' for {}: {}", value, arg, reason)
+            }
+            Message::DeprecatedArg { arg, since, removal, note } => {
+                write!(f, "{} is deprecated", arg)?;
+                if let Some(since) = since {
+                    write!(f, " since {}", since)?;
+                }
+                if let Some(removal) = removal {
+                    write!(f, " and will be removed in {}", removal)?;
+                }
+                if let Some(note) = note {
+                    write!(f, ": {}", note)?;
+                }
+                Ok(())
+            }
+            Message::RenamedArg { arg, new } => {
+                write!(f, "{} is deprecated: it was renamed to {}", arg, new)
+            }
+            Message::MissingValue { arg, expected } => {
+                write!(f, "missing value for '{}': expected <{}>", arg, expected)
+            }
+            Message::SingleDashLong { arg, long } => {
+                write!(f, "unknown option '{}', did you mean '{}'?", arg, long)
+            }
+            Message::CorrectedLong { arg, long } => {
+                write!(f, "{} was taken as {}: long options take two dashes", arg, long)
+            }
+            Message::RemovedArg { arg, version } => {
+                write!(f, "{} was removed in version {}", arg, version)
+            }
+            Message::OccurrenceCount { arg, count, min, max } => {
+                let times = |n: usize| if n == 1 { "time" } else { "times" };
+                match max {
+                    Some(max) if count > max => write!(
+                        f,
+                        "{} may be given at most {} {}, but was given {}",
+                        arg, max, times(max), count
+                    ),
+                    _ => write!(
+                        f,
+                        "{} must be given at least {} {}, but was given {}",
+                        arg, min, times(min), count
+                    ),
+                }
+            }
+            Message::LimitsExceeded { limit, max } => match limit {
+                Limit::Tokens => write!(f, "too many arguments: at most {} are allowed", max),
+                Limit::TokenLength { index } => write!(
+                    f,
+                    "argument at position {} is too long: at most {} bytes are allowed",
+                    index, max
+                ),
+                Limit::Values { arg } => {
+                    write!(f, "too many values for {}: at most {} are allowed", arg, max)
+                }
+            },
+            Message::ControlCharacter { index, character } => write!(
+                f,
+                "control character '{}' in argument at position {}",
+                character.escape_default(),
+                index
+            ),
+            Message::ShortCluster { index, token } => {
+                let text = "short options cannot be combined as in";
+                write!(f, "{} '{}' at position {}", text, token, index)
+            }
+            Message::ConversionFailed { arg, value, expected, reason } => write!(
+                f,
+                "invalid value '{}' for {}: expected {} ({})",
+                value, arg, expected, reason
+            ),
+        }
+    }
+}
+
+/// Replaces the `{placeholder}`s of `template` with the values given by `lookup`.
+///
+/// Braces that do not enclose a known placeholder are kept verbatim.
+pub(crate) fn expand(template: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        match lookup(&rest[1..end]) {
+            Some(value) => {
+                out.push_str(&value);
+                rest = &rest[end + 1..];
+            }
+            // Not a placeholder: keep the brace and move on
+            None => {
+                out.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+
+    out
+}
@@ -0,0 +1,135 @@
+use crate::spec::ArgSpec;
+use std::fmt;
+use std::path::Path;
+
+/// Where the value of an option came from.
+///
+/// The [`Display`] implementation describes the source in English, for messages such as
+/// "port 8000 came from the config file /home/me/.app.conf".
+///
+/// [`Display`]: std::fmt::Display
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum ValueSource<'s> {
+    /// The command line.
+    CommandLine,
+    /// The environment variable of the option, by name.
+    Env(&'s str),
+    /// A configuration file, added with [`ParserContext::config_values`] or
+    /// [`ParserContext::config_file`].
+    ///
+    /// [`ParserContext::config_values`]: crate::ParserContext::config_values
+    /// [`ParserContext::config_file`]: crate::ParserContext::config_file
+    ConfigFile(&'s Path),
+    /// The default value of the option.
+    Default,
+}
+
+impl ValueSource<'_> {
+    /// Returns the kind of the source.
+    pub fn kind(&self) -> SourceKind {
+        match self {
+            ValueSource::CommandLine => SourceKind::CommandLine,
+            ValueSource::Env(_) => SourceKind::Env,
+            ValueSource::ConfigFile(_) => SourceKind::Config,
+            ValueSource::Default => SourceKind::Default,
+        }
+    }
+}
+
+impl fmt::Display for ValueSource<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueSource::CommandLine => write!(f, "the command line"),
+            ValueSource::Env(name) => write!(f, "the environment variable {}", name),
+            ValueSource::ConfigFile(path) => write!(f, "the config file {}", path.display()),
+            ValueSource::Default => write!(f, "the default value"),
+        }
+    }
+}
+
+/// A kind of [`ValueSource`], for ordering sources by precedence.
+///
+/// See [`ParserContext::precedence`].
+///
+/// [`ParserContext::precedence`]: crate::ParserContext::precedence
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum SourceKind {
+    /// The command line.
+    CommandLine,
+    /// The environment variable of the option.
+    Env,
+    /// The configuration files.
+    Config,
+    /// The default value of the option.
+    Default,
+}
+
+/// Callbacks notified of how a command line is used, set on a context with
+/// [`ParserContext::observer`].
+///
+/// Observers gather telemetry or migration metrics without changing how parsing behaves.
+/// Every method does nothing by default, so an observer only implements what it records.
+///
+/// # Examples
+///
+/// ```
+/// # use argsparse::{ArgDef, ArgSpec, Matches, Observer, ParserContext, ValueSource};
+/// use std::sync::Mutex;
+///
+/// #[derive(Debug, Default)]
+/// struct Usage(Mutex<Vec<String>>);
+///
+/// impl Observer for Usage {
+///     fn unknown_option(&self, name: &str) {
+///         self.0.lock().unwrap().push(format!("unknown {}", name));
+///     }
+///
+///     fn deprecated(&self, spec: &ArgSpec) {
+///         self.0.lock().unwrap().push(format!("deprecated {}", spec.def));
+///     }
+///
+///     fn fallback(&self, spec: &ArgSpec, source: ValueSource) {
+///         self.0.lock().unwrap().push(format!("{:?} for {}", source, spec.def));
+///     }
+/// }
+///
+/// let usage = Usage::default();
+/// let mut ctx = ParserContext::new();
+/// ctx.register(ArgSpec::new(ArgDef::Long("color")).deprecated("use --colour")).unwrap();
+/// ctx.register(ArgSpec::new(ArgDef::Long("jobs")).default("4")).unwrap();
+/// ctx.observer(&usage);
+///
+/// Matches::parse(&["--color"], &ctx).unwrap();
+/// assert!(Matches::parse(&["--colr"], &ctx).is_err());
+///
+/// assert_eq!(
+///     *usage.0.lock().unwrap(),
+///     ["deprecated --color", "Default for --jobs", "unknown --colr"]
+/// );
+/// ```
+///
+/// [`ParserContext::observer`]: crate::ParserContext::observer
+pub trait Observer: fmt::Debug + Sync {
+    /// Called when the option `name`, as typed (e.g., `--frobnicate`), is rejected because
+    /// it is not defined.
+    fn unknown_option(&self, name: &str) {
+        let _ = name;
+    }
+
+    /// Called each time a [`deprecated`] argument is given, or one past its [`deprecated_in`]
+    /// version.
+    ///
+    /// [`deprecated`]: crate::ArgSpec::deprecated
+    /// [`deprecated_in`]: crate::ArgSpec::deprecated_in
+    fn deprecated(&self, spec: &ArgSpec) {
+        let _ = spec;
+    }
+
+    /// Called once per parse for each option absent from the command line whose value comes
+    /// from its environment variable, the configuration or its default value.
+    fn fallback(&self, spec: &ArgSpec, source: ValueSource) {
+        let _ = (spec, source);
+    }
+}
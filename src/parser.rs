@@ -1,255 +1,2468 @@
 use crate::ArgDef;
-use crate::args::Args;
+use crate::args::{self, Args};
+use crate::config::DEFAULT_PRECEDENCE;
 use crate::defs::ArgName;
 use crate::defs::Argument;
+use crate::defs::DefinitionConflict;
+use crate::defs::ErrorKind;
+use crate::defs::Limit;
 use crate::defs::ParseArgError;
-use std::collections::HashMap;
-use std::iter::Peekable;
+use crate::defs::RegisterError;
+use crate::defs::ValueError;
+use crate::lookup::{Interner, Lookup, SymbolMap};
+use crate::matches::Matches;
+use crate::messages::{self, Catalog, Message};
+use crate::observe::{Observer, SourceKind};
+use crate::spec::{ArgAction, ArgSpec, PositionalSpec};
+use crate::types::{self, TriState};
+use crate::unicode;
+use std::borrow::Cow;
+use std::iter::{Enumerate, Peekable};
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::str::Chars;
+use std::sync::Arc;
+
+/// A rewrite of the raw tokens run before they are parsed.
+///
+/// Given the position of a token among the arguments and the token itself, returns the
+/// tokens replacing it, or `None` to keep it as is.
+pub type TokenTransform = fn(usize, &str) -> Option<Vec<String>>;
+
+/// The arguments before a subcommand, its name, and the arguments after it, as returned by
+/// [`ParserContext::split_at_subcommand`].
+pub type SubcommandSplit<'t, S> = (&'t [S], &'t str, &'t [S]);
+
+/// Caps on the size of the command lines a context accepts, set with
+/// [`ParserContext::limits`].
+///
+/// Every limit is off by default. Exceeding one fails the parse with
+/// [`ParseArgError::LimitsExceeded`], so services that parse command lines supplied by users
+/// can bound the memory and time spent on them.
+///
+/// [`ParseArgError::LimitsExceeded`]: crate::ParseArgError::LimitsExceeded
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct Limits {
+    max_tokens: Option<usize>,
+    max_token_len: Option<usize>,
+    max_values: Option<usize>,
+}
+
+impl Limits {
+    /// Creates limits that are all off.
+    pub const fn new() -> Self {
+        Self { max_tokens: None, max_token_len: None, max_values: None }
+    }
+
+    /// Sets the number of tokens a command line may hold, after aliases and transforms.
+    pub const fn max_tokens(mut self, max: usize) -> Self {
+        self.max_tokens = Some(max);
+        self
+    }
+
+    /// Sets the length in bytes a single token may have.
+    pub const fn max_token_len(mut self, max: usize) -> Self {
+        self.max_token_len = Some(max);
+        self
+    }
+
+    /// Sets the number of values a single option may be given, over all its occurrences.
+    pub const fn max_values(mut self, max: usize) -> Self {
+        self.max_values = Some(max);
+        self
+    }
+}
+
+/// How several short names may be combined in one token (e.g., `-abc`), set with
+/// [`ParserContext::clustering`].
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum Clustering {
+    /// Any short names may be combined, and the last one may take a value.
+    #[default]
+    Enabled,
+    /// Only defined flags may be combined, so `-abc` is three flags or an error.
+    FlagsOnly,
+    /// Every short name must be given in its own token.
+    Disabled,
+}
+
+/// The number of nested aliases an alias may expand through.
+const MAX_ALIAS_DEPTH: usize = 16;
 
 /// Maintains context for parsing arguments, including definitions and lookup maps.
+#[derive(Debug, Clone)]
 pub struct ParserContext<'a> {
     /// A list of defined arguments.
-    defs: Vec<ArgDef<'a>>,
-    /// Maps short characters (e.g., `-h`) to their index in `defs`.
-    short_map: HashMap<char, usize>,
-    /// Maps long strings (e.g., `--help`) to their index in `defs`.
-    long_map: HashMap<&'a str, usize>,
+    specs: Vec<ArgSpec<'a>>,
+    /// Maps short characters (e.g., `-h`) to their index in `specs`.
+    short_map: Lookup<char>,
+    /// The long names of the definitions, and their normalized spellings, each stored once.
+    longs: Interner<'a>,
+    /// Maps interned long names (e.g., `--help`) to their index in `specs`.
+    long_map: SymbolMap,
+    /// Maps interned long names with `_` turned into `-` to their index in `specs`, filled
+    /// only when [`normalize_underscores`] is set.
+    ///
+    /// [`normalize_underscores`]: ParserContext::normalize_underscores
+    normalized_map: SymbolMap,
+    /// Child contexts whose long names are exposed as `--<prefix>-<name>`.
+    namespaces: Vec<(&'a str, ParserContext<'a>)>,
+    /// The token ending option parsing, if any.
+    terminator: Option<&'a str>,
+    /// Named positionals, in the order they are matched.
+    positionals: Vec<PositionalSpec<'a>>,
+    /// Free text rendered before the usage line of the help output.
+    pub(crate) before_help: Option<&'a str>,
+    /// Free text rendered after the options of the help output.
+    pub(crate) after_help: Option<&'a str>,
+    /// The layout of the help output, replacing the default one.
+    pub(crate) help_template: Option<&'a str>,
+    /// A link to the full documentation of the program, shown in help output.
+    pub(crate) doc_url: Option<&'a str>,
+    /// Example invocations of the program, each with its description.
+    pub(crate) examples: Vec<(&'a str, &'a str)>,
+    /// The version of the program, against which the lifecycle of arguments is checked.
+    pub(crate) version: Option<&'a str>,
+    /// The locale selecting the localized spellings of long names, if any.
+    locale: Option<&'a str>,
+    /// The translation of user-facing messages, if any.
+    catalog: Option<&'a dyn Catalog>,
+    /// The callbacks notified of how the command line is used, if any.
+    observer: Option<&'a dyn Observer>,
+    /// Message templates overriding the catalog for some kinds of errors.
+    error_templates: Vec<(ErrorKind, &'a str)>,
+    /// Rewrites applied to the raw tokens before parsing, in order.
+    transforms: Vec<TokenTransform>,
+    /// Aliases for the first token, with the tokens they expand into.
+    aliases: Vec<(&'a str, Vec<&'a str>)>,
+    /// Layers of configuration values, with the file they were read from, lowest first.
+    pub(crate) configs: Vec<(PathBuf, Vec<(String, String)>)>,
+    /// Variables read from `.env` files, consulted after the environment, latest first.
+    #[cfg(feature = "dotenv")]
+    pub(crate) dotenv: Vec<(String, String)>,
+    /// The order in which the sources of values are consulted.
+    pub(crate) precedence: &'a [SourceKind],
+    /// Whether `-` and `_` are interchangeable in long names.
+    normalize_underscores: bool,
+    /// Whether long names may also be given with a single dash.
+    single_dash_longs: bool,
+    /// Whether long names given with a single dash by mistake are taken as such.
+    correct_single_dash_longs: bool,
+    /// How short names may be combined in one token.
+    clustering: Clustering,
+    /// Caps on the size of the command line.
+    limits: Limits,
+    /// Whether tokens holding control characters are rejected.
+    reject_control_chars: bool,
 }
 
 impl<'a> ParserContext<'a> {
     pub fn new() -> Self {
         Self {
-            defs: Vec::new(),
-            short_map: HashMap::new(),
-            long_map: HashMap::new(),
+            specs: Vec::new(),
+            short_map: Lookup::new(),
+            longs: Interner::new(),
+            long_map: SymbolMap::new(),
+            normalized_map: SymbolMap::new(),
+            namespaces: Vec::new(),
+            terminator: Some("--"),
+            positionals: Vec::new(),
+            before_help: None,
+            after_help: None,
+            help_template: None,
+            doc_url: None,
+            examples: Vec::new(),
+            version: None,
+            locale: None,
+            catalog: None,
+            observer: None,
+            error_templates: Vec::new(),
+            transforms: Vec::new(),
+            aliases: Vec::new(),
+            configs: Vec::new(),
+            #[cfg(feature = "dotenv")]
+            dotenv: Vec::new(),
+            precedence: DEFAULT_PRECEDENCE,
+            normalize_underscores: false,
+            single_dash_longs: false,
+            correct_single_dash_longs: false,
+            clustering: Clustering::Enabled,
+            limits: Limits::new(),
+            reject_control_chars: false,
         }
     }
 
-    pub fn from(defs: Vec<ArgDef<'a>>) -> Self {
+    /// Builds a context from a list of definitions.
+    ///
+    /// Definitions are registered in order. The first definition that reuses a short or
+    /// long name already claimed by an earlier one aborts the build, and the returned
+    /// [`DefinitionConflict`] names both colliding definitions.
+    ///
+    /// Short names are not validated here; build the context with [`TryFrom`] to also reject
+    /// the ones [`register`] rejects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ParserContext};
+    /// let ok = ParserContext::from(vec![ArgDef::Short('v'), ArgDef::Long("quiet")]);
+    /// assert!(ok.is_ok());
+    ///
+    /// let err = ParserContext::from(vec![
+    ///     ArgDef::ShortAndLong { short: 'v', long: "verbose" },
+    ///     ArgDef::Short('v'),
+    /// ]).err().unwrap();
+    ///
+    /// assert_eq!(err.existing, ArgDef::ShortAndLong { short: 'v', long: "verbose" });
+    /// assert_eq!(err.conflicting, ArgDef::Short('v'));
+    /// ```
+    ///
+    /// [`DefinitionConflict`]: crate::DefinitionConflict
+    /// [`register`]: ParserContext::register
+    pub fn from(defs: Vec<ArgDef<'a>>) -> Result<Self, DefinitionConflict<'a>> {
         let mut ctx = Self::new();
-        defs.iter().for_each(|def| {
-            ctx.register(*def).unwrap();
-        });
-        ctx
+        for def in defs {
+            if let Some(conflict) = ctx.find_conflict(def) {
+                return Err(conflict);
+            }
+            ctx.insert(def.into());
+        }
+        Ok(ctx)
+    }
+
+    /// Registers a definition, or a full [`ArgSpec`], with the context.
+    ///
+    /// Fails with a [`RegisterError`] naming the first of its names that is already defined.
+    ///
+    /// # Short names
+    ///
+    /// Any single Unicode character that can be typed on its own may be a short name,
+    /// including non-ASCII letters and single-code-point emoji. Characters that only make
+    /// sense attached to another one (combining accents, joiners, variation selectors, skin
+    /// tone modifiers), as well as whitespace, control characters, `-` and `=`, are rejected
+    /// with [`RegisterError::InvalidShort`].
+    ///
+    /// When parsing, a cluster is split into user-perceived characters: a character made of
+    /// several code points, such as `u` followed by a combining diaeresis, is reported as
+    /// [`ParseArgError::InvalidShort`] rather than exploded into separate shorts.
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Args, ParseArgError, ParserContext, RegisterError};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgDef::Short('ü')).unwrap();
+    /// ctx.register(ArgDef::Short('u')).unwrap();
+    ///
+    /// assert!(Args::parse_with_context(&["-üu"], &ctx).is_ok());
+    ///
+    /// let err = Args::parse_with_context(&["-u\u{308}"], &ctx).unwrap_err();
+    /// assert!(matches!(err, ParseArgError::InvalidShort(s) if s == "u\u{308}"));
+    ///
+    /// let err = ctx.register(ArgDef::Short('\u{308}')).unwrap_err();
+    /// assert_eq!(err, RegisterError::InvalidShort('\u{308}'));
+    /// ```
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ArgSpec, Args, ParseArgError, ParserContext, RegisterError};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgDef::Short('v')).unwrap();
+    /// ctx.register(ArgSpec::new(ArgDef::Long("input")).required(true)).unwrap();
+    ///
+    /// let err = Args::parse_with_context(&["-v"], &ctx).unwrap_err();
+    /// assert!(matches!(err, ParseArgError::MissingRequired(_)));
+    ///
+    /// let dup = ctx.register(ArgDef::ShortAndLong { short: 'v', long: "verbose" }).unwrap_err();
+    /// assert_eq!(dup, RegisterError::DuplicateShort('v'));
+    /// assert_eq!(dup.to_string(), "Short argument -v already defined");
+    /// ```
+    ///
+    /// [`ArgSpec`]: crate::ArgSpec
+    /// [`RegisterError`]: crate::RegisterError
+    /// [`RegisterError::InvalidShort`]: crate::RegisterError::InvalidShort
+    /// [`ParseArgError::InvalidShort`]: crate::ParseArgError::InvalidShort
+    pub fn register(&mut self, arg: impl Into<ArgSpec<'a>>) -> Result<&Self, RegisterError> {
+        let spec = arg.into();
+        self.check(&spec)?;
+        self.insert(spec);
+        Ok(self)
     }
 
-    pub fn register(&mut self, arg: ArgDef<'a>) -> Result<&Self, String> {
+    /// Checks that `spec` can be registered: its short name can be typed on its own, and
+    /// none of its names, former names or localized spellings is taken.
+    fn check(&self, spec: &ArgSpec<'a>) -> Result<(), RegisterError> {
+        if let Some(short) = spec.def.short().filter(|&s| !unicode::is_valid_short(s)) {
+            return Err(RegisterError::InvalidShort(short));
+        }
+
         // Check for conflicts
-        match &arg {
-            ArgDef::Short(s) => {
-                if self.short_map.contains_key(s) {
-                    return Err(format!("Short argument -{} already defined", s));
+        if self.find_conflict(spec.def).is_some() {
+            return Err(match spec.def {
+                ArgDef::Short(s) => RegisterError::DuplicateShort(s),
+                ArgDef::Long(l) => RegisterError::DuplicateLong(l.into()),
+                ArgDef::ShortAndLong { short, long } => {
+                    if self.short_map.contains_key(&short) {
+                        RegisterError::DuplicateShort(short)
+                    } else {
+                        RegisterError::DuplicateLong(long.into())
+                    }
                 }
+            });
+        }
+        if let Some(&old) = spec.renamed_from.iter().find(|old| self.find_long(old).is_some()) {
+            return Err(RegisterError::DuplicateLong(old.into()));
+        }
+        // Spellings of every locale are checked, so that switching locales cannot clash
+        let localized = |spec: &ArgSpec<'a>| spec.localized.iter().map(|&(_, long)| long);
+        let taken = |long: &str| {
+            self.find_long(long).is_some()
+                || self.specs.iter().any(|spec| localized(spec).any(|other| other == long))
+        };
+        if let Some(long) = spec.def.long().into_iter().chain(localized(spec)).find(|l| taken(l)) {
+            return Err(RegisterError::DuplicateLong(long.into()));
+        }
+        Ok(())
+    }
+
+    /// Builds a context from a table of specifications, such as a `static` one.
+    ///
+    /// [`ArgSpec`] and [`PositionalSpec`] are built with `const fn`s, so the whole table can
+    /// be written as a `static` and checked by the compiler; startup then only indexes the
+    /// names. Specifications are registered in order, and the first one that [`register`]
+    /// rejects aborts the build.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Matches, ParserContext, PositionalSpec};
+    /// static OPTIONS: &[ArgSpec] = &[
+    ///     ArgSpec::new(ArgDef::ShortAndLong { short: 'v', long: "verbose" })
+    ///         .action(ArgAction::Flag)
+    ///         .help("Print more"),
+    ///     ArgSpec::new(ArgDef::Long("jobs")).action(ArgAction::Value).default("4"),
+    /// ];
+    /// static ARGUMENTS: &[PositionalSpec] = &[PositionalSpec::new("FILE").required(true)];
+    ///
+    /// let ctx = ParserContext::from_specs(OPTIONS, ARGUMENTS).unwrap();
+    /// let matches = Matches::parse(&["-v", "a.txt"], &ctx).unwrap();
+    ///
+    /// assert_eq!(matches.value_of(ArgDef::Long("jobs")).as_deref(), Some("4"));
+    /// assert_eq!(matches.positional_values("FILE"), ["a.txt"]);
+    /// ```
+    ///
+    /// [`ArgSpec`]: crate::ArgSpec
+    /// [`PositionalSpec`]: crate::PositionalSpec
+    /// [`register`]: ParserContext::register
+    pub fn from_specs(
+        specs: &[ArgSpec<'a>],
+        positionals: &[PositionalSpec<'a>],
+    ) -> Result<Self, RegisterError> {
+        let mut ctx = Self::new();
+        for spec in specs {
+            ctx.register(spec.clone())?;
+        }
+        for spec in positionals {
+            ctx.positional(spec.clone());
+        }
+        Ok(ctx)
+    }
+
+    /// Registers a flag with both a short and a long name, returning the context for chaining.
+    ///
+    /// Unlike definitions added through [`register`], a flag registered this way never
+    /// consumes the token that follows it, so `-v input.txt` leaves `input.txt` positional.
+    ///
+    /// # Panics
+    ///
+    /// Panics on any [`RegisterError`] that [`register`] would return: if either name is
+    /// already defined, or if `short` is not a valid short name. Use [`register`] to handle
+    /// errors coming from definitions that are not known at compile time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Args, FlagArg, OptionArg, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose").option('o', "output");
+    ///
+    /// let args = Args::parse_with_context(&["-v", "in.txt", "--output", "out.txt"], &ctx).unwrap();
+    ///
+    /// assert!(args.find::<FlagArg>(ArgDef::Short('v')).is_some());
+    /// assert!(args.find::<OptionArg>(ArgDef::Long("output")).is_some());
+    /// ```
+    ///
+    /// [`register`]: ParserContext::register
+    /// [`RegisterError`]: crate::RegisterError
+    pub fn flag(&mut self, short: char, long: &'a str) -> &mut Self {
+        self.define(ArgSpec::new(ArgDef::ShortAndLong { short, long }).action(ArgAction::Flag))
+    }
+
+    /// Registers an option with both a short and a long name, returning the context for chaining.
+    ///
+    /// An option registered this way always takes the token that follows it as its value.
+    ///
+    /// # Panics
+    ///
+    /// Panics on any [`RegisterError`] that [`register`] would return: if either name is
+    /// already defined, or if `short` is not a valid short name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Args, OptionArg, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.option('o', "output");
+    ///
+    /// let args = Args::parse_with_context(&["-o", "out.txt"], &ctx).unwrap();
+    /// let opt = args.find::<OptionArg>(ArgDef::Short('o')).unwrap();
+    ///
+    /// assert_eq!(opt.value, "out.txt");
+    /// ```
+    ///
+    /// [`register`]: ParserContext::register
+    /// [`RegisterError`]: crate::RegisterError
+    pub fn option(&mut self, short: char, long: &'a str) -> &mut Self {
+        self.define(ArgSpec::new(ArgDef::ShortAndLong { short, long }).action(ArgAction::Value))
+    }
+
+    /// Registers a tri-state flag with the long name `long`, returning the context for
+    /// chaining.
+    ///
+    /// The flag accepts `--NAME`, `--NAME=always|never|auto` and `--no-NAME`, and resolves
+    /// to a [`TriState`] with [`Args::tri_state`]: [`TriState::Auto`] when absent, and the
+    /// state given last otherwise. A value must be attached with `=`, so the next token is
+    /// never taken as one.
+    ///
+    /// # Panics
+    ///
+    /// Panics on any [`RegisterError`] that [`register`] would return, that is if `long` is
+    /// already defined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Args, ParserContext, TriState};
+    /// let color = ArgDef::Long("color");
+    /// let mut ctx = ParserContext::new();
+    /// ctx.tri_state("color");
+    ///
+    /// let state = |tokens: &[&str]| {
+    ///     Args::parse_with_context(tokens, &ctx).unwrap().tri_state(color).unwrap()
+    /// };
+    /// assert_eq!(state(&[]), TriState::Auto);
+    /// assert_eq!(state(&["--color"]), TriState::Always);
+    /// assert_eq!(state(&["--color", "--no-color"]), TriState::Never);
+    /// assert_eq!(state(&["--no-color", "--color=auto"]), TriState::Auto);
+    ///
+    /// assert!(Args::parse_with_context(&["--color=sometimes"], &ctx).is_err());
+    /// assert!(ctx.render_help("app").contains("--[no-]color"));
+    /// ```
+    ///
+    /// [`TriState`]: crate::TriState
+    /// [`TriState::Auto`]: crate::TriState::Auto
+    /// [`Args::tri_state`]: crate::Args::tri_state
+    /// [`register`]: ParserContext::register
+    /// [`RegisterError`]: crate::RegisterError
+    pub fn tri_state(&mut self, long: &'a str) -> &mut Self {
+        self.define(
+            ArgSpec::new(ArgDef::Long(long))
+                .action(ArgAction::Flag)
+                .value_name("WHEN")
+                .possible_values(TriState::VALUES)
+                .negation("never"),
+        )
+    }
+
+    /// Registers `-h, --help` as an [`exclusive`] flag that prints the help, given either
+    /// alone or as `--help=json` for the help as JSON, returning the context for chaining.
+    ///
+    /// See [`handle_help`] for printing the help once parsed.
+    ///
+    /// # Panics
+    ///
+    /// Panics on any [`RegisterError`] that [`register`] would return, that is if `-h` or
+    /// `--help` is already defined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Matches, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.help_flag();
+    ///
+    /// let matches = Matches::parse(&["--help=json"], &ctx).unwrap();
+    /// assert_eq!(matches.value_of(ArgDef::Long("help")).as_deref(), Some("json"));
+    /// assert!(Matches::parse(&["--help=yaml"], &ctx).is_err());
+    /// ```
+    ///
+    /// [`exclusive`]: crate::ArgSpec::exclusive
+    /// [`handle_help`]: ParserContext::handle_help
+    /// [`register`]: ParserContext::register
+    /// [`RegisterError`]: crate::RegisterError
+    pub fn help_flag(&mut self) -> &mut Self {
+        self.define(
+            ArgSpec::new(ArgDef::ShortAndLong { short: 'h', long: "help" })
+                .action(ArgAction::Flag)
+                .value_name("FORMAT")
+                .possible_values(&["text", "json"])
+                .exclusive(true)
+                .help("Print help"),
+        )
+    }
+
+    /// Adds the definitions of `other` to this context, along with its nested contexts,
+    /// positionals and aliases.
+    ///
+    /// Each definition is checked as by [`register`], against the ones of this context and
+    /// those of `other` added before it. Nested contexts are added as by [`nest`], and
+    /// positionals after the ones defined here. Aliases are added as by [`alias`], except
+    /// that an alias defined in both contexts keeps the expansion from this one.
+    ///
+    /// The merge is all-or-nothing: if anything from `other` cannot be added, nothing is,
+    /// and every error is reported. Settings, such as the terminator, help texts, locale,
+    /// token transforms and configuration layers, are not carried over.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ArgSpec, Args, ParserContext, PositionalSpec, RegisterError};
+    /// let mut common = ParserContext::new();
+    /// common.flag('v', "verbose").flag('q', "quiet");
+    ///
+    /// let mut app = ParserContext::new();
+    /// app.option('o', "output");
+    /// app.merge(&common).unwrap();
+    ///
+    /// assert!(Args::parse_with_context(&["-v", "-o", "out.txt"], &app).is_ok());
+    ///
+    /// let mut plugin = ParserContext::new();
+    /// plugin.flag('v', "version");
+    ///
+    /// let errors = app.merge(&plugin).unwrap_err();
+    /// assert_eq!(errors, [RegisterError::DuplicateShort('v')]);
+    ///
+    /// // Former names are checked too
+    /// let mut renamed = ParserContext::new();
+    /// renamed.register(ArgSpec::new(ArgDef::Long("out")).renamed_from(&["output"])).unwrap();
+    /// let errors = app.merge(&renamed).unwrap_err();
+    /// assert_eq!(errors, [RegisterError::DuplicateLong("output".into())]);
+    ///
+    /// let mut files = ParserContext::new();
+    /// files.positional(PositionalSpec::new("FILE")).alias("o", ["--output"]).unwrap();
+    /// app.merge(&files).unwrap();
+    ///
+    /// assert_eq!(app.positionals().len(), 1);
+    /// assert!(Args::parse_with_context(&["o", "out.txt"], &app).is_ok());
+    /// ```
+    ///
+    /// [`register`]: ParserContext::register
+    /// [`nest`]: ParserContext::nest
+    /// [`alias`]: ParserContext::alias
+    pub fn merge(&mut self, other: &ParserContext<'a>) -> Result<&mut Self, Vec<RegisterError>> {
+        let mut merged = self.clone();
+        let mut errors = Vec::new();
+
+        for spec in &other.specs {
+            match merged.check(spec) {
+                Ok(()) => merged.insert(spec.clone()),
+                Err(err) => errors.push(err),
             }
-            ArgDef::Long(l) => {
-                if self.long_map.contains_key(l) {
-                    return Err(format!("Long argument --{} already defined", l));
+        }
+        for (prefix, child) in &other.namespaces {
+            if let Err(conflicts) = merged.nest(prefix, child.clone()) {
+                errors.extend(conflicts.iter().filter_map(|conflict| {
+                    let long = conflict.conflicting.long()?;
+                    Some(RegisterError::DuplicateLong(format!("{}-{}", prefix, long)))
+                }));
+            }
+        }
+        for positional in &other.positionals {
+            match merged.positionals.last() {
+                Some(last) if last.variadic => {
+                    errors.push(RegisterError::PositionalAfterVariadic(positional.name.into()));
                 }
+                _ => merged.positionals.push(positional.clone()),
             }
-            ArgDef::ShortAndLong { short, long } => {
-                if self.short_map.contains_key(short) {
-                    return Err(format!("Short argument -{} already defined", short));
+        }
+        for (name, expansion) in &other.aliases {
+            if merged.alias_of(name).is_some() {
+                continue;
+            }
+            if let Err(err) = merged.alias(name, expansion.iter().copied()) {
+                errors.push(err);
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        *self = merged;
+        Ok(self)
+    }
+
+    /// Nests a child context under `prefix`.
+    ///
+    /// Every long name of the child becomes reachable from the command line as
+    /// `--<prefix>-<name>`, while the child keeps seeing its own unprefixed names through
+    /// [`Args::scope`]. Short names of the child are not exposed, since a single character
+    /// cannot carry a prefix.
+    ///
+    /// If a prefixed name collides with a name already defined here, nothing is nested
+    /// and every collision is reported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Args, OptionArg, ParserContext};
+    /// let mut plugin = ParserContext::new();
+    /// plugin.option('c', "cache-dir");
+    ///
+    /// let mut host = ParserContext::new();
+    /// host.flag('v', "verbose");
+    /// host.nest("myplugin", plugin).unwrap();
+    ///
+    /// let args = Args::parse_with_context(&["-v", "--myplugin-cache-dir", "/tmp"], &host).unwrap();
+    ///
+    /// // The plugin only sees its own options, under their unprefixed names
+    /// let scoped = args.scope("myplugin");
+    /// let dir = scoped.find::<OptionArg>(ArgDef::Long("cache-dir")).unwrap();
+    /// assert_eq!(dir.value, "/tmp");
+    /// ```
+    ///
+    /// [`Args::scope`]: crate::Args::scope
+    pub fn nest(
+        &mut self,
+        prefix: &'a str,
+        child: ParserContext<'a>,
+    ) -> Result<&mut Self, Vec<DefinitionConflict<'a>>> {
+        let conflicts: Vec<_> = child
+            .specs
+            .iter()
+            .filter_map(|spec| {
+                let long = spec.def.long()?;
+                self.find_long(&format!("{}-{}", prefix, long))
+                    .map(|existing| DefinitionConflict {
+                        existing: existing.def,
+                        conflicting: spec.def,
+                    })
+            })
+            .collect();
+
+        if !conflicts.is_empty() {
+            return Err(conflicts);
+        }
+
+        self.namespaces.push((prefix, child));
+        Ok(self)
+    }
+
+    /// Removes every definition that shares a short or long name with `def`.
+    ///
+    /// Returns the removed specifications, in registration order. Removing a name that is
+    /// not defined is not an error and returns an empty list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Args, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose").flag('q', "quiet");
+    ///
+    /// let removed = ctx.remove(ArgDef::Short('q'));
+    /// assert_eq!(removed[0].def, ArgDef::ShortAndLong { short: 'q', long: "quiet" });
+    ///
+    /// assert!(Args::parse_with_context(&["--quiet"], &ctx).is_err());
+    /// assert!(Args::parse_with_context(&["--verbose"], &ctx).is_ok());
+    /// ```
+    pub fn remove(&mut self, def: ArgDef<'a>) -> Vec<ArgSpec<'a>> {
+        let (removed, kept) = self.specs.drain(..).partition(|spec| spec.def.overlaps(&def));
+        self.specs = kept;
+        self.reindex();
+        removed
+    }
+
+    /// Registers `arg`, first removing any definitions that share one of its names.
+    ///
+    /// This lets an embedder override defaults provided by a shared base context. Returns
+    /// the specifications that were displaced.
+    ///
+    /// Once those are set aside, `arg` is checked as by [`register`]: it fails with a
+    /// [`RegisterError`] if its short name is invalid, or if one of its former names or
+    /// localized spellings is taken by a definition that stays. The context is then left
+    /// unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Args, OptionArg, ParserContext};
+    /// # use argsparse::RegisterError;
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('c', "color").flag('q', "quiet");
+    ///
+    /// // Turn `--color` into an option taking a value
+    /// let spec = ArgSpec::new(ArgDef::Long("color")).action(ArgAction::Value);
+    /// let displaced = ctx.replace(spec).unwrap();
+    /// assert_eq!(displaced.len(), 1);
+    ///
+    /// let args = Args::parse_with_context(&["--color", "never"], &ctx).unwrap();
+    /// assert!(args.find::<OptionArg>(ArgDef::Long("color")).is_some());
+    ///
+    /// let spec = ArgSpec::new(ArgDef::Long("color")).renamed_from(&["quiet"]);
+    /// assert_eq!(ctx.replace(spec).unwrap_err(), RegisterError::DuplicateLong("quiet".into()));
+    /// assert_eq!(ctx.specs().len(), 2);
+    /// ```
+    ///
+    /// [`register`]: ParserContext::register
+    /// [`RegisterError`]: crate::RegisterError
+    pub fn replace(
+        &mut self,
+        arg: impl Into<ArgSpec<'a>>,
+    ) -> Result<Vec<ArgSpec<'a>>, RegisterError> {
+        let spec = arg.into();
+        let mut replaced = self.clone();
+        let removed = replaced.remove(spec.def);
+        replaced.check(&spec)?;
+        replaced.insert(spec);
+        *self = replaced;
+        Ok(removed)
+    }
+
+    /// Defines the next named positional, returning the context for chaining.
+    ///
+    /// Positionals are matched in definition order. See [`PositionalSpec`] for how their
+    /// count is validated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a variadic positional was already defined, since it would capture every
+    /// operand meant for this one.
+    ///
+    /// [`PositionalSpec`]: crate::PositionalSpec
+    pub fn positional(&mut self, spec: impl Into<PositionalSpec<'a>>) -> &mut Self {
+        if let Some(last) = self.positionals.last().filter(|last| last.variadic) {
+            panic!("positional {} is variadic and must be the last one", last);
+        }
+        self.positionals.push(spec.into());
+        self
+    }
+
+    /// Returns the named positionals, in the order they are matched.
+    pub fn positionals(&self) -> &[PositionalSpec<'a>] {
+        &self.positionals
+    }
+
+    /// Returns the argument specifications, in registration order.
+    ///
+    /// Definitions of nested contexts are not included; see [`namespaces`].
+    ///
+    /// [`namespaces`]: ParserContext::namespaces
+    pub fn specs(&self) -> &[ArgSpec<'a>] {
+        &self.specs
+    }
+
+    /// Returns the nested contexts along with their prefixes, in nesting order.
+    pub fn namespaces(&self) -> impl Iterator<Item = (&'a str, &ParserContext<'a>)> {
+        self.namespaces.iter().map(|(prefix, child)| (*prefix, child))
+    }
+
+    /// Checks that every definition appears as many times as its `occurrences` allow.
+    fn check_occurrences<'b>(&self, matches: &Matches) -> Result<(), ParseArgError<'b>> {
+        for spec in &self.specs {
+            let (count, (min, max)) = (matches.count(spec.def), spec.occurrences);
+            let absent = count == 0 && self.fallback(spec).is_some();
+            if !absent && (count < min || max.is_some_and(|max| count > max)) {
+                let arg = match spec.def {
+                    ArgDef::Long(long) | ArgDef::ShortAndLong { long, .. } => format!("--{}", long),
+                    def => def.to_string(),
+                };
+                return Err(ParseArgError::OccurrenceCount { arg, count, min, max });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the positionals of `args` against the named positional definitions.
+    fn check_positionals<'b>(&self, args: &Args<'b>) -> Result<(), ParseArgError<'b>> {
+        if self.positionals.is_empty() {
+            return Ok(());
+        }
+
+        let mut supplied = args.iter().filter_map(|arg| match arg {
+            Argument::Positional { value } => Some(value),
+            _ => None,
+        });
+
+        for spec in &self.positionals {
+            if supplied.next().is_none() && spec.required {
+                return Err(ParseArgError::MissingPositional(spec.name.into()));
+            }
+            if spec.variadic {
+                return Ok(());
+            }
+        }
+
+        match supplied.next() {
+            Some(extra) => Err(ParseArgError::UnexpectedPositional(extra.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Makes `-` and `_` interchangeable in long names, so that `--dry_run` finds the
+    /// definition of `--dry-run`, and registering `--dry_run` next to it is a conflict.
+    ///
+    /// Arguments are recorded under the name of the definition they matched. Set this before
+    /// registering definitions, as ones already registered are not checked against each
+    /// other. Nested contexts keep their own setting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Args, ParserContext, RegisterError};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.normalize_underscores(true).flag('n', "dry-run");
+    ///
+    /// let args = Args::parse_with_context(&["--dry_run"], &ctx).unwrap();
+    /// assert!(args.has(ArgDef::Long("dry-run")));
+    ///
+    /// let err = ctx.register(ArgDef::Long("dry_run")).unwrap_err();
+    /// assert_eq!(err, RegisterError::DuplicateLong("dry_run".into()));
+    /// ```
+    pub fn normalize_underscores(&mut self, enabled: bool) -> &mut Self {
+        self.normalize_underscores = enabled;
+        self.reindex();
+        self
+    }
+
+    /// Lets long names be given with a single dash, as in `find . -name '*.rs' -maxdepth 2`,
+    /// for programs following the conventions of classic Unix tools.
+    ///
+    /// A single-dash token whose name (up to any `=`) is a long name of the context is parsed
+    /// as that long option; any other is still a cluster of short names, so `-v` and `-xvf`
+    /// keep working. Long names keep working with two dashes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Args, OptionArg, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.single_dash_longs(true).flag('L', "follow");
+    /// ctx.register(ArgSpec::new(ArgDef::Long("name")).action(ArgAction::Value)).unwrap();
+    /// ctx.register(ArgSpec::new(ArgDef::Long("maxdepth")).action(ArgAction::Value)).unwrap();
+    ///
+    /// let args = Args::parse_with_context(&["-L", "-name", "*.rs", "-maxdepth=2"], &ctx)
+    ///     .unwrap();
+    ///
+    /// assert!(args.has(ArgDef::Short('L')));
+    /// let name: OptionArg = args.find(ArgDef::Long("name")).unwrap();
+    /// assert_eq!(name.value, "*.rs");
+    /// let depth: OptionArg = args.find(ArgDef::Long("maxdepth")).unwrap();
+    /// assert_eq!(depth.value, "2");
+    /// ```
+    pub fn single_dash_longs(&mut self, enabled: bool) -> &mut Self {
+        self.single_dash_longs = enabled;
+        self
+    }
+
+    /// Sets caps on the size of the command lines the context accepts.
+    ///
+    /// The number and length of tokens are checked before anything else, on the raw tokens
+    /// and again after aliases and transforms. The number of values is checked as options
+    /// are matched, so it only applies to [`Args::parse_with_context`] and [`Matches::parse`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, Limits, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.option('I', "include")
+    ///     .limits(Limits::new().max_tokens(64).max_token_len(4096).max_values(2));
+    ///
+    /// assert!(Args::parse_with_context(&["-I", "a", "-I", "b"], &ctx).is_ok());
+    ///
+    /// let err = Args::parse_with_context(&["-I", "a", "-I", "b", "-I", "c"], &ctx).unwrap_err();
+    /// assert_eq!(err.to_string(), "too many values for -I: at most 2 are allowed");
+    ///
+    /// let long = "x".repeat(5000);
+    /// let args = ["-I", &long];
+    /// let err = Args::parse_with_context(&args, &ctx).unwrap_err();
+    /// assert_eq!(err.position(&args), Some(1));
+    /// ```
+    ///
+    /// [`Args::parse_with_context`]: crate::Args::parse_with_context
+    /// [`Matches::parse`]: crate::Matches::parse
+    pub fn limits(&mut self, limits: Limits) -> &mut Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Takes a long name given with a single dash by mistake (e.g., `-verbose`) as that long
+    /// name, with a warning, rather than failing with [`ParseArgError::SingleDashLong`].
+    ///
+    /// Only tokens that make no sense as a cluster of short names are corrected: when every
+    /// character of `-verbose` is a defined short name that may be combined with the others,
+    /// it is still a cluster. Unlike [`single_dash_longs`], this is meant to forgive a typo,
+    /// not to define the syntax of the program. Off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Matches, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.correct_single_dash_longs(true).flag('v', "verbose");
+    ///
+    /// let matches = Matches::parse(&["-verbose"], &ctx).unwrap();
+    /// assert!(matches.has(ArgDef::Long("verbose")));
+    /// assert_eq!(
+    ///     matches.warnings(),
+    ///     ["-verbose was taken as --verbose: long options take two dashes"]
+    /// );
+    /// ```
+    ///
+    /// [`ParseArgError::SingleDashLong`]: crate::ParseArgError::SingleDashLong
+    /// [`single_dash_longs`]: ParserContext::single_dash_longs
+    pub fn correct_single_dash_longs(&mut self, enabled: bool) -> &mut Self {
+        self.correct_single_dash_longs = enabled;
+        self
+    }
+
+    /// Returns the name of the single-dash token `token` if it spells a long name of the
+    /// context (up to any `=`), in which case it is the rest of the token.
+    fn single_dash_long<'t>(&self, token: &'t str) -> Option<&'t str> {
+        let name = token.strip_prefix('-').filter(|name| name.chars().nth(1).is_some())?;
+        let long = name.split_once('=').map_or(name, |(long, _)| long);
+        (self.find_long(long).is_some() || self.negated(long).is_some()).then_some(name)
+    }
+
+    /// Returns `true` if the single-dash token `token` at `index` is a valid cluster of
+    /// defined short names.
+    fn is_cluster(&self, index: usize, token: &str) -> bool {
+        let names = token[1..].split_once('=').map_or(&token[1..], |(names, _)| names);
+        self.check_cluster(index, token).is_ok()
+            && names.chars().all(|short| self.spec_of(&ArgName::Short(short)).is_some())
+    }
+
+    /// Returns `true` if `token` names defined options, so that it cannot be meant as the
+    /// value of the option before it.
+    fn names_option(&self, token: &str) -> bool {
+        match token.strip_prefix("--") {
+            Some(long) => {
+                let long = long.split_once('=').map_or(long, |(long, _)| long);
+                self.find_long(long).is_some() || self.negated(long).is_some()
+            }
+            None => {
+                let single_dash_long =
+                    self.single_dash_longs && self.single_dash_long(token).is_some();
+                token.len() > 1
+                    && token.starts_with('-')
+                    && (single_dash_long || self.is_cluster(0, token))
+            }
+        }
+    }
+
+    /// Checks that the option `name`, given as the token at `index` without `=`, is followed
+    /// by its value if it takes one, `next` being the token that follows, if any.
+    fn check_value_follows(
+        &self,
+        index: usize,
+        name: &ArgName,
+        next: Option<&str>,
+    ) -> Result<(), ParseArgError<'static>> {
+        let Some(spec) = self.spec_of(name).filter(|spec| spec.action == ArgAction::Value) else {
+            return Ok(());
+        };
+        if next.is_some_and(|next| !self.names_option(next)) {
+            return Ok(());
+        }
+        let arg = types::display_name(name);
+        let expected = spec.value_name.unwrap_or("VALUE").to_string();
+        Err(ParseArgError::MissingValue { index, arg, expected })
+    }
+
+    /// Sets how short names may be combined in one token, so that programs whose users
+    /// expect `-abc` to mean something else can make it an error rather than several flags.
+    ///
+    /// A token combining names it does not allow fails the parse with
+    /// [`ParseArgError::ShortCluster`]. Tokens made of a single short name, with or without
+    /// a value, are not affected, nor are single-dash long names. Clustering is
+    /// [`Clustering::Enabled`] by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, Clustering, ParseArgError, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.clustering(Clustering::FlagsOnly).flag('x', "extract").flag('v', "verbose");
+    /// ctx.option('f', "file");
+    ///
+    /// assert!(Args::parse_with_context(&["-xv", "-f", "a.tar"], &ctx).is_ok());
+    ///
+    /// let err = Args::parse_with_context(&["-xvf", "a.tar"], &ctx).unwrap_err();
+    /// assert!(matches!(err, ParseArgError::ShortCluster { index: 0, .. }));
+    ///
+    /// ctx.clustering(Clustering::Disabled);
+    /// let err = Args::parse_with_context(&["-xv"], &ctx).unwrap_err();
+    /// assert_eq!(err.to_string(), "short options cannot be combined as in '-xv' at position 0");
+    /// ```
+    ///
+    /// [`ParseArgError::ShortCluster`]: crate::ParseArgError::ShortCluster
+    pub fn clustering(&mut self, clustering: Clustering) -> &mut Self {
+        self.clustering = clustering;
+        self
+    }
+
+    /// Checks the single-dash token `token` at `index` against the [`Clustering`] of the
+    /// context.
+    fn check_cluster<'t>(&self, index: usize, token: &'t str) -> Result<(), ParseArgError<'t>> {
+        let names = &token[1..];
+        let names = names.split_once('=').map_or(names, |(names, _)| names);
+        let mut chars = names.chars();
+        let Some(first) = chars.next() else {
+            return Ok(());
+        };
+        // The code points of a single character are not several names
+        if chars.all(|next| unicode::continues_grapheme(first, next)) {
+            return Ok(());
+        }
+
+        let allowed = match self.clustering {
+            Clustering::Enabled => true,
+            Clustering::FlagsOnly => names.chars().all(|short| {
+                self.spec_of(&ArgName::Short(short)).is_some_and(|s| s.action == ArgAction::Flag)
+            }),
+            Clustering::Disabled => false,
+        };
+        match allowed {
+            true => Ok(()),
+            false => Err(ParseArgError::ShortCluster { index, token: token.into() }),
+        }
+    }
+
+    /// Rejects tokens holding a NUL byte or another C0 control character (e.g., an escape or
+    /// a newline) with [`ParseArgError::ControlCharacter`].
+    ///
+    /// An operating system never passes NUL bytes in `argv`, and terminals do not let users
+    /// type most control characters, so they usually betray a command line rebuilt from
+    /// untrusted input. Tokens are checked as a whole, names and values alike, before and
+    /// after aliases and transforms. Off by default, since values such as commit messages
+    /// may legitimately span lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, ParseArgError, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.option('m', "message").reject_control_chars(true);
+    ///
+    /// let err = Args::parse_with_context(&["-m", "ok\0rm -rf /"], &ctx).unwrap_err();
+    /// assert!(matches!(err, ParseArgError::ControlCharacter { index: 1, character: '\0' }));
+    /// assert_eq!(err.to_string(), "control character '\\u{0}' in argument at position 1");
+    /// ```
+    ///
+    /// [`ParseArgError::ControlCharacter`]: crate::ParseArgError::ControlCharacter
+    pub fn reject_control_chars(&mut self, enabled: bool) -> &mut Self {
+        self.reject_control_chars = enabled;
+        self
+    }
+
+    /// Checks the token at `index` against the limits on the number and length of tokens,
+    /// and for control characters.
+    fn check_token(&self, index: usize, token: &str) -> Result<(), ParseArgError<'static>> {
+        let Limits { max_tokens, max_token_len, .. } = self.limits;
+        if let Some(max) = max_tokens
+            && index >= max
+        {
+            return Err(ParseArgError::LimitsExceeded { limit: Limit::Tokens, max });
+        }
+        if let Some(max) = max_token_len
+            && token.len() > max
+        {
+            return Err(ParseArgError::LimitsExceeded { limit: Limit::TokenLength { index }, max });
+        }
+        if self.reject_control_chars
+            && let Some(character) = token.chars().find(|c| matches!(c, '\0'..='\x1f'))
+        {
+            return Err(ParseArgError::ControlCharacter { index, character });
+        }
+        Ok(())
+    }
+
+    /// Sets the token that ends option parsing, or disables it with `None`.
+    ///
+    /// Every token after the terminator is positional. It is `--` by default; embedding the
+    /// parser in a language where `--` means something else may call for a different token,
+    /// or for none at all.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose").set_terminator(Some(";"));
+    ///
+    /// let args = Args::parse_with_context(&["-v", ";", "-x"], &ctx).unwrap();
+    /// assert_eq!(args.trailing().len(), 1);
+    ///
+    /// ctx.set_terminator(None);
+    /// assert!(Args::parse_with_context(&["-v", ";", "-x"], &ctx).is_err());
+    /// ```
+    pub fn set_terminator(&mut self, terminator: Option<&'a str>) -> &mut Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /// Sets free text rendered before the usage line of the help output (e.g., a banner).
+    pub fn before_help(&mut self, text: &'a str) -> &mut Self {
+        self.before_help = Some(text);
+        self
+    }
+
+    /// Sets free text rendered after the options of the help output (e.g., examples,
+    /// copyright or links).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::ParserContext;
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose")
+    ///     .before_help("app 1.0")
+    ///     .after_help("Examples:\n  app -v");
+    ///
+    /// let help = ctx.render_help("app");
+    /// assert!(help.starts_with("app 1.0\n\nUsage: app [OPTIONS]\n"));
+    /// assert!(help.ends_with("-v, --verbose\n\nExamples:\n  app -v\n"));
+    /// ```
+    pub fn after_help(&mut self, text: &'a str) -> &mut Self {
+        self.after_help = Some(text);
+        self
+    }
+
+    /// Adds an example invocation of the program, `command`, with its `description`, listed
+    /// in the examples section of help output along with the [`examples`] of definitions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.example("app build", "Build the project");
+    /// ctx.register(ArgSpec::new(ArgDef::Long("jobs"))
+    ///     .action(ArgAction::Value)
+    ///     .examples(&[("app --jobs 8 build", "Build with 8 jobs")]))
+    ///     .unwrap();
+    ///
+    /// assert!(ctx.render_help("app").ends_with("\
+    /// Examples:
+    ///   app build
+    ///       Build the project
+    ///   app --jobs 8 build
+    ///       Build with 8 jobs
+    /// "));
+    /// ```
+    ///
+    /// [`examples`]: crate::ArgSpec::examples
+    pub fn example(&mut self, command: &'a str, description: &'a str) -> &mut Self {
+        self.examples.push((command, description));
+        self
+    }
+
+    /// Sets the template used to render the help output, replacing the default layout.
+    ///
+    /// The following placeholders are replaced, and any other text is kept verbatim:
+    ///
+    /// * `{name}` - The program name.
+    /// * `{usage}` - The usage line.
+    /// * `{before-help}`, `{after-help}` - The texts set with [`before_help`] and
+    ///   [`after_help`].
+    /// * `{arguments}` - The positionals and their descriptions, without a heading.
+    /// * `{options}` - The visible options and their descriptions, without a heading.
+    /// * `{examples}` - The examples and their descriptions, without a heading.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::ParserContext;
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose")
+    ///     .help_template("{name} - a tool\n\n{usage}\n\nFLAGS\n{options}\n");
+    ///
+    /// assert_eq!(
+    ///     ctx.render_help("app"),
+    ///     "app - a tool\n\nUsage: app [OPTIONS]\n\nFLAGS\n  -v, --verbose\n"
+    /// );
+    /// ```
+    ///
+    /// [`before_help`]: ParserContext::before_help
+    /// [`after_help`]: ParserContext::after_help
+    pub fn help_template(&mut self, template: &'a str) -> &mut Self {
+        self.help_template = Some(template);
+        self
+    }
+
+    /// Sets a link to the full documentation of the program.
+    ///
+    /// Help output turns the program name of the usage line into a hyperlink on terminals,
+    /// and spells the URL out after the options otherwise.
+    pub fn doc_url(&mut self, url: &'a str) -> &mut Self {
+        self.doc_url = Some(url);
+        self
+    }
+
+    /// Adds a rewrite of the raw tokens, run before they are classified as flags, options or
+    /// positionals.
+    ///
+    /// Transforms run in the order they were added, each on the tokens produced by the
+    /// previous one, and see every token, including those after the `--` separator. They
+    /// apply whenever a whole list of arguments is parsed, such as with
+    /// [`Args::parse_with_context`] or [`Matches::parse`], but not to the lazy
+    /// [`Args::parse_iter_with_context`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Matches, ParserContext};
+    /// // Accept the legacy `+debug` syntax as `--debug`
+    /// fn legacy(_index: usize, token: &str) -> Option<Vec<String>> {
+    ///     token.strip_prefix('+').map(|name| vec![format!("--{}", name)])
+    /// }
+    ///
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('d', "debug").transform_tokens(legacy);
+    ///
+    /// let matches = Matches::parse(&["+debug"], &ctx).unwrap();
+    /// assert!(matches.has(ArgDef::Short('d')));
+    /// ```
+    ///
+    /// [`Args::parse_with_context`]: crate::Args::parse_with_context
+    /// [`Matches::parse`]: crate::Matches::parse
+    /// [`Args::parse_iter_with_context`]: crate::Args::parse_iter_with_context
+    pub fn transform_tokens(&mut self, transform: TokenTransform) -> &mut Self {
+        self.transforms.push(transform);
+        self
+    }
+
+    /// Defines `name` as an alias for the tokens of `expansion`, in the style of git aliases.
+    ///
+    /// When the first argument is `name`, it is replaced with the expansion before parsing,
+    /// and the rest of the arguments follow it. An expansion may start with another alias,
+    /// which is expanded in turn, so aliases are checked here: one that would expand into
+    /// itself fails with [`RegisterError::RecursiveAlias`], and one that would go through
+    /// more than 16 nested aliases with [`RegisterError::AliasTooDeep`], counting the aliases
+    /// already defined to expand into `name`. Defining `name` again replaces its expansion.
+    ///
+    /// Aliases are expanded before the [token transforms] run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Matches, ParserContext, PositionalSpec, RegisterError};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.option('b', "branch").positional(PositionalSpec::new("COMMAND"));
+    /// ctx.alias("co", ["checkout"]).unwrap();
+    /// ctx.alias("nb", ["co", "-b"]).unwrap();
+    ///
+    /// let matches = Matches::parse(&["nb", "topic"], &ctx).unwrap();
+    /// assert_eq!(matches.positional_values("COMMAND"), ["checkout"]);
+    /// assert_eq!(matches.value_of(ArgDef::Long("branch")).as_deref(), Some("topic"));
+    ///
+    /// let err = ctx.alias("checkout", ["nb"]).unwrap_err();
+    /// assert_eq!(err, RegisterError::RecursiveAlias("checkout".into()));
+    /// ```
+    ///
+    /// The depth is checked whichever end of a chain is defined first:
+    ///
+    /// ```
+    /// # use argsparse::{ParserContext, RegisterError};
+    /// let names: Vec<String> = (0..=18).map(|i| format!("a{}", i)).collect();
+    /// let mut ctx = ParserContext::new();
+    ///
+    /// // `a0` expands through `a1` to `a16`
+    /// for pair in names.windows(2).take(17) {
+    ///     ctx.alias(&pair[0], [pair[1].as_str()]).unwrap();
+    /// }
+    ///
+    /// let err = ctx.alias("a17", ["a18"]).unwrap_err();
+    /// assert_eq!(err, RegisterError::AliasTooDeep("a17".into()));
+    /// ```
+    ///
+    /// [`RegisterError::RecursiveAlias`]: crate::RegisterError::RecursiveAlias
+    /// [`RegisterError::AliasTooDeep`]: crate::RegisterError::AliasTooDeep
+    /// [token transforms]: ParserContext::transform_tokens
+    pub fn alias(
+        &mut self,
+        name: &'a str,
+        expansion: impl IntoIterator<Item = &'a str>,
+    ) -> Result<&mut Self, RegisterError> {
+        let expansion: Vec<&'a str> = expansion.into_iter().collect();
+
+        // Follow the chain of aliases the expansion starts with
+        let mut next = expansion.first().copied();
+        let mut depth = 0;
+        while let Some(token) = next {
+            if token == name {
+                return Err(RegisterError::RecursiveAlias(name.to_string()));
+            }
+            let Some(tokens) = self.alias_of(token) else {
+                break;
+            };
+            depth += 1;
+            if depth > MAX_ALIAS_DEPTH {
+                return Err(RegisterError::AliasTooDeep(name.to_string()));
+            }
+            next = tokens.first().copied();
+        }
+        // The aliases expanding into `name` go through its expansion too
+        if self.depth_above(name) + depth > MAX_ALIAS_DEPTH {
+            return Err(RegisterError::AliasTooDeep(name.to_string()));
+        }
+
+        self.aliases.retain(|&(existing, _)| existing != name);
+        self.aliases.push((name, expansion));
+        Ok(self)
+    }
+
+    /// Returns the number of nested aliases in the longest chain expanding into `name`.
+    fn depth_above(&self, name: &str) -> usize {
+        self.aliases
+            .iter()
+            .filter(|(_, expansion)| expansion.first() == Some(&name))
+            .map(|&(alias, _)| 1 + self.depth_above(alias))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the expansion of the alias `name`, if it is defined.
+    fn alias_of(&self, name: &str) -> Option<&[&'a str]> {
+        self.aliases
+            .iter()
+            .find(|&&(alias, _)| alias == name)
+            .map(|(_, expansion)| expansion.as_slice())
+    }
+
+    /// Expands the aliases and runs the token transforms over `args`, or returns `None` if
+    /// there is nothing to do.
+    pub(crate) fn transform(&self, args: &[&str]) -> Option<Vec<String>> {
+        if self.transforms.is_empty() && self.aliases.is_empty() {
+            return None;
+        }
+
+        // `alias` rules out cycles, so this terminates
+        let mut tokens: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        while let Some(expansion) = tokens.first().and_then(|first| self.alias_of(first)) {
+            let expansion = expansion.iter().map(|token| token.to_string());
+            tokens.splice(..1, expansion);
+        }
+
+        for transform in &self.transforms {
+            tokens = tokens
+                .into_iter()
+                .enumerate()
+                .flat_map(|(index, token)| transform(index, &token).unwrap_or(vec![token]))
+                .collect();
+        }
+        Some(tokens)
+    }
+
+    /// Splits `args` at the subcommand, the first operand, into the global arguments before
+    /// it, its name, and the arguments after it, which are left unparsed.
+    ///
+    /// Only the global arguments are parsed, with the definitions of the context, so that
+    /// the value of an option is not taken for the subcommand; a launcher can then check
+    /// them and hand the rest over to another process. Aliases and transforms are not
+    /// applied. Returns `None` when there is no operand before the terminator (`--` by
+    /// default), and fails with the first error found in the global arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::ParserContext;
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose").option('C', "directory");
+    ///
+    /// let args = ["-v", "-C", "src", "run", "--release", "-v"];
+    /// let (global, name, rest) = ctx.split_at_subcommand(&args).unwrap().unwrap();
+    ///
+    /// assert_eq!(global, ["-v", "-C", "src"]);
+    /// assert_eq!(name, "run");
+    /// assert_eq!(rest, ["--release", "-v"]);
+    ///
+    /// assert!(ctx.split_at_subcommand(&["-v", "--", "run"]).unwrap().is_none());
+    /// ```
+    pub fn split_at_subcommand<'t, S: AsRef<str>>(
+        &self,
+        args: &'t [S],
+    ) -> Result<Option<SubcommandSplit<'t, S>>, ParseArgError<'t>> {
+        let mut iter = ParseIter::new(args.iter().map(AsRef::as_ref), Some(self));
+        while let Some(arg) = iter.next() {
+            if let Argument::Positional { .. } = arg? {
+                if iter.trailing_start().is_some() {
+                    return Ok(None);
                 }
-                if self.long_map.contains_key(long) {
-                    return Err(format!("Long argument --{} already defined", long));
+                // Operands are yielded as soon as their token is taken
+                let index = iter.input.peek().map_or(args.len(), |&(index, _)| index) - 1;
+                let name = args[index].as_ref();
+                return Ok(Some((&args[..index], name, &args[index + 1..])));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Sets the version of the program, against which the [`deprecated_in`] and
+    /// [`removed_in`] versions of arguments are checked.
+    ///
+    /// [`deprecated_in`]: crate::ArgSpec::deprecated_in
+    /// [`removed_in`]: crate::ArgSpec::removed_in
+    pub fn version(&mut self, version: &'a str) -> &mut Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Sets the locale of the user (e.g., `de` or `pt_BR.UTF-8`, as found in `LANG`), which
+    /// selects the [`localized`] spellings of long names accepted and shown in help output.
+    ///
+    /// Nested contexts keep their own locale. See [`ArgSpec::localized`] for an example.
+    ///
+    /// [`localized`]: crate::ArgSpec::localized
+    /// [`ArgSpec::localized`]: crate::ArgSpec::localized
+    pub fn locale(&mut self, locale: &'a str) -> &mut Self {
+        self.locale = Some(locale);
+        self.reindex();
+        self
+    }
+
+    /// Returns the spelling of the long name of `spec` for the locale of the context, if it
+    /// has one.
+    pub(crate) fn localized_long<'s>(&self, spec: &ArgSpec<'s>) -> Option<&'s str> {
+        let locale = self.locale?;
+        let applies = |tag: &str| {
+            locale.get(..tag.len()).is_some_and(|start| start.eq_ignore_ascii_case(tag))
+                && matches!(locale[tag.len()..].chars().next(), None | Some('-' | '_' | '.'))
+        };
+        spec.localized
+            .iter()
+            .filter(|(tag, _)| applies(tag))
+            .max_by_key(|(tag, _)| tag.len())
+            .map(|&(_, long)| long)
+    }
+
+    /// Sets the catalog translating error messages and help headings.
+    ///
+    /// See [`Catalog`] for an example.
+    ///
+    /// [`Catalog`]: crate::Catalog
+    pub fn catalog(&mut self, catalog: &'a dyn Catalog) -> &mut Self {
+        self.catalog = Some(catalog);
+        self
+    }
+
+    /// Sets the observer notified of unknown options, deprecated arguments and fallback
+    /// values.
+    ///
+    /// See [`Observer`] for an example.
+    ///
+    /// [`Observer`]: crate::Observer
+    pub fn observer(&mut self, observer: &'a dyn Observer) -> &mut Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Returns the text of `message`, translated by the catalog if one is set.
+    pub fn text(&self, message: &Message) -> String {
+        self.catalog
+            .and_then(|catalog| catalog.text(message))
+            .unwrap_or_else(|| message.to_string())
+    }
+
+    /// Overrides the message of every error of the given `kind` with `template`.
+    ///
+    /// The template takes precedence over the catalog. Its placeholders are replaced with
+    /// the details of the error, and any other text is kept verbatim:
+    ///
+    /// * `{arg}` - The argument at fault, as typed (e.g., `--port` or `<SRC>`).
+    /// * `{value}` - The offending value, positional or token.
+    /// * `{possible}` - The possible values, for [`ErrorKind::InvalidValue`].
+    /// * `{expected}`, `{reason}` - The requested type and the reason it could not be
+    ///   produced, for [`ErrorKind::Conversion`].
+    /// * `{reason}` - Why the value was rejected, for [`ErrorKind::RejectedValue`].
+    /// * `{index}` - The position of the token, for [`ErrorKind::MalformedArg`] and
+    ///   [`ErrorKind::ShortCluster`], along with the token as `{value}`, and for
+    ///   [`ErrorKind::ControlCharacter`], along with the escaped character as `{value}`.
+    /// * `{version}` - The version that removed the argument, for [`ErrorKind::RemovedArg`].
+    /// * `{expected}` - The placeholder of the value, for [`ErrorKind::MissingValue`].
+    /// * `{long}` - The long option meant, for [`ErrorKind::SingleDashLong`].
+    /// * `{count}`, `{min}`, `{max}` - The number of times the argument was given and the
+    ///   bounds it must be within, for [`ErrorKind::OccurrenceCount`].
+    /// * `{max}` - The value of the limit, for [`ErrorKind::LimitsExceeded`], along with
+    ///   `{index}` for the length of a token and `{arg}` for the number of values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, ErrorKind, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose")
+    ///     .error_template(ErrorKind::UnknownOption, "Oops! We don't know {arg}. Try --help.");
+    ///
+    /// let err = Args::parse_with_context(&["--verbos"], &ctx).unwrap_err();
+    /// assert_eq!(ctx.render_error(&err), "Oops! We don't know --verbos. Try --help.");
+    /// ```
+    ///
+    /// [`ErrorKind::InvalidValue`]: crate::ErrorKind::InvalidValue
+    /// [`ErrorKind::Conversion`]: crate::ErrorKind::Conversion
+    /// [`ErrorKind::RejectedValue`]: crate::ErrorKind::RejectedValue
+    /// [`ErrorKind::MalformedArg`]: crate::ErrorKind::MalformedArg
+    /// [`ErrorKind::RemovedArg`]: crate::ErrorKind::RemovedArg
+    /// [`ErrorKind::OccurrenceCount`]: crate::ErrorKind::OccurrenceCount
+    /// [`ErrorKind::LimitsExceeded`]: crate::ErrorKind::LimitsExceeded
+    /// [`ErrorKind::ControlCharacter`]: crate::ErrorKind::ControlCharacter
+    /// [`ErrorKind::ShortCluster`]: crate::ErrorKind::ShortCluster
+    /// [`ErrorKind::SingleDashLong`]: crate::ErrorKind::SingleDashLong
+    /// [`ErrorKind::MissingValue`]: crate::ErrorKind::MissingValue
+    pub fn error_template(&mut self, kind: ErrorKind, template: &'a str) -> &mut Self {
+        self.error_templates.retain(|&(existing, _)| existing != kind);
+        self.error_templates.push((kind, template));
+        self
+    }
+
+    /// Renders `err` as a message, using the error templates and the catalog if set.
+    pub fn render_error(&self, err: &ParseArgError) -> String {
+        err.with_message(|message| self.error_text(err.kind(), message))
+    }
+
+    /// Renders `err` as a message, using the error templates and the catalog if set.
+    pub fn render_value_error(&self, err: &ValueError) -> String {
+        self.error_text(ErrorKind::Conversion, &err.message())
+    }
+
+    fn error_text(&self, kind: ErrorKind, message: &Message) -> String {
+        match self.error_templates.iter().find(|&&(existing, _)| existing == kind) {
+            Some((_, template)) => messages::expand(template, |key| message.placeholder(key)),
+            None => self.text(message),
+        }
+    }
+
+    /// Freezes the context into an immutable form that can be shared across threads.
+    ///
+    /// See [`FrozenContext`].
+    ///
+    /// [`FrozenContext`]: crate::FrozenContext
+    pub fn freeze(self) -> FrozenContext<'a> {
+        FrozenContext(Arc::new(self))
+    }
+
+    /// Registers `spec`, panicking on any error [`register`] would return.
+    ///
+    /// [`register`]: ParserContext::register
+    fn define(&mut self, spec: ArgSpec<'a>) -> &mut Self {
+        if let Err(err) = self.check(&spec) {
+            panic!("{}", err);
+        }
+        self.insert(spec);
+        self
+    }
+
+    /// Returns the number of definitions registered directly with this context.
+    pub(crate) fn len(&self) -> usize {
+        self.specs.len()
+    }
+
+    /// Returns the id of the top-level definition matching `name`, if any.
+    ///
+    /// Ids are positions in registration order and are only stable until a definition
+    /// is removed.
+    pub(crate) fn id_of(&self, name: &ArgName) -> Option<usize> {
+        match name {
+            ArgName::Short(s) => self.short_map.get(s),
+            ArgName::Long(l) => self.long_index(l),
+        }
+    }
+
+    /// Returns the id of the top-level definition claiming any name of `def`, if any.
+    pub(crate) fn id_of_def(&self, def: ArgDef) -> Option<usize> {
+        match def {
+            ArgDef::Short(s) => self.short_map.get(&s),
+            ArgDef::Long(l) => self.long_index(l),
+            ArgDef::ShortAndLong { short, long } => {
+                self.short_map.get(&short).or_else(|| self.long_index(long))
+            }
+        }
+    }
+
+    /// Returns the specification matching `name`, if it is defined.
+    pub(crate) fn spec_of(&self, name: &ArgName) -> Option<&ArgSpec<'a>> {
+        match name {
+            ArgName::Short(s) => self.short_map.get(s).map(|index| &self.specs[index]),
+            ArgName::Long(l) => self.find_long(l),
+        }
+    }
+
+    /// Looks up a long name, descending into nested contexts when it carries their prefix.
+    fn find_long(&self, name: &str) -> Option<&ArgSpec<'a>> {
+        if let Some(index) = self.long_index(name) {
+            return Some(&self.specs[index]);
+        }
+
+        self.namespaces.iter().find_map(|(prefix, child)| {
+            name.strip_prefix(prefix)
+                .and_then(|rest| rest.strip_prefix('-'))
+                .and_then(|rest| child.find_long(rest))
+        })
+    }
+
+    /// Returns the definition negated by the long name `name` (e.g., `no-color`), if it
+    /// accepts a [`negation`] and no definition is named `name` itself.
+    ///
+    /// [`negation`]: ArgSpec::negation
+    fn negated(&self, name: &str) -> Option<&ArgSpec<'a>> {
+        if self.find_long(name).is_some() {
+            return None;
+        }
+        let spec = &self.specs[self.long_index(name.strip_prefix("no-")?)?];
+        spec.negation.is_some().then_some(spec)
+    }
+
+    /// Returns the index of the top-level definition with the long name `name`, taking
+    /// [`normalize_underscores`] into account.
+    ///
+    /// [`normalize_underscores`]: ParserContext::normalize_underscores
+    fn long_index(&self, name: &str) -> Option<usize> {
+        if let Some(index) = self.longs.get(name).and_then(|symbol| self.long_map.get(symbol)) {
+            return Some(index);
+        }
+        if !self.normalize_underscores {
+            return None;
+        }
+
+        let symbol = match name.contains('_') {
+            true => self.longs.get(&name.replace('_', "-")),
+            false => self.longs.get(name),
+        };
+        symbol.and_then(|symbol| self.normalized_map.get(symbol))
+    }
+
+    /// Returns the first required definition, here or in a nested context, missing from `args`.
+    ///
+    /// `prefix` is the accumulated namespace prefix, used to name nested definitions the way
+    /// they appear on the command line.
+    fn missing_required(&self, args: &Args, prefix: &str) -> Option<String> {
+        // A flag given `false` last is not given
+        let given = |spec: &ArgSpec| match args.iter_matching(spec.def).last() {
+            Some(Argument::Option { value, .. }) if spec.is_boolean() => value != "false",
+            last => last.is_some(),
+        };
+        let missing =
+            |spec: &&ArgSpec| spec.required && !given(spec) && self.fallback(spec).is_none();
+        if let Some(spec) = self.specs.iter().find(missing) {
+            return Some(match spec.def {
+                ArgDef::Long(long) | ArgDef::ShortAndLong { long, .. } if !prefix.is_empty() => {
+                    format!("--{}{}", prefix, long)
                 }
+                def => def.to_string(),
+            });
+        }
+
+        self.namespaces.iter().find_map(|(name, child)| {
+            child.missing_required(&args.scope(name), &format!("{}{}-", prefix, name))
+        })
+    }
+
+    /// Returns `true` if the definition matching `name`, here or in a nested context, is
+    /// [`sensitive`].
+    ///
+    /// [`sensitive`]: ArgSpec::sensitive
+    pub(crate) fn is_sensitive(&self, name: &ArgName) -> bool {
+        self.spec_of(name).is_some_and(|spec| spec.sensitive)
+    }
+
+    /// Returns the action of the definition matching `name`, or [`ArgAction::Infer`] if unknown.
+    fn action_of(&self, name: &ArgName) -> ArgAction {
+        self.spec_of(name).map_or(ArgAction::Infer, |spec| spec.action)
+    }
+
+    /// Checks `value` against the possible and provided values, the range and the validator
+    /// of the definition matching `name`.
+    pub(crate) fn check_value(
+        &self,
+        name: &ArgName,
+        value: &str,
+    ) -> Result<(), ParseArgError<'static>> {
+        let Some(spec) = self.spec_of(name) else {
+            return Ok(());
+        };
+        let arg = || types::display_name(name);
+        let shown = if spec.sensitive { args::REDACTED } else { value };
+
+        // A flag given a value (e.g., `--follow=false`) sets its state explicitly
+        if spec.is_boolean() {
+            return match value {
+                "true" | "false" => Ok(()),
+                _ => Err(ParseArgError::InvalidValue {
+                    arg: arg(),
+                    value: shown.to_string(),
+                    possible: vec!["true".into(), "false".into()],
+                }),
+            };
+        }
+
+        if !spec.possible_values.is_empty() && !spec.possible_values.contains(&value) {
+            return Err(ParseArgError::InvalidValue {
+                arg: arg(),
+                value: shown.to_string(),
+                possible: spec.possible_values.iter().map(|v| v.to_string()).collect(),
+            });
+        }
+
+        if let Some(provider) = &spec.values_from {
+            let provided = provider();
+            if !provided.iter().any(|v| v == value) {
+                return Err(ParseArgError::InvalidValue {
+                    arg: arg(),
+                    value: shown.to_string(),
+                    possible: provided,
+                });
             }
         }
 
+        spec.validate(value).map_err(|reason| ParseArgError::RejectedValue {
+            arg: arg(),
+            value: shown.to_string(),
+            reason,
+        })
+    }
+
+    /// Returns the conflict `arg` would cause if it were registered, if any.
+    fn find_conflict(&self, arg: ArgDef<'a>) -> Option<DefinitionConflict<'a>> {
+        let (short, long) = (arg.short(), arg.long());
+
+        short
+            .and_then(|s| self.short_map.get(&s).map(|index| &self.specs[index]))
+            .or_else(|| long.and_then(|l| self.find_long(l)))
+            .map(|existing| DefinitionConflict {
+                existing: existing.def,
+                conflicting: arg,
+            })
+    }
+
+    /// Stores `spec` and indexes its names. Callers must check for conflicts first.
+    fn insert(&mut self, spec: ArgSpec<'a>) {
         // No conflict, insert and update maps
-        let index = self.defs.len();
-        match &arg {
+        let index = self.specs.len();
+        self.specs.push(spec);
+        self.index(index);
+    }
+
+    /// Adds the names of the definition at `index` to the lookup maps.
+    fn index(&mut self, index: usize) {
+        match self.specs[index].def {
             ArgDef::Short(s) => {
-                self.short_map.insert(*s, index);
+                self.short_map.insert(s, index);
             }
             ArgDef::Long(l) => {
-                self.long_map.insert(l, index);
+                self.index_long(l, index);
             }
             ArgDef::ShortAndLong { short, long } => {
-                self.short_map.insert(*short, index);
-                self.long_map.insert(long, index);
+                self.short_map.insert(short, index);
+                self.index_long(long, index);
+            }
+        }
+        for &old in self.specs[index].renamed_from {
+            self.index_long(old, index);
+        }
+        if let Some(long) = self.localized_long(&self.specs[index]) {
+            self.index_long(long, index);
+        }
+    }
+
+    /// Adds the long name `long` of the definition at `index` to the lookup maps.
+    fn index_long(&mut self, long: &'a str, index: usize) {
+        let symbol = self.longs.intern(long.into());
+        self.long_map.insert(symbol, index);
+        if self.normalize_underscores {
+            let normalized = match long.contains('_') {
+                true => self.longs.intern(long.replace('_', "-").into()),
+                false => symbol,
+            };
+            // The first definition registered wins
+            if !self.normalized_map.contains_key(normalized) {
+                self.normalized_map.insert(normalized, index);
             }
         }
+    }
 
-        self.defs.push(arg);
-        Ok(self)
+    /// Rebuilds the lookup maps after definitions were removed.
+    fn reindex(&mut self) {
+        self.short_map.clear();
+        self.longs.clear();
+        self.long_map.clear();
+        self.normalized_map.clear();
+        (0..self.specs.len()).for_each(|index| self.index(index));
     }
 }
 
-pub fn parse_with_ctx<'a>(
-    args: &'a [&str],
-    ctx: &ParserContext,
-) -> Result<Args<'a>, ParseArgError<'a>> {
-    let mut result = Vec::new();
-    let mut args = args.iter().peekable();
+// Contexts are meant to be built once and shared through `FrozenContext`, so everything
+// stored in one, such as the catalog, the observer and value providers, must be thread-safe.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<ParserContext<'static>>();
+};
+
+/// An immutable [`ParserContext`] that is cheap to clone and safe to share across threads.
+///
+/// Created with [`ParserContext::freeze`]. Cloning only bumps a reference count, so a server
+/// can build its context once at startup and hand a copy to every worker. It dereferences to
+/// [`ParserContext`], so it can be passed anywhere a `&ParserContext` is expected.
+///
+/// # Examples
+///
+/// ```
+/// # use argsparse::{Args, ParserContext};
+/// # use std::thread;
+/// let mut ctx = ParserContext::new();
+/// ctx.flag('v', "verbose").option('o', "output");
+/// let ctx = ctx.freeze();
+///
+/// let workers: Vec<_> = (0..4)
+///     .map(|i| {
+///         let ctx = ctx.clone();
+///         thread::spawn(move || {
+///             let output = format!("out-{}.txt", i);
+///             let argv = ["-v", "--output", output.as_str()];
+///             Args::parse_with_context(&argv, &ctx).map(|args| args.len()).unwrap()
+///         })
+///     })
+///     .collect();
+///
+/// for worker in workers {
+///     assert_eq!(worker.join().unwrap(), 2);
+/// }
+/// ```
+///
+/// [`ParserContext`]: crate::ParserContext
+/// [`ParserContext::freeze`]: crate::ParserContext::freeze
+#[derive(Debug, Clone)]
+pub struct FrozenContext<'a>(Arc<ParserContext<'a>>);
+
+impl<'a> Deref for FrozenContext<'a> {
+    type Target = ParserContext<'a>;
 
-    let mut positional = false;
+    fn deref(&self) -> &ParserContext<'a> {
+        &self.0
+    }
+}
 
-    while let Some(&arg) = args.next() {
-        if positional {
-            result.push(parse_positional(arg));
-            continue;
+impl<'a> From<ParserContext<'a>> for FrozenContext<'a> {
+    fn from(ctx: ParserContext<'a>) -> Self {
+        ctx.freeze()
+    }
+}
+
+impl Default for ParserContext<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds a context by registering the definitions in order, failing with the
+/// [`RegisterError`] of the first one that [`register`] rejects.
+///
+/// # Examples
+///
+/// ```
+/// # use argsparse::{ArgDef, ParserContext, RegisterError};
+/// let ctx = ParserContext::try_from(vec![ArgDef::Short('v'), ArgDef::Long("quiet")]);
+/// assert!(ctx.is_ok());
+///
+/// let err = ParserContext::try_from(vec![ArgDef::Short('v'), ArgDef::Short('-')]).unwrap_err();
+/// assert_eq!(err, RegisterError::InvalidShort('-'));
+/// ```
+///
+/// [`register`]: ParserContext::register
+impl<'a> TryFrom<Vec<ArgDef<'a>>> for ParserContext<'a> {
+    type Error = RegisterError;
+
+    fn try_from(defs: Vec<ArgDef<'a>>) -> Result<Self, Self::Error> {
+        ParserContext::try_from(defs.as_slice())
+    }
+}
+
+/// Builds a context from a slice of definitions, as from a [`Vec`].
+impl<'a> TryFrom<&[ArgDef<'a>]> for ParserContext<'a> {
+    type Error = RegisterError;
+
+    fn try_from(defs: &[ArgDef<'a>]) -> Result<Self, Self::Error> {
+        let mut ctx = ParserContext::new();
+        for &def in defs {
+            ctx.register(def)?;
         }
+        Ok(ctx)
+    }
+}
 
-        if arg == "--" {
-            positional = true;
-            continue;
+/// A short cluster (e.g., `-abc`) whose arguments have not all been yielded yet.
+///
+/// Names are turned into arguments one at a time, so no intermediate collection is built
+/// no matter how long the cluster is.
+struct Cluster<'a> {
+    /// The names not yet yielded.
+    names: Chars<'a>,
+    /// The value taken by the cluster, if any.
+    value: Option<&'a str>,
+    /// Whether the value was given with `=`, which binds it to every name in the cluster.
+    explicit: bool,
+}
+
+/// A lazy parser that yields one [`Argument`] at a time.
+///
+/// Created by [`Args::parse_iter`] and [`Args::parse_iter_with_context`]. Tokens are pulled
+/// from the input only as arguments are requested, so arbitrarily long command lines can be
+/// processed without collecting them first.
+///
+/// Checks that need the whole command line, such as required definitions, are not performed.
+/// After an error is yielded, the iterator is exhausted.
+///
+/// [`Argument`]: crate::Argument
+/// [`Args::parse_iter`]: crate::Args::parse_iter
+/// [`Args::parse_iter_with_context`]: crate::Args::parse_iter_with_context
+pub struct ParseIter<'a, 'c, I: Iterator<Item = &'a str>> {
+    /// The remaining raw tokens, along with their position on the command line.
+    input: Peekable<Enumerate<I>>,
+    /// The context used to validate and classify names, if any.
+    ctx: Option<&'c ParserContext<'c>>,
+    /// The short cluster currently being yielded, if any.
+    cluster: Option<Cluster<'a>>,
+    /// Whether the terminator (`--` by default) was seen.
+    positional: bool,
+    /// Whether an error was yielded.
+    failed: bool,
+    /// The number of arguments yielded so far.
+    yielded: usize,
+    /// The number of arguments yielded before the `--` separator, once it was seen.
+    trailing: Option<usize>,
+    /// The option capturing the tokens that follow, if any.
+    capture: Option<Capture<'a, 'c>>,
+    /// The long name last given with a single dash by mistake and taken as such, until the
+    /// caller warns about it.
+    pub(crate) corrected: Option<&'a str>,
+}
+
+/// The state of an option with [`ArgAction::Capture`] while it takes the tokens that follow.
+struct Capture<'a, 'c> {
+    /// The name the option was given under.
+    name: ArgName<'a>,
+    /// The token that ends the capture, if any.
+    terminator: Option<&'c str>,
+    /// Whether the option still has to be yielded, having captured nothing yet.
+    pending: bool,
+}
+
+impl<'a, 'c, I: Iterator<Item = &'a str>> ParseIter<'a, 'c, I> {
+    pub(crate) fn new(input: I, ctx: Option<&'c ParserContext<'c>>) -> Self {
+        Self {
+            input: input.enumerate().peekable(),
+            ctx,
+            cluster: None,
+            positional: false,
+            failed: false,
+            yielded: 0,
+            trailing: None,
+            capture: None,
+            corrected: None,
         }
+    }
 
-        if arg.starts_with("--") {
-            // Long argument
-            let parsed = parse_long(arg, &mut args)?;
-            let name = match parsed.name() {
-                Some(ArgName::Long(name)) => name,
-                Some(_) => unreachable!("parse_long should never return a short name"),
-                None => {
-                    result.push(parsed);
-                    return Ok(Args(result)); // or continue, depending on your logic
+    /// Returns the number of arguments yielded before the `--` separator, or `None` if the
+    /// separator has not been seen yet.
+    ///
+    /// Every argument yielded from that point on came after the separator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::Args;
+    /// let mut iter = Args::parse_iter(["a", "--", "b"]);
+    /// iter.next();
+    /// assert_eq!(iter.trailing_start(), None);
+    ///
+    /// iter.next();
+    /// assert_eq!(iter.trailing_start(), Some(1));
+    /// ```
+    pub fn trailing_start(&self) -> Option<usize> {
+        self.trailing
+    }
+
+    /// Returns `arg`, as yielded by this iterator, with its value shown as `<redacted>` if
+    /// the context marks it [`sensitive`].
+    ///
+    /// The arguments yielded keep their values; pass them through this method before
+    /// printing them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Args, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(ArgDef::Long("token")).action(ArgAction::Value).sensitive(true))
+    ///     .unwrap();
+    ///
+    /// let mut iter = Args::parse_iter_with_context(["--token", "hunter2"], &ctx);
+    /// let arg = iter.next().unwrap().unwrap();
+    ///
+    /// assert!(format!("{:?}", arg).contains("hunter2"));
+    /// assert!(!format!("{:?}", iter.shown(&arg)).contains("hunter2"));
+    /// ```
+    ///
+    /// [`sensitive`]: ArgSpec::sensitive
+    pub fn shown<'s>(&self, arg: &'s Argument<'a>) -> Cow<'s, Argument<'a>> {
+        match (arg, self.ctx) {
+            (Argument::Option { name, .. }, Some(ctx)) if ctx.is_sensitive(name) => {
+                Cow::Owned(Argument::Option { name: name.clone(), value: args::REDACTED.into() })
+            }
+            (arg, _) => Cow::Borrowed(arg),
+        }
+    }
+
+    /// Takes the next token as is if it starts a new argument and satisfies `predicate`.
+    #[cfg_attr(not(feature = "expr"), allow(dead_code))]
+    pub(crate) fn next_token_if<P>(&mut self, predicate: P) -> Option<&'a str>
+    where
+        P: FnOnce(&str) -> bool,
+    {
+        if self.cluster.as_ref().is_some_and(|cluster| cluster.names.clone().next().is_some())
+            || self.capture.is_some()
+            || self.positional
+        {
+            return None;
+        }
+        self.input.next_if(|&(_, token)| predicate(token)).map(|(_, token)| token)
+    }
+
+    /// Returns `true` if the option named `name` captures the tokens that follow it.
+    fn captures(&self, name: &ArgName) -> bool {
+        self.ctx.is_some_and(|ctx| ctx.action_of(name) == ArgAction::Capture)
+    }
+
+    /// Yields the next argument of the current short cluster, if one is in progress.
+    fn next_clustered(&mut self) -> Option<Result<Argument<'a>, ParseArgError<'a>>> {
+        let cluster = self.cluster.as_mut()?;
+        let Some(short) = cluster.names.next() else {
+            self.cluster = None;
+            return None;
+        };
+
+        // A multi-codepoint character can't be a short name; report it whole
+        let continues = |names: &Chars| {
+            names.clone().next().is_some_and(|next| unicode::continues_grapheme(short, next))
+        };
+        if continues(&cluster.names) {
+            let mut grapheme = String::from(short);
+            while continues(&cluster.names) {
+                grapheme.extend(cluster.names.next());
+            }
+            return Some(Err(ParseArgError::InvalidShort(grapheme)));
+        }
+
+        let name = ArgName::Short(short);
+        let action = match self.ctx.map(|ctx| ctx.spec_of(&name)) {
+            Some(Some(spec)) => spec.action,
+            Some(None) => return Some(Err(ParseArgError::UnknownShort(short.into()))),
+            None => ArgAction::Infer,
+        };
+
+        Some(Ok(match cluster.value {
+            Some(value) if cluster.explicit || action != ArgAction::Flag => Argument::Option {
+                name,
+                value: value.into(),
+            },
+            _ => Argument::Flag { name },
+        }))
+    }
+
+    /// Parses a single token, returning `None` when it yields no argument (e.g., `--`, or
+    /// a short cluster whose arguments are then yielded by [`next_clustered`]).
+    ///
+    /// [`next_clustered`]: ParseIter::next_clustered
+    fn parse_token(
+        &mut self,
+        index: usize,
+        arg: &'a str,
+    ) -> Result<Option<Argument<'a>>, ParseArgError<'a>> {
+        if let Some(capture) = &mut self.capture {
+            if capture.terminator == Some(arg) {
+                let capture = self.capture.take().unwrap();
+                return Ok(capture.pending.then_some(Argument::Flag { name: capture.name }));
+            }
+            capture.pending = false;
+            let name = capture.name.clone();
+            return Ok(Some(Argument::Option { name, value: arg.into() }));
+        }
+        if self.positional {
+            return Ok(Some(parse_positional(arg)));
+        }
+
+        let terminator = self.ctx.map_or(Some("--"), |ctx| ctx.terminator);
+        if terminator == Some(arg) {
+            self.positional = true;
+            self.trailing = Some(self.yielded);
+            return Ok(None);
+        }
+
+        let ctx = self.ctx;
+        let action_of = |name: &ArgName| ctx.map_or(ArgAction::Infer, |ctx| ctx.action_of(name));
+
+        let mut long_name = arg.strip_prefix("--");
+        if long_name.is_none()
+            && let Some(ctx) = ctx
+            && let Some(name) = ctx.single_dash_long(arg)
+        {
+            // A single dash followed by a long name, in the style of find(1), or by mistake
+            // when it makes no sense as a cluster of short names
+            if ctx.single_dash_longs {
+                long_name = Some(name);
+            } else if !ctx.is_cluster(index, arg) {
+                let long = name.split_once('=').map_or(name, |(long, _)| long);
+                if !ctx.correct_single_dash_longs {
+                    return Err(ParseArgError::SingleDashLong { index, long: long.to_string() });
                 }
+                self.corrected = Some(long);
+                long_name = Some(name);
+            }
+        }
+        if let Some(long_name) = long_name {
+            // Long argument
+            let mode_of = |name: &str| match ctx.and_then(|ctx| ctx.negated(name)) {
+                Some(_) => ArgAction::Flag,
+                None => action_of(&ArgName::Long(name.into())),
             };
-
-            if !ctx.long_map.contains_key(name) {
-                return Err(ParseArgError::UnknownLong(name.into()));
+            if let Some(ctx) = ctx
+                && !long_name.contains('=')
+            {
+                let next = self.input.peek().map(|&(_, next)| next);
+                ctx.check_value_follows(index, &ArgName::Long(long_name.into()), next)?;
+            }
+            let parsed = parse_long(long_name, &mut self.input, mode_of);
+            if let (Some(ctx), Some(ArgName::Long(name))) = (ctx, parsed.name()) {
+                // Record a negation as the value of the definition it negates
+                if let Argument::Flag { .. } = parsed
+                    && let Some(spec) = ctx.negated(name)
+                    && let (Some(long), Some(value)) = (spec.def.long(), spec.negation)
+                {
+                    return Ok(Some(Argument::Option {
+                        name: ArgName::Long(long.to_string().into()),
+                        value: value.to_string().into(),
+                    }));
+                }
+                if ctx.find_long(name).is_none() {
+                    return Err(ParseArgError::UnknownLong(name.to_string()));
+                }
+                // Record a normalized name under the one of its definition; old names of
+                // renamed definitions are left to the caller, which warns about them
+                let spec = ctx.long_index(name).map(|index| &ctx.specs[index]);
+                let long = spec
+                    .filter(|spec| !spec.renamed_from.contains(&name.as_ref()))
+                    .and_then(|spec| spec.def.long());
+                if let Some(long) = long.filter(|long| long != name) {
+                    let name = ArgName::Long(long.to_string().into());
+                    return Ok(Some(match parsed {
+                        Argument::Option { value, .. } => Argument::Option { name, value },
+                        _ => Argument::Flag { name },
+                    }));
+                }
             }
-            result.push(parsed);
-        } else if arg.starts_with("-") && arg.len() > 1 {
+            Ok(Some(parsed))
+        } else if arg.starts_with("-") && (ctx.is_none() || arg.len() > 1) {
             // Short or cluster
-            let mut parsed_args = parse_short(arg, &mut args)?;
-            for short_arg in &parsed_args {
-                if let Some(name) = short_arg.name() {
-                    match name {
-                        ArgName::Short(name) => {
-                            if !ctx.short_map.contains_key(&name) {
-                                return Err(ParseArgError::UnknownShort(name.into()));
-                            }
-                        }
-                        _ => unreachable!(
-                            "parse_short should never return an argument with a short name"
-                        ),
-                    }
+            if let Some(ctx) = ctx {
+                ctx.check_cluster(index, arg)?;
+                let next = self.input.peek().map(|&(_, next)| next);
+                let value = arg[1..]
+                    .chars()
+                    .find(|&short| ctx.action_of(&ArgName::Short(short)) == ArgAction::Value);
+                if let Some(short) = value.filter(|_| !arg.contains('=')) {
+                    ctx.check_value_follows(index, &ArgName::Short(short), next)?;
                 }
             }
-            result.append(&mut parsed_args);
+            let mode_of = |name| action_of(&ArgName::Short(name));
+            let cluster = parse_short(index, arg, &mut self.input, mode_of)?;
+            self.cluster = Some(cluster);
+            Ok(None)
         } else {
-            result.push(parse_positional(arg));
+            Ok(Some(parse_positional(arg)))
         }
     }
+}
+
+impl<'a, 'c, I: Iterator<Item = &'a str>> Iterator for ParseIter<'a, 'c, I> {
+    type Item = Result<Argument<'a>, ParseArgError<'a>>;
 
-    Ok(Args(result))
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.failed {
+            let captured = self.capture.is_some();
+            let result = match self.next_clustered() {
+                Some(result) => result.map(Some),
+                None => match self.input.next() {
+                    Some((index, arg)) => match self.ctx.map(|ctx| ctx.check_token(index, arg)) {
+                        Some(Err(err)) => Err(err),
+                        _ => self.parse_token(index, arg),
+                    },
+                    // A capture that took nothing still records its option
+                    None => {
+                        let capture = self.capture.take().filter(|capture| capture.pending)?;
+                        Ok(Some(Argument::Flag { name: capture.name }))
+                    }
+                },
+            };
+
+            match result {
+                Ok(Some(parsed)) => {
+                    // An option that captures the tokens that follow; given without a value,
+                    // it is only yielded on its own if it captures nothing
+                    if !captured
+                        && let Some(name) = parsed.name()
+                        && self.captures(name)
+                    {
+                        let terminator = self.ctx.and_then(|ctx| ctx.spec_of(name)?.capture_until);
+                        let pending = matches!(parsed, Argument::Flag { .. });
+                        self.capture = Some(Capture { name: name.clone(), terminator, pending });
+                        if pending {
+                            continue;
+                        }
+                    }
+                    self.yielded += 1;
+                    return Some(Ok(parsed));
+                }
+                Ok(None) => continue,
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+
+        None
+    }
 }
 
-pub fn parse<'a>(args: &'a [&str]) -> Result<Args<'a>, ParseArgError<'a>> {
-    let mut result = Vec::new();
-    let mut args = args.iter().peekable();
+pub fn parse_with_ctx<'a, 'c>(
+    args: &'a [&str],
+    ctx: &'c ParserContext<'c>,
+) -> Result<Matches<'a, 'c>, ParseArgError<'a>> {
+    // Bound the input before spending anything on it
+    for (index, token) in args.iter().enumerate() {
+        ctx.check_token(index, token)?;
+    }
+
+    let Some(tokens) = ctx.transform(args) else {
+        return parse_tokens(args, ctx);
+    };
 
-    let mut positional = false;
-    while let Some(&arg) = args.next() {
-        if positional {
-            result.push(parse_positional(arg));
-            continue;
+    // The rewritten tokens are local, so the result must own its strings
+    let tokens: Vec<&str> = tokens.iter().map(String::as_str).collect();
+    parse_tokens(&tokens, ctx)
+        .map(Matches::into_owned)
+        .map_err(ParseArgError::into_owned)
+}
+
+/// Parses `args` with `ctx`, once the token transforms have run.
+fn parse_tokens<'a, 'c>(
+    args: &'a [&str],
+    ctx: &'c ParserContext<'c>,
+) -> Result<Matches<'a, 'c>, ParseArgError<'a>> {
+    let mut matches = Matches::new(ctx);
+    let mut values = vec![0; ctx.len()];
+    let mut iter = ParseIter::new(args.iter().copied(), Some(ctx));
+    while let Some(arg) = iter.next() {
+        let mut arg = match arg {
+            Ok(arg) => arg,
+            Err(err) => {
+                if let Some(observer) = ctx.observer {
+                    match &err {
+                        ParseArgError::UnknownLong(name) => {
+                            observer.unknown_option(&format!("--{}", name))
+                        }
+                        ParseArgError::UnknownShort(name)
+                        | ParseArgError::SingleDashLong { long: name, .. } => {
+                            observer.unknown_option(&format!("-{}", name))
+                        }
+                        _ => {}
+                    }
+                }
+                return Err(err);
+            }
+        };
+        if let Some(long) = iter.corrected.take() {
+            let warning = ctx.text(&Message::CorrectedLong {
+                arg: &format!("-{}", long),
+                long: &format!("--{}", long),
+            });
+            matches.warn(warning);
         }
+        // An old spelling is recorded under the current name
+        if let Some(ArgName::Long(old)) = arg.name()
+            && let Some(spec) = ctx.spec_of(&ArgName::Long(old.clone()))
+            && spec.renamed_from.contains(&old.as_ref())
+            && let Some(long) = spec.def.long()
+        {
+            let warning = ctx.text(&Message::RenamedArg {
+                arg: &format!("--{}", old),
+                new: &format!("--{}", long),
+            });
+            matches.warn(warning);
+            if let Some(observer) = ctx.observer {
+                observer.deprecated(spec);
+            }
+            let name = ArgName::Long(long.to_string().into());
+            arg = match arg {
+                Argument::Flag { .. } => Argument::Flag { name },
+                Argument::Option { value, .. } => Argument::Option { name, value },
+                positional => positional,
+            };
+        }
+        if let Argument::Option { name, value } = &arg {
+            ctx.check_value(name, value)?;
+            if let Some(max) = ctx.limits.max_values
+                && let Some(id) = ctx.id_of(name)
+            {
+                values[id] += 1;
+                if values[id] > max {
+                    let arg = types::display_name(name);
+                    return Err(ParseArgError::LimitsExceeded { limit: Limit::Values { arg }, max });
+                }
+            }
+        }
+        if let Some(name) = arg.name()
+            && let Some(spec) = ctx.spec_of(name)
+        {
+            let typed = || types::display_name(name);
+            if spec.is_removed(ctx.version) {
+                let version = spec.removed_in.unwrap_or_default().to_string();
+                return Err(ParseArgError::RemovedArg { arg: typed(), version });
+            }
+            if spec.is_deprecated(ctx.version) {
+                let warning = ctx.text(&Message::DeprecatedArg {
+                    arg: &typed(),
+                    since: spec.deprecated_in,
+                    removal: spec.removed_in,
+                    note: spec.deprecated,
+                });
+                matches.warn(warning);
+                if let Some(observer) = ctx.observer {
+                    observer.deprecated(spec);
+                }
+            }
+        }
+        matches.push(arg);
+    }
+    matches.set_trailing(iter.trailing_start());
+    matches.resolve_overrides();
+
+    // Exclusive arguments like `--help` skip the checks on the command line as a whole
+    if !ctx.specs.iter().any(|spec| spec.exclusive && matches.has(spec.def)) {
+        #[cfg(feature = "prompt")]
+        ctx.prompt_missing(&mut matches);
 
-        if arg == "--" {
-            positional = true;
-            continue;
+        if let Some(missing) = ctx.missing_required(&matches, "") {
+            return Err(ParseArgError::MissingRequired(missing));
         }
+        ctx.check_occurrences(&matches)?;
+        ctx.check_positionals(&matches)?;
+    }
 
-        if arg.starts_with("--") {
-            result.push(parse_long(arg, &mut args)?);
-        } else if arg.starts_with("-") {
-            result.append(&mut parse_short(arg, &mut args)?);
-        } else {
-            result.push(parse_positional(arg));
+    if let Some(observer) = ctx.observer {
+        for spec in ctx.specs.iter().filter(|spec| !matches.has(spec.def)) {
+            if let Some((_, source)) = ctx.fallback(spec) {
+                observer.fallback(spec, source);
+            }
         }
     }
 
-    Ok(Args(result))
+    Ok(matches)
+}
+
+pub fn parse<'a>(args: &'a [&str]) -> Result<Args<'a>, ParseArgError<'a>> {
+    let mut iter = ParseIter::new(args.iter().copied(), None);
+    let mut args = Args::from((&mut iter).collect::<Result<Vec<_>, _>>()?);
+    args.set_trailing(iter.trailing_start());
+    Ok(args)
 }
 
-fn parse_positional(arg: &str) -> Argument {
+fn parse_positional(arg: &str) -> Argument<'_> {
     Argument::Positional { value: arg.into() }
 }
 
-fn parse_long<'a, I>(
-    arg: &'a str,
-    input: &mut Peekable<I>,
-) -> Result<Argument<'a>, ParseArgError<'a>>
+/// Parses the long argument `long_name`, given without its leading dashes.
+fn parse_long<'a, I, M>(long_name: &'a str, input: &mut Peekable<I>, mode_of: M) -> Argument<'a>
 where
-    I: Iterator<Item = &'a &'a str>,
+    I: Iterator<Item = (usize, &'a str)>,
+    M: Fn(&str) -> ArgAction,
 {
-    if let Some((name, value)) = arg.split_once("=") {
-        Ok(Argument::Option {
-            name: ArgName::Long(name),
-            value,
-        })
-    } else if let Some(long_name) = arg.strip_prefix("--") {
-        if let Some(&next) = input.peek() {
-            if next.starts_with("-") {
-                Ok(Argument::Flag {
-                    name: ArgName::Long(long_name),
-                })
+    // Everything after the first `=` is the value, taken verbatim
+    if let Some((name, value)) = long_name.split_once('=') {
+        Argument::Option {
+            name: ArgName::Long(name.into()),
+            value: value.into(),
+        }
+    } else {
+        let mode = mode_of(long_name);
+        if let Some(&(_, next)) = input.peek()
+            && mode != ArgAction::Flag
+            && mode != ArgAction::Capture
+        {
+            if mode == ArgAction::Infer && next.starts_with("-") {
+                Argument::Flag {
+                    name: ArgName::Long(long_name.into()),
+                }
             } else {
-                let value = input.next().unwrap();
-                Ok(Argument::Option {
-                    name: ArgName::Long(long_name),
-                    value,
-                })
+                let (_, value) = input.next().unwrap();
+                Argument::Option {
+                    name: ArgName::Long(long_name.into()),
+                    value: value.into(),
+                }
             }
         } else {
-            Ok(Argument::Flag {
-                name: ArgName::Long(long_name),
-            })
+            Argument::Flag {
+                name: ArgName::Long(long_name.into()),
+            }
         }
-    } else {
-        Err(ParseArgError::MalformedArg(
-            "Malformed argument at position {index}",
-        ))
     }
 }
 
-fn parse_short<'a, I>(
+fn parse_short<'a, I, M>(
+    index: usize,
     arg: &'a str,
     input: &mut Peekable<I>,
-) -> Result<Vec<Argument<'a>>, ParseArgError<'a>>
+    action_of: M,
+) -> Result<Cluster<'a>, ParseArgError<'a>>
 where
-    I: Iterator<Item = &'a &'a str>,
+    I: Iterator<Item = (usize, &'a str)>,
+    M: Fn(char) -> ArgAction,
 {
     if arg.len() < 2 {
-        return Err(ParseArgError::MalformedArg(
-            "Malformed argument at position {index}".into(),
-        ));
+        return Err(ParseArgError::MalformedArg { index, token: arg.into() });
     }
 
+    // As with longs, everything after the first `=` is the value, taken verbatim
     if let Some((names, value)) = arg.split_once('=') {
-        return Ok(names
-            .chars()
-            .skip(1)
-            .map(|short| Argument::Option {
-                name: ArgName::Short(short),
-                value,
-            })
-            .collect());
+        return Ok(Cluster {
+            names: names[1..].chars(),
+            value: Some(value),
+            explicit: true,
+        });
     }
 
-    let chars: Vec<char> = arg.chars().skip(1).collect();
-
-    if let Some(&next) = input.peek() {
-        if !next.starts_with('-') {
-            let value = input.next().unwrap(); // consume the argument
-            return Ok(chars
-                .into_iter()
-                .map(|short| Argument::Option {
-                    name: ArgName::Short(short),
-                    value,
-                })
-                .collect());
-        }
+    let names = &arg[1..];
+    let (mut always, mut infer) = (false, false);
+    for action in names.chars().map(action_of) {
+        always |= action == ArgAction::Value;
+        infer |= action == ArgAction::Infer;
     }
 
-    Ok(chars
-        .into_iter()
-        .map(|short| Argument::Flag {
-            name: ArgName::Short(short),
-        })
-        .collect())
+    // Flags never take a value, so a cluster made only of flags leaves the next token alone
+    let wants_value =
+        always || infer && input.peek().is_some_and(|(_, next)| !next.starts_with('-'));
+
+    Ok(Cluster {
+        names: names.chars(),
+        value: if wants_value { input.next().map(|(_, value)| value) } else { None },
+        explicit: false,
+    })
 }
@@ -0,0 +1,149 @@
+use crate::defs::{ArgName, Argument};
+use crate::matches::Matches;
+use crate::parser::ParserContext;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+#[cfg(unix)]
+use std::process::Command;
+
+impl ParserContext<'_> {
+    /// Asks on the terminal for the definitions with a [`prompt`] that no source provides
+    /// a value for, recording the answers as if given on the command line.
+    ///
+    /// Nothing is asked when there is no terminal, or when it cannot be read; a required
+    /// argument is then reported missing as usual.
+    ///
+    /// [`prompt`]: crate::ArgSpec::prompt
+    pub(crate) fn prompt_missing(&self, matches: &mut Matches) {
+        for spec in self.specs() {
+            let Some(prompt) = spec.prompt else {
+                continue;
+            };
+            if matches.has(spec.def) || self.fallback(spec).is_some() {
+                continue;
+            }
+            let name = match (spec.def.long(), spec.def.short()) {
+                (Some(long), _) => ArgName::Long(long.to_string().into()),
+                (None, Some(short)) => ArgName::Short(short),
+                (None, None) => continue,
+            };
+            let Ok(value) = read_tty(prompt, !spec.sensitive) else {
+                return;
+            };
+            matches.push(Argument::Option { name, value: value.into() });
+        }
+    }
+}
+
+/// Writes `prompt` to the terminal and reads a line from it, without the keys typed being
+/// shown unless `echo` is set.
+fn read_tty(prompt: &str, echo: bool) -> io::Result<String> {
+    let (input, mut output) = open_tty()?;
+    write!(output, "{}", prompt)?;
+    output.flush()?;
+
+    let hidden = if echo { None } else { Some(Echo::hide(&input)?) };
+    let mut line = String::new();
+    let read = BufReader::new(&input).read_line(&mut line);
+    if let Some(hidden) = hidden {
+        hidden.restore(&input)?;
+        // The newline typed was not shown either
+        writeln!(output)?;
+    }
+    if read? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Opens the terminal of the process for reading and for writing.
+fn open_tty() -> io::Result<(File, File)> {
+    let (input, output) = match cfg!(windows) {
+        true => ("CONIN$", "CONOUT$"),
+        false => ("/dev/tty", "/dev/tty"),
+    };
+    let input = OpenOptions::new().read(true).write(true).open(input)?;
+    let output = OpenOptions::new().write(true).open(output)?;
+    Ok((input, output))
+}
+
+/// The echo of a terminal, turned off until restored.
+struct Echo {
+    #[cfg(windows)]
+    mode: u32,
+}
+
+#[cfg(unix)]
+impl Echo {
+    fn hide(tty: &File) -> io::Result<Self> {
+        stty(tty, "-echo")?;
+        Ok(Echo {})
+    }
+
+    fn restore(self, tty: &File) -> io::Result<()> {
+        stty(tty, "echo")
+    }
+}
+
+/// Runs stty(1) on `tty`, which saves binding termios(3) without a libc dependency.
+#[cfg(unix)]
+fn stty(tty: &File, setting: &str) -> io::Result<()> {
+    let status = Command::new("stty").arg(setting).stdin(tty.try_clone()?).status()?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(io::Error::other("stty failed")),
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+impl Echo {
+    fn hide(_: &File) -> io::Result<Self> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    fn restore(self, _: &File) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+impl Echo {
+    const ENABLE_ECHO_INPUT: u32 = 0x0004;
+
+    fn hide(tty: &File) -> io::Result<Self> {
+        let mut mode = 0;
+        console::call(unsafe { console::GetConsoleMode(console::handle(tty), &mut mode) })?;
+        console::call(unsafe {
+            console::SetConsoleMode(console::handle(tty), mode & !Self::ENABLE_ECHO_INPUT)
+        })?;
+        Ok(Echo { mode })
+    }
+
+    fn restore(self, tty: &File) -> io::Result<()> {
+        console::call(unsafe { console::SetConsoleMode(console::handle(tty), self.mode) })
+    }
+}
+
+#[cfg(windows)]
+mod console {
+    use std::ffi::c_void;
+    use std::fs::File;
+    use std::io;
+    use std::os::windows::io::AsRawHandle;
+
+    unsafe extern "system" {
+        pub fn GetConsoleMode(handle: *mut c_void, mode: *mut u32) -> i32;
+        pub fn SetConsoleMode(handle: *mut c_void, mode: u32) -> i32;
+    }
+
+    pub fn handle(file: &File) -> *mut c_void {
+        file.as_raw_handle()
+    }
+
+    pub fn call(result: i32) -> io::Result<()> {
+        match result {
+            0 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::complete::complete_word;
+use crate::matches::Matches;
+use crate::parser::ParserContext;
+use rustyline::completion::{Completer, FilenameCompleter, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Context, Helper};
+
+/// A [rustyline] helper completing and validating lines against the definitions of a
+/// [`ParserContext`], for interactive shells that parse each line they read.
+///
+/// Tab completes option names, option values from their possible values or
+/// [`values_from`] provider, and paths for values and operands hinted as such. A line is
+/// accepted only once it parses; otherwise the error is shown below it and editing goes on.
+///
+/// Lines are split on whitespace, without shell quoting. Available with the `rustyline`
+/// feature.
+///
+/// # Examples
+///
+/// ```
+/// # use argsparse::{ArgDef, ArgSpec, ParserContext, ReplHelper};
+/// use rustyline::completion::{Candidate, Completer};
+/// use rustyline::history::DefaultHistory;
+/// use rustyline::Context;
+///
+/// let mut ctx = ParserContext::new();
+/// ctx.flag('v', "verbose");
+/// ctx.register(ArgSpec::new(ArgDef::Long("color")).possible_values(&["auto", "never"]))
+///     .unwrap();
+///
+/// let helper = ReplHelper::new(&ctx);
+/// let history = DefaultHistory::new();
+/// let complete = |line: &str| {
+///     let (start, candidates) = helper.complete(line, line.len(), &Context::new(&history))
+///         .unwrap();
+///     let candidates: Vec<_> = candidates.iter().map(|c| c.replacement().to_string())
+///         .collect();
+///     (start, candidates)
+/// };
+///
+/// assert_eq!(complete("-v --ve"), (3, vec!["--verbose".to_string()]));
+/// assert_eq!(complete("--color "), (8, vec!["auto".to_string(), "never".to_string()]));
+/// assert_eq!(complete("--color=n"), (8, vec!["never".to_string()]));
+///
+/// let mut editor: rustyline::Editor<_, DefaultHistory> = rustyline::Editor::new().unwrap();
+/// editor.set_helper(Some(helper));
+/// ```
+///
+/// [rustyline]: https://docs.rs/rustyline
+/// [`ParserContext`]: crate::ParserContext
+/// [`values_from`]: crate::ArgSpec::values_from
+#[derive(Debug, Copy, Clone)]
+pub struct ReplHelper<'c> {
+    ctx: &'c ParserContext<'c>,
+}
+
+impl<'c> ReplHelper<'c> {
+    /// Creates a helper for lines parsed with `ctx`.
+    pub fn new(ctx: &'c ParserContext<'c>) -> Self {
+        ReplHelper { ctx }
+    }
+}
+
+impl Completer for ReplHelper<'_> {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        match complete_word(self.ctx, &line[..pos]) {
+            Some((start, words)) => Ok((start, candidates(words.into_iter()))),
+            None => FilenameCompleter::new().complete(line, pos, ctx),
+        }
+    }
+}
+
+impl Hinter for ReplHelper<'_> {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper<'_> {}
+
+impl Validator for ReplHelper<'_> {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        let tokens: Vec<&str> = ctx.input().split_whitespace().collect();
+        Ok(match Matches::parse(&tokens, self.ctx) {
+            Ok(_) => ValidationResult::Valid(None),
+            Err(err) => {
+                ValidationResult::Invalid(Some(format!("\n{}", self.ctx.render_error(&err))))
+            }
+        })
+    }
+}
+
+impl Helper for ReplHelper<'_> {}
+
+/// Turns completed words into candidates.
+fn candidates(words: impl Iterator<Item = String>) -> Vec<Pair> {
+    words.map(|word| Pair { display: word.clone(), replacement: word }).collect()
+}
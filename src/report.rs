@@ -0,0 +1,152 @@
+use crate::defs::ParseArgError;
+use crate::messages::Message;
+use crate::parser::ParserContext;
+use crate::terminal::{self, Stream};
+use std::io::{self, Write};
+
+const RED: &str = "\x1b[1;31m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+impl ParserContext<'_> {
+    /// Renders `err`, raised while parsing `args` for the program `name`, as a report for
+    /// the user: the message, the command line with the token at fault underlined, a
+    /// suggestion when a defined option or possible value is close to what was typed, and a
+    /// pointer to `--help`.
+    ///
+    /// With `color`, the report is highlighted with ANSI escape sequences.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.flag('v', "verbose");
+    ///
+    /// let args = ["--verbos", "in.txt"];
+    /// let err = Args::parse_with_context(&args, &ctx).unwrap_err();
+    ///
+    /// assert_eq!(ctx.render_error_report("app", &args, &err, false), "\
+    /// error: unknown option '--verbos'
+    ///   app --verbos in.txt
+    ///       ^^^^^^^^ did you mean '--verbose'?
+    /// For more information, try '--help'.
+    /// ");
+    /// ```
+    pub fn render_error_report<S: AsRef<str>>(
+        &self,
+        name: &str,
+        args: &[S],
+        err: &ParseArgError,
+        color: bool,
+    ) -> String {
+        let paint = |text: &str, style: &str| match color {
+            true => format!("{}{}{}", style, text, RESET),
+            false => text.to_string(),
+        };
+
+        let label = paint(&self.text(&Message::Error), RED);
+        let mut out = format!("{}: {}\n", label, self.render_error(err));
+
+        let suggestion = self.suggestion(err);
+        let suggestion = suggestion.as_deref().map(|value| {
+            paint(&self.text(&Message::Suggestion(value)), GREEN)
+        });
+        match err.position(args) {
+            Some(position) => {
+                let tokens: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
+                out.push_str(&format!("  {} {}\n", name, tokens.join(" ")));
+
+                let offset: usize = std::iter::once(name)
+                    .chain(tokens[..position].iter().copied())
+                    .map(|token| token.chars().count() + 1)
+                    .sum();
+                let carets = "^".repeat(tokens[position].chars().count().max(1));
+                out.push_str(&format!("  {}{}", " ".repeat(offset), paint(&carets, RED)));
+                if let Some(suggestion) = &suggestion {
+                    out.push(' ');
+                    out.push_str(suggestion);
+                }
+                out.push('\n');
+            }
+            None => {
+                if let Some(suggestion) = &suggestion {
+                    out.push_str(&format!("  {}\n", suggestion));
+                }
+            }
+        }
+
+        out.push_str(&self.text(&Message::TryHelp));
+        out.push('\n');
+        out
+    }
+
+    /// Prints the report of [`render_error_report`] to the standard error, in color as
+    /// decided by [`terminal::use_color`].
+    ///
+    /// [`render_error_report`]: ParserContext::render_error_report
+    /// [`terminal::use_color`]: crate::terminal::use_color
+    pub fn print_error<S: AsRef<str>>(
+        &self,
+        name: &str,
+        args: &[S],
+        err: &ParseArgError,
+    ) -> io::Result<()> {
+        let color = terminal::use_color(Stream::Stderr);
+        let report = self.render_error_report(name, args, err, color);
+        io::stderr().lock().write_all(report.as_bytes())
+    }
+
+    /// Returns the defined option or possible value closest to the one at fault, if one is
+    /// close enough to be a typo.
+    fn suggestion(&self, err: &ParseArgError) -> Option<String> {
+        match err {
+            ParseArgError::UnknownLong(name) => {
+                let mut longs = Vec::new();
+                self.long_names("", &mut longs);
+                closest(name, longs.iter().map(String::as_str)).map(|long| format!("--{}", long))
+            }
+            ParseArgError::InvalidValue { value, possible, .. } => {
+                closest(value, possible.iter().map(String::as_str)).map(str::to_string)
+            }
+            _ => None,
+        }
+    }
+
+    /// Collects the long names of the visible options of this context and its nested
+    /// contexts.
+    fn long_names(&self, prefix: &str, out: &mut Vec<String>) {
+        let specs = self.specs().iter().filter(|spec| !spec.hidden);
+        out.extend(specs.filter_map(|spec| spec.def.long()).map(|long| prefix.to_string() + long));
+        for (name, child) in self.namespaces() {
+            child.long_names(&format!("{}{}-", prefix, name), out);
+        }
+    }
+}
+
+/// Returns the candidate closest to `typed`, if it takes at most a third of its length in
+/// edits to get there.
+fn closest<'c>(typed: &str, candidates: impl Iterator<Item = &'c str>) -> Option<&'c str> {
+    let limit = (typed.chars().count() / 3).max(1);
+    candidates
+        .map(|candidate| (distance(typed, candidate), candidate))
+        .filter(|&(distance, _)| distance <= limit)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Returns the Levenshtein distance between `a` and `b`, in characters.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
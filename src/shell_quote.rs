@@ -0,0 +1,81 @@
+//! Quoting of tokens for shells, for tools that log a command line or run it again through
+//! `sh -c` or PowerShell.
+//!
+//! Tokens made only of characters that every shell takes literally are left bare; the
+//! others are wrapped in single quotes, the one form in which neither shell expands
+//! anything. Turn parsed arguments back into tokens with [`Args::to_tokens`].
+//!
+//! # Examples
+//!
+//! ```
+//! use argsparse::{shell_quote, Args};
+//!
+//! let argv = ["--message", "it's done", "-v", "--", "$HOME/*.txt"];
+//! let args = Args::parse_all(&argv).unwrap();
+//!
+//! let line = shell_quote::join(args.to_tokens());
+//! assert_eq!(line, r"'--message=it'\''s done' -v -- '$HOME/*.txt'");
+//!
+//! let line = shell_quote::join_powershell(args.to_tokens());
+//! assert_eq!(line, "'--message=it''s done' -v -- '$HOME/*.txt'");
+//! ```
+//!
+//! [`Args::to_tokens`]: crate::Args::to_tokens
+
+use std::borrow::Cow;
+
+/// Quotes `token` for a POSIX shell, leaving it bare when that is safe.
+///
+/// A single quote inside the token ends the quoted string, is escaped, and starts a new
+/// one: `it's` becomes `'it'\''s'`. The empty token becomes `''`.
+pub fn quote(token: &str) -> Cow<'_, str> {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c);
+    if !token.is_empty() && token.chars().all(safe) {
+        Cow::Borrowed(token)
+    } else {
+        Cow::Owned(format!("'{}'", token.replace('\'', r"'\''")))
+    }
+}
+
+/// Quotes every token for a POSIX shell with [`quote`] and joins them with spaces.
+pub fn join<I, S>(tokens: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let tokens: Vec<String> = tokens.into_iter().map(|t| quote(t.as_ref()).into_owned()).collect();
+    tokens.join(" ")
+}
+
+/// Quotes `token` for PowerShell, leaving it bare when that is safe.
+///
+/// Single quotes inside the token are doubled, including the typographic ones PowerShell
+/// also treats as quotes: `it's` becomes `'it''s'`. The empty token becomes `''`.
+pub fn quote_powershell(token: &str) -> Cow<'_, str> {
+    // Unlike POSIX shells, PowerShell gives `,`, `@` and `%` a meaning of their own
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:+".contains(c);
+    if !token.is_empty() && token.chars().all(safe) {
+        return Cow::Borrowed(token);
+    }
+
+    let mut quoted = String::from("'");
+    for c in token.chars() {
+        if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}') {
+            quoted.push(c);
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    Cow::Owned(quoted)
+}
+
+/// Quotes every token for PowerShell with [`quote_powershell`] and joins them with spaces.
+pub fn join_powershell<I, S>(tokens: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let tokens: Vec<String> =
+        tokens.into_iter().map(|t| quote_powershell(t.as_ref()).into_owned()).collect();
+    tokens.join(" ")
+}
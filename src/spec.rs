@@ -0,0 +1,1274 @@
+use crate::defs::{ArgDef, OwnedArgDef, SpecError};
+use crate::observe::SourceKind;
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+use std::ptr;
+use std::sync::Arc;
+
+/// Describes how a defined argument treats the token that follows it.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum ArgAction {
+    /// Takes the next token as a value unless it starts with a dash.
+    ///
+    /// This mirrors how [`Args::parse_all`] behaves when no definitions are known.
+    ///
+    /// [`Args::parse_all`]: crate::Args::parse_all
+    #[default]
+    Infer,
+    /// Never takes a value; the argument is a plain flag.
+    Flag,
+    /// Always takes the next token as its value.
+    ///
+    /// The token is taken verbatim, even if it starts with a dash or is the `--` separator,
+    /// so `--separator --` binds `--` as the value instead of ending option parsing. A token
+    /// naming a defined option, or none at all, is reported as
+    /// [`ParseArgError::MissingValue`]; such a value is given as `--opt=-v` instead.
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Args, OptionArg, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(ArgDef::Long("separator")).action(ArgAction::Value)).unwrap();
+    ///
+    /// let args = Args::parse_with_context(&["--separator", "--", "a"], &ctx).unwrap();
+    /// let sep = args.find::<OptionArg>(ArgDef::Long("separator")).unwrap();
+    ///
+    /// assert_eq!(sep.value, "--");
+    /// assert_eq!(args.len(), 2);
+    /// ```
+    ///
+    /// [`ParseArgError::MissingValue`]: crate::ParseArgError::MissingValue
+    Value,
+    /// Takes every token that follows verbatim as one of its values, in the style of the
+    /// `-exec` option of find(1), until the terminator set with [`ArgSpec::capture_until`]
+    /// or the end of the command line.
+    ///
+    /// Captured tokens are never interpreted, so they may start with a dash or be `--`.
+    /// Each one is recorded as if the option had been repeated with it as its value; the
+    /// option is recorded as a flag when it captures nothing.
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Args, OptionArg, ParserContext};
+    /// let exec = ArgDef::Long("exec");
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(exec).action(ArgAction::Capture).capture_until(";")).unwrap();
+    ///
+    /// let tokens = ["--exec", "rm", "-f", "{}", ";", "src"];
+    /// let args = Args::parse_with_context(&tokens, &ctx).unwrap();
+    /// let command = args.find_all_for::<OptionArg>(exec);
+    ///
+    /// assert_eq!(command.iter().map(|o| o.value).collect::<Vec<_>>(), ["rm", "-f", "{}"]);
+    /// assert_eq!(args.positional::<String>(0).unwrap().as_deref(), Some("src"));
+    /// ```
+    Capture,
+}
+
+/// Describes what kind of value an argument expects, so shell completion can offer
+/// suitable candidates.
+///
+/// Hints are only advisory: they never affect parsing or validation.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum ValueHint {
+    /// Nothing is known about the value.
+    #[default]
+    Unknown,
+    /// A value that has no completion (e.g., a free-form string).
+    Other,
+    /// A path to a file or directory.
+    AnyPath,
+    /// A path to a file.
+    FilePath,
+    /// A path to a directory.
+    DirPath,
+    /// The name of a command found in `PATH`.
+    CommandName,
+    /// A host name.
+    Hostname,
+    /// A user name.
+    Username,
+    /// A URL.
+    Url,
+}
+
+/// A check on the value of an argument, returning the reason a value is rejected.
+pub type Validator = fn(&str) -> Result<(), String>;
+
+/// A source of the values an argument currently accepts, queried when they are needed.
+///
+/// Unlike a [`Validator`], a provider may capture state, such as the directory it lists.
+pub type ValueProvider = Arc<dyn Fn() -> Vec<String> + Send + Sync>;
+
+/// A full argument specification: a definition plus the metadata used for help and validation.
+///
+/// An `ArgSpec` can be built from any [`ArgDef`], so existing code that registers bare
+/// definitions keeps working, and the extra metadata can be layered on with the builder
+/// methods.
+///
+/// # Examples
+///
+/// ```
+/// # use argsparse::{ArgAction, ArgDef, ArgSpec};
+/// let spec = ArgSpec::new(ArgDef::ShortAndLong { short: 'o', long: "output" })
+///     .help("Where to write the result")
+///     .value_name("FILE")
+///     .action(ArgAction::Value)
+///     .default("out.txt");
+///
+/// assert_eq!(spec.value_name, Some("FILE"));
+/// assert!(!spec.required);
+/// ```
+#[derive(Clone)]
+pub struct ArgSpec<'a> {
+    /// The names this argument answers to.
+    pub def: ArgDef<'a>,
+    /// A short description shown in help output.
+    pub help: Option<&'a str>,
+    /// The placeholder used for the value in help output (e.g., `FILE`).
+    pub value_name: Option<&'a str>,
+    /// Whether the argument must be present on the command line.
+    pub required: bool,
+    /// The value assumed when the argument is absent.
+    pub default: Option<&'a str>,
+    /// The environment variable consulted when the argument is absent, before `default`.
+    pub env: Option<&'a str>,
+    /// How the argument treats the token that follows it.
+    pub action: ArgAction,
+    /// Whether the argument is left out of help output.
+    pub hidden: bool,
+    /// Whether the presence of the argument skips the checks on the rest of the command line.
+    pub exclusive: bool,
+    /// Whether the values of the argument are secrets, kept out of debug output, snapshots
+    /// and error messages.
+    pub sensitive: bool,
+    /// The question asked on the terminal when no source provides a value, if any.
+    #[cfg(feature = "prompt")]
+    pub prompt: Option<&'a str>,
+    /// The kind of value expected, used by shell completion.
+    pub value_hint: ValueHint,
+    /// The only values the argument accepts, or an empty slice to accept any value.
+    pub possible_values: &'a [&'a str],
+    /// A link to the full documentation of the argument, shown in help output.
+    pub doc_url: Option<&'a str>,
+    /// The bounds a value must be an integer within, if any.
+    pub range: Option<(Bound<i64>, Bound<i64>)>,
+    /// A check every value must pass, returning the reason a value is rejected.
+    pub validator: Option<Validator>,
+    /// The source of the values the argument currently accepts, if they are only known at
+    /// run time.
+    pub values_from: Option<ValueProvider>,
+    /// Whether the argument is deprecated, with a note on what to use instead.
+    pub deprecated: Option<&'a str>,
+    /// The version of the program that introduced the argument.
+    pub since: Option<&'a str>,
+    /// The version of the program from which the argument is deprecated.
+    pub deprecated_in: Option<&'a str>,
+    /// The version of the program from which the argument is rejected.
+    pub removed_in: Option<&'a str>,
+    /// The number of times the argument may appear on the command line, at least and at most.
+    pub occurrences: (usize, Option<usize>),
+    /// The value recorded when the argument is given as `--no-NAME`, if it can be negated.
+    pub negation: Option<&'a str>,
+    /// The definitions that override the argument, and that it overrides, when given later.
+    pub overrides_with: &'a [ArgDef<'a>],
+    /// The long names the argument had before being renamed, still accepted.
+    pub renamed_from: &'a [&'a str],
+    /// Alternate spellings of the long name, each with the locale it is accepted in.
+    pub localized: &'a [(&'a str, &'a str)],
+    /// Example invocations using the argument, each with its description.
+    pub examples: &'a [(&'a str, &'a str)],
+    /// The token that ends the capture of an argument with [`ArgAction::Capture`], if any.
+    pub capture_until: Option<&'a str>,
+    /// The order in which the sources of the value are consulted, overriding the one of the
+    /// context.
+    pub precedence: Option<&'a [SourceKind]>,
+}
+
+impl<'a> ArgSpec<'a> {
+    /// Parses a specification from a spec string: the names as accepted by
+    /// [`ArgDef::parse`], then optionally a value placeholder in angle brackets and a help
+    /// text in single quotes.
+    ///
+    /// A value placeholder sets the [`value_name`] and makes the argument take a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec};
+    /// let spec = ArgSpec::parse("-o, --output <FILE> 'Where to write the result'").unwrap();
+    ///
+    /// assert_eq!(spec.def, ArgDef::ShortAndLong { short: 'o', long: "output" });
+    /// assert_eq!(spec.value_name, Some("FILE"));
+    /// assert_eq!(spec.action, ArgAction::Value);
+    /// assert_eq!(spec.help, Some("Where to write the result"));
+    ///
+    /// assert!(ArgSpec::parse("-o <FILE> <DIR>").is_err());
+    /// ```
+    ///
+    /// [`ArgDef::parse`]: crate::ArgDef::parse
+    /// [`value_name`]: ArgSpec::value_name
+    pub fn parse(spec: &'a str) -> Result<ArgSpec<'a>, SpecError> {
+        let error = |reason| SpecError { spec: spec.to_string(), reason };
+
+        let (head, help) = match spec.trim_end().strip_suffix('\'') {
+            Some(rest) => match rest.split_once('\'') {
+                Some((head, help)) => (head, Some(help)),
+                None => return Err(error("the help text is missing its opening quote")),
+            },
+            None => (spec, None),
+        };
+
+        let mut words = head.split([',', ' ', '\t']).filter(|word| !word.is_empty()).peekable();
+        let names = std::iter::from_fn(|| words.next_if(|word| word.starts_with('-')));
+        let mut parsed = ArgSpec::new(ArgDef::from_names(spec, names)?);
+
+        if let Some(word) = words.next() {
+            let Some(name) = word.strip_prefix('<').and_then(|word| word.strip_suffix('>')) else {
+                return Err(error("expected a value placeholder such as <FILE>"));
+            };
+            parsed = parsed.value_name(name).action(ArgAction::Value);
+        }
+        if words.next().is_some() {
+            return Err(error("only the help text may follow the value placeholder"));
+        }
+
+        Ok(match help {
+            Some(help) => parsed.help(help),
+            None => parsed,
+        })
+    }
+
+    /// Creates a specification for `def` with no metadata attached.
+    pub const fn new(def: ArgDef<'a>) -> Self {
+        Self {
+            def,
+            help: None,
+            value_name: None,
+            required: false,
+            default: None,
+            env: None,
+            action: ArgAction::Infer,
+            hidden: false,
+            exclusive: false,
+            sensitive: false,
+            #[cfg(feature = "prompt")]
+            prompt: None,
+            value_hint: ValueHint::Unknown,
+            possible_values: &[],
+            doc_url: None,
+            range: None,
+            validator: None,
+            values_from: None,
+            deprecated: None,
+            since: None,
+            deprecated_in: None,
+            removed_in: None,
+            occurrences: (0, None),
+            negation: None,
+            overrides_with: &[],
+            renamed_from: &[],
+            localized: &[],
+            examples: &[],
+            capture_until: None,
+            precedence: None,
+        }
+    }
+
+    /// Sets the help text.
+    pub const fn help(mut self, help: &'a str) -> Self {
+        self.help = Some(help);
+        self
+    }
+
+    /// Sets the value placeholder used in help output.
+    pub const fn value_name(mut self, name: &'a str) -> Self {
+        self.value_name = Some(name);
+        self
+    }
+
+    /// Marks the argument as required or optional.
+    ///
+    /// A required argument is also satisfied by its environment variable, when set, or by
+    /// its default value.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use argsparse::{ArgDef, ArgSpec, Matches, ParserContext};
+    /// let token = ArgDef::Long("token");
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(token).required(true).env("MYAPP_DOC_TOKEN")).unwrap();
+    ///
+    /// assert!(Matches::parse(&[], &ctx).is_err());
+    ///
+    /// unsafe { std::env::set_var("MYAPP_DOC_TOKEN", "secret") };
+    /// let matches = Matches::parse(&[], &ctx).unwrap();
+    /// assert_eq!(matches.value_of(token).as_deref(), Some("secret"));
+    /// ```
+    pub const fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Sets the value assumed when the argument is absent.
+    pub const fn default(mut self, value: &'a str) -> Self {
+        self.default = Some(value);
+        self
+    }
+
+    /// Sets the environment variable consulted when the argument is absent.
+    ///
+    /// See [`Matches::value_of`] for how the value of an argument is resolved.
+    ///
+    /// [`Matches::value_of`]: crate::Matches::value_of
+    pub const fn env(mut self, name: &'a str) -> Self {
+        self.env = Some(name);
+        self
+    }
+
+    /// Sets how the argument treats the token that follows it.
+    pub const fn action(mut self, action: ArgAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Hides the argument from help output.
+    pub const fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Marks the argument as exclusive, for flags like `--help` and `--version`.
+    ///
+    /// When an exclusive argument is given, parsing with a context skips the checks on the
+    /// command line as a whole: missing [`required`] arguments and positionals, extra
+    /// positionals and [`occurrences`]. The checks on each argument still apply.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Matches, ParserContext};
+    /// let help = ArgDef::ShortAndLong { short: 'h', long: "help" };
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(help).action(ArgAction::Flag).exclusive(true)).unwrap();
+    /// ctx.register(ArgSpec::new(ArgDef::Long("input")).required(true)).unwrap();
+    ///
+    /// assert!(Matches::parse(&[], &ctx).is_err());
+    ///
+    /// let matches = Matches::parse(&["--help"], &ctx).unwrap();
+    /// assert!(matches.has(help));
+    /// ```
+    ///
+    /// [`required`]: ArgSpec::required
+    /// [`occurrences`]: ArgSpec::occurrences
+    pub const fn exclusive(mut self, exclusive: bool) -> Self {
+        self.exclusive = exclusive;
+        self
+    }
+
+    /// Marks the values of the argument as secrets, such as tokens and passwords.
+    ///
+    /// Once parsed with a context, including under the prefix of a nested context, the
+    /// values are replaced with `<redacted>` in the [`Debug`] output of [`Args`] and
+    /// [`Matches`], in their snapshots, in [`ParseIter::shown`], and in the errors raised for
+    /// them. The arguments themselves keep their values, so reading them is unaffected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Args, Matches, ParserContext};
+    /// let token = ArgDef::Long("token");
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(token).action(ArgAction::Value).sensitive(true)).unwrap();
+    ///
+    /// let matches = Matches::parse(&["--token", "hunter2"], &ctx).unwrap();
+    /// assert_eq!(matches.value_of(token).as_deref(), Some("hunter2"));
+    /// assert!(!format!("{:?}", matches).contains("hunter2"));
+    /// assert_eq!(matches.snapshot(), "option --token=\"<redacted>\" [cli]\n");
+    ///
+    /// // Under the prefix of a nested context
+    /// let mut host = ParserContext::new();
+    /// host.nest("db", ctx).unwrap();
+    /// let args = Args::parse_with_context(&["--db-token", "s3cret"], &host).unwrap();
+    /// assert!(!format!("{:?}", args).contains("s3cret"));
+    /// ```
+    ///
+    /// [`Debug`]: std::fmt::Debug
+    /// [`Args`]: crate::Args
+    /// [`Matches`]: crate::Matches
+    /// [`ParseIter::shown`]: crate::ParseIter::shown
+    pub const fn sensitive(mut self, sensitive: bool) -> Self {
+        self.sensitive = sensitive;
+        self
+    }
+
+    /// Asks for the value on the terminal with `prompt` when neither the command line nor
+    /// any other source provides one, so that secrets do not have to be typed where they
+    /// land in the shell history.
+    ///
+    /// The answer of a [`sensitive`] argument is typed without echo. Parsing with a context
+    /// asks before checking for required arguments, and only when there is a terminal to
+    /// ask on; the answer is then recorded as if given on the command line. Nothing is asked
+    /// when an [`exclusive`] argument such as `--help` is given.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Matches, ParserContext};
+    /// let password = ArgDef::Long("password");
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(password)
+    ///     .action(ArgAction::Value)
+    ///     .env("MYAPP_PASSWORD")
+    ///     .sensitive(true)
+    ///     .prompt("Password: "))
+    ///     .unwrap();
+    ///
+    /// // Asks for the password unless $MYAPP_PASSWORD is set
+    /// let matches = Matches::parse(&[], &ctx).unwrap();
+    /// let password = matches.value_of(password);
+    /// ```
+    ///
+    /// [`sensitive`]: ArgSpec::sensitive
+    /// [`exclusive`]: ArgSpec::exclusive
+    #[cfg(feature = "prompt")]
+    pub const fn prompt(mut self, prompt: &'a str) -> Self {
+        self.prompt = Some(prompt);
+        self
+    }
+
+    /// Sets the kind of value expected, used by shell completion.
+    pub const fn value_hint(mut self, hint: ValueHint) -> Self {
+        self.value_hint = hint;
+        self
+    }
+
+    /// Restricts the argument to the given values.
+    ///
+    /// Any other value is rejected when parsing with a context, and the allowed set is
+    /// listed in help output and offered by shell completion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ArgSpec, Matches, ParseArgError, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// let colors = ["auto", "always", "never"];
+    /// ctx.register(ArgSpec::new(ArgDef::Long("color")).possible_values(&colors)).unwrap();
+    ///
+    /// assert!(Matches::parse(&["--color", "never"], &ctx).is_ok());
+    ///
+    /// let err = Matches::parse(&["--color=sometimes"], &ctx).unwrap_err();
+    /// assert!(matches!(err, ParseArgError::InvalidValue { ref value, .. } if value == "sometimes"));
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "invalid value 'sometimes' for --color [possible values: auto, always, never]"
+    /// );
+    /// ```
+    pub const fn possible_values(mut self, values: &'a [&'a str]) -> Self {
+        self.possible_values = values;
+        self
+    }
+
+    /// Restricts the values of the argument to integers within `range`.
+    ///
+    /// Values are checked while parsing; a value that is not an integer or lies outside the
+    /// range fails with [`ParseArgError::RejectedValue`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ArgSpec, Matches, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(ArgDef::Long("port")).range(1..=65535)).unwrap();
+    ///
+    /// assert!(Matches::parse(&["--port", "8080"], &ctx).is_ok());
+    ///
+    /// let err = Matches::parse(&["--port", "0"], &ctx).unwrap_err();
+    /// assert_eq!(err.to_string(), "invalid value '0' for --port: expected a value in 1..=65535");
+    /// ```
+    ///
+    /// [`ParseArgError::RejectedValue`]: crate::ParseArgError::RejectedValue
+    pub fn range(mut self, range: impl RangeBounds<i64>) -> Self {
+        self.range = Some((range.start_bound().cloned(), range.end_bound().cloned()));
+        self
+    }
+
+    /// Restricts the number of times the argument may appear on the command line to `range`.
+    ///
+    /// Occurrences are counted while parsing, whatever name they are given under; a count
+    /// outside the range fails with [`ParseArgError::OccurrenceCount`]. As with [`required`],
+    /// an argument that is absent but has a value from its environment variable, the
+    /// configuration or its default value is not checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Matches, ParserContext};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(ArgDef::ShortAndLong { short: 'v', long: "verbose" })
+    ///     .action(ArgAction::Flag)
+    ///     .occurrences(..=2))
+    ///     .unwrap();
+    /// ctx.register(ArgSpec::new(ArgDef::Long("input")).occurrences(1..)).unwrap();
+    ///
+    /// assert!(Matches::parse(&["-vv", "--input", "a.txt"], &ctx).is_ok());
+    ///
+    /// let err = Matches::parse(&["-vvv", "--input", "a.txt"], &ctx).unwrap_err();
+    /// assert_eq!(err.to_string(), "--verbose may be given at most 2 times, but was given 3");
+    ///
+    /// let err = Matches::parse(&[], &ctx).unwrap_err();
+    /// assert_eq!(err.to_string(), "--input must be given at least 1 time, but was given 0");
+    /// ```
+    ///
+    /// [`ParseArgError::OccurrenceCount`]: crate::ParseArgError::OccurrenceCount
+    /// [`required`]: ArgSpec::required
+    pub fn occurrences(mut self, range: impl RangeBounds<usize>) -> Self {
+        let min = match range.start_bound() {
+            Bound::Included(&min) => min,
+            Bound::Excluded(&min) => min + 1,
+            Bound::Unbounded => 0,
+        };
+        let max = match range.end_bound() {
+            Bound::Included(&max) => Some(max),
+            Bound::Excluded(&max) => Some(max.saturating_sub(1)),
+            Bound::Unbounded => None,
+        };
+        self.occurrences = (min, max);
+        self
+    }
+
+    /// Accepts `--no-NAME` for the argument, recording it as `--NAME=value`.
+    ///
+    /// The argument needs a long name. A definition actually named `no-NAME` takes
+    /// precedence over the negation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Args, OptionArg, ParserContext};
+    /// let pager = ArgDef::Long("pager");
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(pager).action(ArgAction::Value).negation("cat")).unwrap();
+    ///
+    /// let args = Args::parse_with_context(&["--no-pager"], &ctx).unwrap();
+    /// assert_eq!(args.find::<OptionArg>(pager).unwrap().value, "cat");
+    /// ```
+    pub const fn negation(mut self, value: &'a str) -> Self {
+        self.negation = Some(value);
+        self
+    }
+
+    /// Makes the argument and each of `defs` override each other: when several of them are
+    /// given, only the last one on the command line is considered present.
+    ///
+    /// The relationship works both ways, so it only needs to be declared on one side. The
+    /// raw arguments are kept; [`Matches`] drops the overridden occurrences once parsing is
+    /// done.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Matches, ParserContext};
+    /// const VERBOSE: ArgDef = ArgDef::ShortAndLong { short: 'v', long: "verbose" };
+    /// const QUIET: ArgDef = ArgDef::ShortAndLong { short: 'q', long: "quiet" };
+    ///
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(VERBOSE).action(ArgAction::Flag).overrides_with(&[QUIET]))
+    ///     .unwrap();
+    /// ctx.register(ArgSpec::new(QUIET).action(ArgAction::Flag)).unwrap();
+    ///
+    /// let matches = Matches::parse(&["-v", "-q"], &ctx).unwrap();
+    /// assert!(matches.has(QUIET) && !matches.has(VERBOSE));
+    ///
+    /// let matches = Matches::parse(&["-q", "-vv"], &ctx).unwrap();
+    /// assert_eq!((matches.count(QUIET), matches.count(VERBOSE)), (0, 2));
+    /// ```
+    ///
+    /// [`Matches`]: crate::Matches
+    pub const fn overrides_with(mut self, defs: &'a [ArgDef<'a>]) -> Self {
+        self.overrides_with = defs;
+        self
+    }
+
+    /// Sets the token that ends the capture of an argument with [`ArgAction::Capture`],
+    /// such as `;`.
+    ///
+    /// The terminator itself is consumed without being recorded. Without one, the capture
+    /// runs to the end of the command line.
+    pub const fn capture_until(mut self, terminator: &'a str) -> Self {
+        self.capture_until = Some(terminator);
+        self
+    }
+
+    /// Sets a check every value of the argument must pass.
+    ///
+    /// The validator returns the reason a value is rejected. Values are checked while
+    /// parsing; a rejected value fails with [`ParseArgError::RejectedValue`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ArgSpec, Matches, ParserContext};
+    /// fn not_empty(value: &str) -> Result<(), String> {
+    ///     if value.is_empty() { Err("must not be empty".into()) } else { Ok(()) }
+    /// }
+    ///
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(ArgDef::Long("name")).validator(not_empty)).unwrap();
+    ///
+    /// let err = Matches::parse(&["--name="], &ctx).unwrap_err();
+    /// assert_eq!(err.to_string(), "invalid value '' for --name: must not be empty");
+    /// ```
+    ///
+    /// [`ParseArgError::RejectedValue`]: crate::ParseArgError::RejectedValue
+    pub const fn validator(mut self, validator: Validator) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// Sets a provider for the values the argument currently accepts, such as the profiles
+    /// found on disk.
+    ///
+    /// The provider is called while parsing, to reject any other value with
+    /// [`ParseArgError::InvalidValue`], and by completion scripts, to offer its values; see
+    /// [`ParserContext::complete_values_from_env`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, ArgSpec, Matches, ParserContext};
+    /// fn profiles() -> Vec<String> {
+    ///     // e.g., the file stems in ~/.config/myapp/profiles
+    ///     vec!["dev".into(), "prod".into()]
+    /// }
+    ///
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(ArgDef::Long("profile")).values_from(profiles)).unwrap();
+    ///
+    /// assert!(Matches::parse(&["--profile", "dev"], &ctx).is_ok());
+    ///
+    /// let err = Matches::parse(&["--profile", "test"], &ctx).unwrap_err();
+    /// assert_eq!(err.to_string(), "invalid value 'test' for --profile [possible values: dev, prod]");
+    ///
+    /// // A closure can capture what it lists
+    /// let regions = vec!["eu".to_string(), "us".to_string()];
+    /// ctx.register(ArgSpec::new(ArgDef::Long("region")).values_from(move || regions.clone()))
+    ///     .unwrap();
+    /// assert!(Matches::parse(&["--region", "eu"], &ctx).is_ok());
+    /// ```
+    ///
+    /// [`ParseArgError::InvalidValue`]: crate::ParseArgError::InvalidValue
+    /// [`ParserContext::complete_values_from_env`]: crate::ParserContext::complete_values_from_env
+    pub fn values_from<F>(mut self, provider: F) -> Self
+    where
+        F: Fn() -> Vec<String> + Send + Sync + 'static,
+    {
+        self.values_from = Some(Arc::new(provider));
+        self
+    }
+
+    /// Checks `value` against the range and the validator, returning the reason it is
+    /// rejected.
+    pub(crate) fn validate(&self, value: &str) -> Result<(), String> {
+        if let Some((start, end)) = self.range {
+            let inside = value.parse::<i64>().is_ok_and(|n| (start, end).contains(&n));
+            if !inside {
+                return Err(format!("expected a value in {}", format_range(start, end)));
+            }
+        }
+        match self.validator {
+            Some(validator) => validator(value),
+            None => Ok(()),
+        }
+    }
+
+    /// Marks the argument as deprecated, with a note on what to use instead.
+    ///
+    /// The argument keeps working; an [`Observer`] is notified each time it is given.
+    ///
+    /// [`Observer`]: crate::Observer
+    pub const fn deprecated(mut self, note: &'a str) -> Self {
+        self.deprecated = Some(note);
+        self
+    }
+
+    /// Keeps accepting the long names the argument had before being renamed, given without
+    /// their dashes.
+    ///
+    /// Parsing with a context rewrites an old name to the current one, with a deprecation
+    /// warning, so the program only ever sees the current name; the lazy
+    /// [`Args::parse_iter_with_context`] yields old names as given. Old names are left out of
+    /// help output and completion, and conflict with other definitions like any name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Args, Matches, ParserContext};
+    /// let output = ArgDef::Long("output");
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(output).action(ArgAction::Value).renamed_from(&["out-file"]))
+    ///     .unwrap();
+    ///
+    /// let matches = Matches::parse(&["--out-file", "a.txt"], &ctx).unwrap();
+    /// assert_eq!(matches.value_of(output).as_deref(), Some("a.txt"));
+    /// assert_eq!(matches.warnings(), ["--out-file is deprecated: it was renamed to --output"]);
+    ///
+    /// let args = Args::parse_with_context(&["--out-file=b.txt"], &ctx).unwrap();
+    /// assert!(args.has(output));
+    /// ```
+    ///
+    /// [`Args::parse_iter_with_context`]: crate::Args::parse_iter_with_context
+    pub const fn renamed_from(mut self, names: &'a [&'a str]) -> Self {
+        self.renamed_from = names;
+        self
+    }
+
+    /// Sets alternate spellings of the long name for other languages, as pairs of a locale
+    /// and a long name given without its dashes (e.g., `("es", "ayuda")` for `--help`).
+    ///
+    /// The spelling of the locale set with [`ParserContext::locale`] is accepted along with
+    /// the long name, recorded under the long name, and shown in help output in its place.
+    /// A locale such as `es` also applies to regional variants like `es_MX.UTF-8`; the most
+    /// specific one wins. Spellings conflict with other definitions like any name, whatever
+    /// their locale.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Matches, ParserContext};
+    /// let help = ArgDef::ShortAndLong { short: 'h', long: "help" };
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(help)
+    ///     .action(ArgAction::Flag)
+    ///     .localized(&[("es", "ayuda"), ("fr", "aide")]))
+    ///     .unwrap();
+    /// ctx.locale("es_ES.UTF-8");
+    ///
+    /// let matches = Matches::parse(&["--ayuda"], &ctx).unwrap();
+    /// assert!(matches.has(help));
+    /// assert!(Matches::parse(&["--aide"], &ctx).is_err());
+    /// assert!(ctx.render_help("app").contains("-h, --ayuda"));
+    /// ```
+    ///
+    /// [`ParserContext::locale`]: crate::ParserContext::locale
+    pub const fn localized(mut self, names: &'a [(&'a str, &'a str)]) -> Self {
+        self.localized = names;
+        self
+    }
+
+    /// Sets example invocations using the argument, as pairs of a command line and its
+    /// description, listed in the examples section of help output after those of the
+    /// context.
+    ///
+    /// See [`ParserContext::example`] for an example.
+    ///
+    /// [`ParserContext::example`]: crate::ParserContext::example
+    pub const fn examples(mut self, examples: &'a [(&'a str, &'a str)]) -> Self {
+        self.examples = examples;
+        self
+    }
+
+    /// Sets the version of the program that introduced the argument, shown in help output.
+    pub const fn since(mut self, version: &'a str) -> Self {
+        self.since = Some(version);
+        self
+    }
+
+    /// Deprecates the argument from the given version of the program on.
+    ///
+    /// Once the version set with [`ParserContext::version`] reaches `version`, using the
+    /// argument adds a warning to [`Matches::warnings`] and notifies the [`Observer`], and
+    /// help output marks it as deprecated. Without a program version, the argument is
+    /// deprecated right away.
+    ///
+    /// [`ParserContext::version`]: crate::ParserContext::version
+    /// [`Matches::warnings`]: crate::Matches::warnings
+    /// [`Observer`]: crate::Observer
+    pub const fn deprecated_in(mut self, version: &'a str) -> Self {
+        self.deprecated_in = Some(version);
+        self
+    }
+
+    /// Removes the argument from the given version of the program on.
+    ///
+    /// Once the version set with [`ParserContext::version`] reaches `version`, the argument
+    /// is left out of help output and using it fails with [`ParseArgError::RemovedArg`].
+    /// Before that, help output announces the removal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, Matches, ParserContext};
+    /// let legacy = ArgSpec::new(ArgDef::Long("legacy"))
+    ///     .action(ArgAction::Flag)
+    ///     .help("Use the old format")
+    ///     .since("1.0")
+    ///     .deprecated_in("2.0")
+    ///     .removed_in("3.0");
+    ///
+    /// let mut ctx = ParserContext::new();
+    /// ctx.version("2.4.1").register(legacy.clone()).unwrap();
+    ///
+    /// let matches = Matches::parse(&["--legacy"], &ctx).unwrap();
+    /// assert_eq!(
+    ///     matches.warnings(),
+    ///     ["--legacy is deprecated since 2.0 and will be removed in 3.0"]
+    /// );
+    /// assert!(ctx.render_help("app").contains(
+    ///     "Use the old format [since 1.0] [deprecated since 2.0, removed in 3.0]"
+    /// ));
+    ///
+    /// let mut ctx = ParserContext::new();
+    /// ctx.version("3.0.0").register(legacy).unwrap();
+    ///
+    /// let err = Matches::parse(&["--legacy"], &ctx).unwrap_err();
+    /// assert_eq!(err.to_string(), "--legacy was removed in version 3.0");
+    /// assert!(!ctx.render_help("app").contains("--legacy"));
+    /// ```
+    ///
+    /// [`ParserContext::version`]: crate::ParserContext::version
+    /// [`ParseArgError::RemovedArg`]: crate::ParseArgError::RemovedArg
+    pub const fn removed_in(mut self, version: &'a str) -> Self {
+        self.removed_in = Some(version);
+        self
+    }
+
+    /// Sets the order in which the sources of the value are consulted for this argument,
+    /// overriding the [`precedence`] of the context.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use argsparse::{ArgDef, ArgSpec, Matches, ParserContext, SourceKind};
+    /// // In this deployment, the environment has the final say on the log level
+    /// let level = ArgDef::Long("log-level");
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(level)
+    ///     .env("MYAPP_DOC_LOG_LEVEL")
+    ///     .precedence(&[SourceKind::Env, SourceKind::CommandLine, SourceKind::Default]))
+    ///     .unwrap();
+    ///
+    /// let matches = Matches::parse(&["--log-level", "debug"], &ctx).unwrap();
+    /// assert_eq!(matches.value_of(level).as_deref(), Some("debug"));
+    ///
+    /// unsafe { std::env::set_var("MYAPP_DOC_LOG_LEVEL", "warn") };
+    /// assert_eq!(matches.value_of(level).as_deref(), Some("warn"));
+    /// ```
+    ///
+    /// [`precedence`]: crate::ParserContext::precedence
+    pub const fn precedence(mut self, order: &'a [SourceKind]) -> Self {
+        self.precedence = Some(order);
+        self
+    }
+
+    /// Returns `true` if the argument is deprecated in the program version `current`.
+    pub(crate) fn is_deprecated(&self, current: Option<&str>) -> bool {
+        self.deprecated.is_some()
+            || self.deprecated_in.is_some_and(|version| {
+                current.is_none_or(|current| version_reached(current, version))
+            })
+    }
+
+    /// Returns `true` if the argument is removed in the program version `current`.
+    pub(crate) fn is_removed(&self, current: Option<&str>) -> bool {
+        match (self.removed_in, current) {
+            (Some(version), Some(current)) => version_reached(current, version),
+            _ => false,
+        }
+    }
+
+    /// Sets a link to the full documentation of the argument.
+    ///
+    /// Help output turns the names of the argument into a hyperlink on terminals, and
+    /// spells the URL out otherwise.
+    pub const fn doc_url(mut self, url: &'a str) -> Self {
+        self.doc_url = Some(url);
+        self
+    }
+
+    /// Returns `true` if the argument is a plain flag, whose only explicit values are `true`
+    /// and `false`.
+    pub(crate) fn is_boolean(&self) -> bool {
+        self.action == ArgAction::Flag
+            && self.possible_values.is_empty()
+            && self.values_from.is_none()
+    }
+
+    /// Returns `true` if the argument takes a value, as far as its metadata tells.
+    pub(crate) fn takes_value(&self) -> bool {
+        match self.action {
+            ArgAction::Flag => false,
+            ArgAction::Value | ArgAction::Capture => true,
+            ArgAction::Infer => {
+                self.value_name.is_some()
+                    || !self.possible_values.is_empty()
+                    || self.values_from.is_some()
+            }
+        }
+    }
+}
+
+/// An [`ArgSpec`] that owns its strings, for definitions only known at run time, such as
+/// the options of a plugin read from its manifest, without leaking them.
+///
+/// A context borrows its definitions, so keep owned specs alive as long as the context and
+/// register the views returned by [`spec`]. Only the most common metadata is covered; set
+/// the rest on the returned [`ArgSpec`].
+///
+/// # Examples
+///
+/// ```
+/// # use argsparse::{ArgAction, Matches, OwnedArgDef, OwnedArgSpec, ParserContext};
+/// // Read from a plugin manifest at run time
+/// let manifest = vec![("lint".to_string(), "Run the linter".to_string())];
+///
+/// let specs: Vec<_> = manifest
+///     .into_iter()
+///     .map(|(name, help)| {
+///         OwnedArgSpec::new(OwnedArgDef::Long(name)).help(help).action(ArgAction::Flag)
+///     })
+///     .collect();
+///
+/// let mut ctx = ParserContext::new();
+/// for spec in &specs {
+///     ctx.register(spec.spec()).unwrap();
+/// }
+///
+/// let matches = Matches::parse(&["--lint"], &ctx).unwrap();
+/// assert!(matches.has(specs[0].def.as_def()));
+/// assert!(ctx.render_help("app").contains("Run the linter"));
+/// ```
+///
+/// [`spec`]: OwnedArgSpec::spec
+#[derive(Debug, PartialEq, Clone)]
+pub struct OwnedArgSpec {
+    /// The names of the argument.
+    pub def: OwnedArgDef,
+    /// The help text shown next to the argument.
+    pub help: Option<String>,
+    /// The placeholder used for the value in help output (e.g., `FILE`).
+    pub value_name: Option<String>,
+    /// Whether the argument must be present on the command line.
+    pub required: bool,
+    /// The value assumed when the argument is absent.
+    pub default: Option<String>,
+    /// The environment variable consulted when the argument is absent, before `default`.
+    pub env: Option<String>,
+    /// How the argument treats the token that follows it.
+    pub action: ArgAction,
+    /// Whether the argument is left out of help output.
+    pub hidden: bool,
+}
+
+impl OwnedArgSpec {
+    /// Creates a specification for `def` with no metadata attached.
+    pub fn new(def: OwnedArgDef) -> Self {
+        Self {
+            def,
+            help: None,
+            value_name: None,
+            required: false,
+            default: None,
+            env: None,
+            action: ArgAction::Infer,
+            hidden: false,
+        }
+    }
+
+    /// Sets the help text shown next to the argument.
+    pub fn help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    /// Sets the placeholder used for the value in help output.
+    pub fn value_name(mut self, name: impl Into<String>) -> Self {
+        self.value_name = Some(name.into());
+        self
+    }
+
+    /// Marks the argument as required or optional.
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Sets the value assumed when the argument is absent.
+    pub fn default(mut self, value: impl Into<String>) -> Self {
+        self.default = Some(value.into());
+        self
+    }
+
+    /// Sets the environment variable consulted when the argument is absent.
+    pub fn env(mut self, name: impl Into<String>) -> Self {
+        self.env = Some(name.into());
+        self
+    }
+
+    /// Sets how the argument treats the token that follows it.
+    pub fn action(mut self, action: ArgAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Hides the argument from help output.
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Returns the specification borrowing the strings owned here, to register with a
+    /// context.
+    pub fn spec(&self) -> ArgSpec<'_> {
+        ArgSpec {
+            help: self.help.as_deref(),
+            value_name: self.value_name.as_deref(),
+            required: self.required,
+            default: self.default.as_deref(),
+            env: self.env.as_deref(),
+            action: self.action,
+            hidden: self.hidden,
+            ..ArgSpec::new(self.def.as_def())
+        }
+    }
+}
+
+/// Returns `true` if the version `current` is at least `target`.
+///
+/// Versions are compared by their dot-separated numeric components, missing ones counting
+/// as zero; pre-release and build suffixes (after `-` or `+`) are ignored.
+fn version_reached(current: &str, target: &str) -> bool {
+    let components = |version: &str| -> Vec<u64> {
+        let core = version.split(['-', '+']).next().unwrap_or_default();
+        core.split('.').map(|part| part.trim().parse().unwrap_or(0)).collect()
+    };
+    let (mut current, mut target) = (components(current), components(target));
+    let len = current.len().max(target.len());
+    current.resize(len, 0);
+    target.resize(len, 0);
+    current >= target
+}
+
+/// Formats bounds the way they are written in Rust, e.g. `1..=65535`.
+fn format_range(start: Bound<i64>, end: Bound<i64>) -> String {
+    let start = match start {
+        Bound::Included(n) => n.to_string(),
+        Bound::Excluded(n) => (n + 1).to_string(),
+        Bound::Unbounded => String::new(),
+    };
+    match end {
+        Bound::Included(n) => format!("{}..={}", start, n),
+        Bound::Excluded(n) => format!("{}..{}", start, n),
+        Bound::Unbounded => format!("{}..", start),
+    }
+}
+
+/// Compares every field but [`values_from`], as providers cannot be compared. Validators are
+/// compared by address.
+///
+/// [`values_from`]: ArgSpec::values_from
+impl PartialEq for ArgSpec<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        let validator = match (self.validator, other.validator) {
+            (Some(a), Some(b)) => ptr::fn_addr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        #[cfg(feature = "prompt")]
+        if self.prompt != other.prompt {
+            return false;
+        }
+
+        validator
+            && self.def == other.def
+            && self.help == other.help
+            && self.value_name == other.value_name
+            && self.required == other.required
+            && self.default == other.default
+            && self.env == other.env
+            && self.action == other.action
+            && self.hidden == other.hidden
+            && self.exclusive == other.exclusive
+            && self.sensitive == other.sensitive
+            && self.value_hint == other.value_hint
+            && self.possible_values == other.possible_values
+            && self.doc_url == other.doc_url
+            && self.range == other.range
+            && self.deprecated == other.deprecated
+            && self.since == other.since
+            && self.deprecated_in == other.deprecated_in
+            && self.removed_in == other.removed_in
+            && self.occurrences == other.occurrences
+            && self.negation == other.negation
+            && self.overrides_with == other.overrides_with
+            && self.renamed_from == other.renamed_from
+            && self.localized == other.localized
+            && self.examples == other.examples
+            && self.capture_until == other.capture_until
+            && self.precedence == other.precedence
+    }
+}
+
+/// Shows every field but [`values_from`], as providers cannot be shown.
+///
+/// [`values_from`]: ArgSpec::values_from
+impl fmt::Debug for ArgSpec<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("ArgSpec");
+        debug.field("def", &self.def);
+        debug.field("help", &self.help);
+        debug.field("value_name", &self.value_name);
+        debug.field("required", &self.required);
+        debug.field("default", &self.default);
+        debug.field("env", &self.env);
+        debug.field("action", &self.action);
+        debug.field("hidden", &self.hidden);
+        debug.field("exclusive", &self.exclusive);
+        debug.field("sensitive", &self.sensitive);
+        #[cfg(feature = "prompt")]
+        debug.field("prompt", &self.prompt);
+        debug.field("value_hint", &self.value_hint);
+        debug.field("possible_values", &self.possible_values);
+        debug.field("doc_url", &self.doc_url);
+        debug.field("range", &self.range);
+        debug.field("validator", &self.validator);
+        debug.field("deprecated", &self.deprecated);
+        debug.field("since", &self.since);
+        debug.field("deprecated_in", &self.deprecated_in);
+        debug.field("removed_in", &self.removed_in);
+        debug.field("occurrences", &self.occurrences);
+        debug.field("negation", &self.negation);
+        debug.field("overrides_with", &self.overrides_with);
+        debug.field("renamed_from", &self.renamed_from);
+        debug.field("localized", &self.localized);
+        debug.field("examples", &self.examples);
+        debug.field("capture_until", &self.capture_until);
+        debug.field("precedence", &self.precedence);
+        debug.finish_non_exhaustive()
+    }
+}
+
+impl<'a> From<ArgDef<'a>> for ArgSpec<'a> {
+    fn from(def: ArgDef<'a>) -> Self {
+        ArgSpec::new(def)
+    }
+}
+
+/// A named positional argument (an operand), such as `SRC` in `cp SRC DEST`.
+///
+/// Once a context defines any positional, the number of positionals on the command line is
+/// checked against the definitions: missing required ones and unexpected extra ones are
+/// reported by name. Positionals are matched in the order they were defined.
+///
+/// # Examples
+///
+/// ```
+/// # use argsparse::{Args, ParseArgError, ParserContext, PositionalSpec};
+/// let mut ctx = ParserContext::new();
+/// ctx.positional(PositionalSpec::new("SRC").required(true))
+///     .positional(PositionalSpec::new("DEST"));
+///
+/// assert!(Args::parse_with_context(&["a"], &ctx).is_ok());
+/// assert!(Args::parse_with_context(&["a", "b"], &ctx).is_ok());
+///
+/// let err = Args::parse_with_context(&[], &ctx).unwrap_err();
+/// assert!(matches!(err, ParseArgError::MissingPositional(name) if name == "SRC"));
+///
+/// let err = Args::parse_with_context(&["a", "b", "c"], &ctx).unwrap_err();
+/// assert!(matches!(err, ParseArgError::UnexpectedPositional(value) if value == "c"));
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct PositionalSpec<'a> {
+    /// The name shown in help output and errors (e.g., `SRC`).
+    pub name: &'a str,
+    /// A short description shown in help output.
+    pub help: Option<&'a str>,
+    /// Whether the positional must be present on the command line.
+    pub required: bool,
+    /// Whether the positional captures all remaining operands (e.g., `FILES...`).
+    pub variadic: bool,
+    /// The kind of value expected, used by shell completion.
+    pub value_hint: ValueHint,
+}
+
+impl<'a> PositionalSpec<'a> {
+    /// Creates an optional positional named `name`.
+    pub const fn new(name: &'a str) -> Self {
+        Self {
+            name,
+            help: None,
+            required: false,
+            variadic: false,
+            value_hint: ValueHint::Unknown,
+        }
+    }
+
+    /// Sets the help text.
+    pub const fn help(mut self, help: &'a str) -> Self {
+        self.help = Some(help);
+        self
+    }
+
+    /// Marks the positional as required or optional.
+    pub const fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Makes the positional capture all remaining operands.
+    ///
+    /// Only the last positional of a context may be variadic. A required variadic
+    /// positional needs at least one operand.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Matches, ParserContext, PositionalSpec};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.positional(PositionalSpec::new("DEST").required(true))
+    ///     .positional(PositionalSpec::new("FILES").required(true).variadic(true));
+    ///
+    /// let matches = Matches::parse(&["out/", "a.txt", "b.txt", "c.txt"], &ctx).unwrap();
+    /// assert_eq!(matches.positional_values("FILES"), ["a.txt", "b.txt", "c.txt"]);
+    ///
+    /// assert!(Matches::parse(&["out/"], &ctx).is_err());
+    /// ```
+    pub const fn variadic(mut self, variadic: bool) -> Self {
+        self.variadic = variadic;
+        self
+    }
+
+    /// Sets the kind of value expected, used by shell completion.
+    pub const fn value_hint(mut self, hint: ValueHint) -> Self {
+        self.value_hint = hint;
+        self
+    }
+}
+
+/// Renders the positional as it appears in a usage line: `<SRC>` when required, `[DEST]`
+/// when optional, followed by `...` when variadic.
+///
+/// # Examples
+///
+/// ```
+/// # use argsparse::PositionalSpec;
+/// assert_eq!(PositionalSpec::new("SRC").required(true).to_string(), "<SRC>");
+/// assert_eq!(PositionalSpec::new("FILES").variadic(true).to_string(), "[FILES]...");
+/// ```
+impl fmt::Display for PositionalSpec<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.required {
+            write!(f, "<{}>", self.name)?;
+        } else {
+            write!(f, "[{}]", self.name)?;
+        }
+        if self.variadic {
+            write!(f, "...")?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> From<&'a str> for PositionalSpec<'a> {
+    fn from(name: &'a str) -> Self {
+        PositionalSpec::new(name)
+    }
+}
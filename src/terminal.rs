@@ -0,0 +1,69 @@
+//! Detection of what the terminal of the program can show, following the conventions of
+//! `NO_COLOR`, `CLICOLOR`, `CLICOLOR_FORCE` and `TERM=dumb`.
+//!
+//! The help and the error reports printed by [`ParserContext`] decide on colors and
+//! hyperlinks with these functions, so a program can use them to keep its own output
+//! consistent with the parser's.
+//!
+//! # Examples
+//!
+//! ```
+//! use argsparse::terminal::{self, Stream};
+//!
+//! let bold = |text: &str| match terminal::use_color(Stream::Stdout) {
+//!     true => format!("\x1b[1m{}\x1b[0m", text),
+//!     false => text.to_string(),
+//! };
+//! println!("{}", bold("Done"));
+//! ```
+//!
+//! [`ParserContext`]: crate::ParserContext
+
+use std::env;
+use std::io::{self, IsTerminal};
+
+/// A standard output stream of the program.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Stream {
+    /// The standard output.
+    Stdout,
+    /// The standard error.
+    Stderr,
+}
+
+/// Returns `true` if `stream` is a terminal.
+pub fn is_terminal(stream: Stream) -> bool {
+    match stream {
+        Stream::Stdout => io::stdout().is_terminal(),
+        Stream::Stderr => io::stderr().is_terminal(),
+    }
+}
+
+/// Returns `true` if escape sequences, such as colors and hyperlinks, may be written to
+/// `stream`: it is a terminal other than a dumb one, or `CLICOLOR_FORCE` is set to anything
+/// but `0`.
+pub fn supports_escapes(stream: Stream) -> bool {
+    if forced() {
+        return true;
+    }
+    is_terminal(stream) && env::var_os("TERM").is_none_or(|term| term != "dumb")
+}
+
+/// Returns `true` if output to `stream` should be colored.
+///
+/// `CLICOLOR_FORCE` set to anything but `0` turns colors on whatever the stream. Otherwise,
+/// a non-empty `NO_COLOR` or `CLICOLOR=0` turns them off, and they are used when
+/// [`supports_escapes`] holds.
+pub fn use_color(stream: Stream) -> bool {
+    if forced() {
+        return true;
+    }
+    let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let disabled = env::var_os("CLICOLOR").is_some_and(|value| value == "0");
+    !no_color && !disabled && supports_escapes(stream)
+}
+
+/// Returns `true` if `CLICOLOR_FORCE` asks for colors whatever the stream.
+fn forced() -> bool {
+    env::var_os("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0")
+}
@@ -1,4 +1,6 @@
 use crate::defs::*;
+use std::fmt;
+use std::str::FromStr;
 
 /// A positional argument, typically representing a value not preceded by a flag or option.
 #[derive(Debug, PartialEq, Copy, Clone)]
@@ -20,16 +22,210 @@ pub struct OptionArg<'a> {
     pub value: &'a str,
 }
 
+impl<'a> PositionalArg<'a> {
+    /// Returns the raw string value of the positional argument.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, PositionalArg};
+    /// let args = Args::parse_all(&["a.txt", "-v", "--", "b.txt"]).unwrap();
+    /// let files: Vec<_> = args.iter_all::<PositionalArg>().map(|arg| arg.value()).collect();
+    ///
+    /// assert_eq!(files, ["a.txt", "b.txt"]);
+    /// ```
+    pub fn value(&self) -> &'a str {
+        self.value
+    }
+
+    /// Converts the value into type `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{Args, PositionalArg};
+    /// let args = Args::parse_all(&["80", "http"]).unwrap();
+    /// let ports = args.find_all::<PositionalArg>();
+    ///
+    /// assert_eq!(ports[0].value_as::<u16>().unwrap(), 80);
+    /// assert_eq!(ports[1].value_as::<u16>().unwrap_err().value, "http");
+    /// ```
+    pub fn value_as<T>(&self) -> Result<T, ValueError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        ValueError::parse("positional", self.value)
+    }
+}
+
+impl<'a> FlagArg<'a> {
+    /// Returns the name the flag was given under.
+    pub fn name(&self) -> &'a ArgName<'a> {
+        self.name
+    }
+
+    /// Returns `true` if the flag was given under a name of `def`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Args, FlagArg};
+    /// let args = Args::parse_all(&["-v", "--quiet"]).unwrap();
+    /// let verbose = ArgDef::ShortAndLong { short: 'v', long: "verbose" };
+    ///
+    /// let flags = args.find_all::<FlagArg>();
+    /// assert!(flags[0].is(verbose));
+    /// assert!(!flags[1].is(verbose));
+    /// ```
+    pub fn is(&self, def: ArgDef) -> bool {
+        def.matches(self.name)
+    }
+}
+
+impl OptionArg<'_> {
+    /// Returns `true` if the option was given under a name of `def`.
+    pub fn is(&self, def: ArgDef) -> bool {
+        def.matches(self.name)
+    }
+
+    /// Converts the value into type `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgDef, Args, OptionArg};
+    /// let args = Args::parse_all(&["--jobs", "4", "-t", "soon"]).unwrap();
+    ///
+    /// let jobs: OptionArg = args.find(ArgDef::Long("jobs")).unwrap();
+    /// assert_eq!(jobs.value_as::<u8>().unwrap(), 4);
+    ///
+    /// let timeout: OptionArg = args.find(ArgDef::Short('t')).unwrap();
+    /// let err = timeout.value_as::<u64>().unwrap_err();
+    /// assert_eq!((err.arg.as_str(), err.value.as_str(), err.expected), ("-t", "soon", "u64"));
+    /// ```
+    pub fn value_as<T>(&self) -> Result<T, ValueError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        ValueError::parse(display_name(self.name), self.value)
+    }
+}
+
+/// Returns `name` as typed on the command line (e.g., `--port`).
+pub(crate) fn display_name(name: &ArgName) -> String {
+    match name {
+        ArgName::Short(s) => format!("-{}", s),
+        ArgName::Long(l) => format!("--{}", l),
+    }
+}
+
+/// The state of a tri-state flag such as `--color`, registered with
+/// [`ParserContext::tri_state`] and read with [`Args::tri_state`].
+///
+/// Parses from and displays as `always`, `never` or `auto`.
+///
+/// [`ParserContext::tri_state`]: crate::ParserContext::tri_state
+/// [`Args::tri_state`]: crate::Args::tri_state
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum TriState {
+    /// Decided by the program, e.g. from whether the output is a terminal.
+    #[default]
+    Auto,
+    /// Turned on, by `--color` or `--color=always`.
+    Always,
+    /// Turned off, by `--no-color` or `--color=never`.
+    Never,
+}
+
+impl TriState {
+    /// The values a tri-state flag accepts.
+    pub(crate) const VALUES: &'static [&'static str] = &["always", "never", "auto"];
+}
+
+impl FromStr for TriState {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "auto" => Ok(TriState::Auto),
+            "always" => Ok(TriState::Always),
+            "never" => Ok(TriState::Never),
+            _ => Err(format!("expected one of {}", TriState::VALUES.join(", "))),
+        }
+    }
+}
+
+impl fmt::Display for TriState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TriState::Auto => write!(f, "auto"),
+            TriState::Always => write!(f, "always"),
+            TriState::Never => write!(f, "never"),
+        }
+    }
+}
+
 pub trait FromArgument<'a>: Sized {
     /// Converts a reference to an `Argument` into `Self`, if possible.
     fn from_argument(arg: &'a Argument<'a>) -> Option<Self>;
 }
 
+/// A fallible companion of [`FromArgument`], telling an argument of another kind apart from
+/// one whose value cannot be converted.
+///
+/// Every [`FromArgument`] type converts without failing; [`Parsed`] converts values with
+/// [`FromStr`].
+pub trait TryFromArgument<'a>: Sized {
+    /// Converts a reference to an `Argument` into `Self`, returning `Ok(None)` if it is not
+    /// of the right kind, and a [`ValueError`] if its value cannot be converted.
+    fn try_from_argument(arg: &'a Argument<'a>) -> Result<Option<Self>, ValueError>;
+}
+
+impl<'a, T: FromArgument<'a>> TryFromArgument<'a> for T {
+    fn try_from_argument(arg: &'a Argument<'a>) -> Result<Option<Self>, ValueError> {
+        Ok(T::from_argument(arg))
+    }
+}
+
+/// The value of an option or a positional, converted to type `T` with [`FromStr`].
+///
+/// # Examples
+///
+/// ```
+/// # use argsparse::{ArgDef, Args, Parsed};
+/// let args = Args::parse_all(&["--jobs", "8", "--port", "http"]).unwrap();
+///
+/// let jobs = args.try_find::<Parsed<u8>>(ArgDef::Long("jobs")).unwrap();
+/// assert_eq!(jobs, Some(Parsed(8)));
+///
+/// let err = args.try_find::<Parsed<u16>>(ArgDef::Long("port")).unwrap_err();
+/// assert_eq!((err.arg.as_str(), err.value.as_str(), err.expected), ("--port", "http", "u16"));
+/// ```
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Parsed<T>(pub T);
+
+impl<'a, T> TryFromArgument<'a> for Parsed<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    fn try_from_argument(arg: &'a Argument<'a>) -> Result<Option<Self>, ValueError> {
+        let (name, value) = match arg {
+            Argument::Option { name, value } => (display_name(name), value),
+            Argument::Positional { value } => ("positional".to_string(), value),
+            Argument::Flag { .. } => return Ok(None),
+        };
+        ValueError::parse(name, value).map(|value| Some(Parsed(value)))
+    }
+}
+
 
 impl<'a> FromArgument<'a> for PositionalArg<'a> {
     fn from_argument(arg: &'a Argument<'a>) -> Option<Self> {
         if let Argument::Positional { value } = arg {
-            Some(PositionalArg { value })
+            Some(PositionalArg { value: value.as_ref() })
         } else {
             None
         }
@@ -49,7 +245,7 @@ impl<'a> FromArgument<'a> for FlagArg<'a> {
 impl<'a> FromArgument<'a> for OptionArg<'a> {
     fn from_argument(arg: &'a Argument<'a>) -> Option<Self> {
         if let Argument::Option { name, value } = arg {
-            Some(OptionArg { name, value })
+            Some(OptionArg { name, value: value.as_ref() })
         } else {
             None
         }
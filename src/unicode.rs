@@ -0,0 +1,42 @@
+//! Minimal grapheme handling for short argument names.
+//!
+//! Short names are single `char`s, but what a user sees as one character may be several
+//! code points (`u` followed by a combining diaeresis, an emoji with a skin tone modifier,
+//! a flag made of two regional indicators, ...). Without pulling in the full Unicode
+//! segmentation tables, these helpers recognize the code points that extend a preceding
+//! character in the common cases, which is enough to reject such shorts with a clear error
+//! instead of silently splitting them into nonsense flags.
+
+/// Returns `true` if `c` attaches to the preceding character instead of standing alone.
+pub(crate) fn is_extending(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'     // Combining Diacritical Marks
+        | '\u{1AB0}'..='\u{1AFF}'   // Combining Diacritical Marks Extended
+        | '\u{1DC0}'..='\u{1DFF}'   // Combining Diacritical Marks Supplement
+        | '\u{20D0}'..='\u{20FF}'   // Combining Diacritical Marks for Symbols
+        | '\u{FE20}'..='\u{FE2F}'   // Combining Half Marks
+        | '\u{200C}'..='\u{200D}'   // Zero-width non-joiner and joiner
+        | '\u{FE00}'..='\u{FE0F}'   // Variation Selectors
+        | '\u{1F3FB}'..='\u{1F3FF}' // Emoji skin tone modifiers
+        | '\u{E0020}'..='\u{E007F}' // Tags
+        | '\u{E0100}'..='\u{E01EF}' // Variation Selectors Supplement
+    )
+}
+
+/// Returns `true` if `c` is a regional indicator, two of which form a flag.
+pub(crate) fn is_regional_indicator(c: char) -> bool {
+    matches!(c, '\u{1F1E6}'..='\u{1F1FF}')
+}
+
+/// Returns `true` if `c` can be used as a short argument name.
+///
+/// Dashes and `=` are reserved by the command-line syntax, and whitespace, control
+/// characters and extending code points cannot be typed as a standalone character.
+pub(crate) fn is_valid_short(c: char) -> bool {
+    !(c == '-' || c == '=' || c.is_whitespace() || c.is_control() || is_extending(c))
+}
+
+/// Returns `true` if `next` continues the grapheme started by `first`.
+pub(crate) fn continues_grapheme(first: char, next: char) -> bool {
+    is_extending(next) || is_regional_indicator(first) && is_regional_indicator(next)
+}
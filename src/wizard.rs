@@ -0,0 +1,177 @@
+use crate::args::Args;
+use crate::defs::ArgName;
+use crate::messages::Message;
+use crate::parser::ParserContext;
+use crate::shell_quote;
+use crate::spec::ArgSpec;
+use std::io::{self, BufRead, Write};
+
+impl ParserContext<'_> {
+    /// Walks the user through every visible definition with prompts read from `input` and
+    /// written to `output`, then returns the resulting arguments along with the equivalent
+    /// command line for the program `name`.
+    ///
+    /// Flags are asked as yes/no questions. Options and positionals show their help, possible
+    /// values and default; an empty answer skips an optional argument, leaving its
+    /// environment variable or default value to apply, while required ones are asked again.
+    /// Answers are checked like values on the command line, against the possible values,
+    /// range, validator and value provider, and asked again when rejected. A variadic
+    /// positional takes one value per line until an empty one.
+    ///
+    /// Only the definitions of this context are asked for, not those of nested contexts.
+    /// Fails with [`io::ErrorKind::UnexpectedEof`] if `input` ends before the wizard does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use argsparse::{ArgAction, ArgDef, ArgSpec, ParserContext, PositionalSpec};
+    /// let mut ctx = ParserContext::new();
+    /// ctx.register(ArgSpec::new(ArgDef::ShortAndLong { short: 'v', long: "verbose" })
+    ///     .action(ArgAction::Flag))
+    ///     .unwrap();
+    /// ctx.register(ArgSpec::new(ArgDef::Long("color"))
+    ///     .possible_values(&["auto", "never"])
+    ///     .default("auto"))
+    ///     .unwrap();
+    /// ctx.positional(PositionalSpec::new("FILE").required(true));
+    ///
+    /// let input = "y\nsometimes\nnever\n\nmy notes.txt\n";
+    /// let mut output = Vec::new();
+    /// let (args, command_line) = ctx.wizard("app", input.as_bytes(), &mut output).unwrap();
+    ///
+    /// assert_eq!(args.len(), 3);
+    /// assert_eq!(command_line, "app --verbose --color=never 'my notes.txt'");
+    ///
+    /// let output = String::from_utf8(output).unwrap();
+    /// assert!(output.contains("invalid value 'sometimes' for --color"));
+    /// assert!(output.contains("<FILE> is required."));
+    /// ```
+    pub fn wizard<R, W>(
+        &self,
+        name: &str,
+        mut input: R,
+        mut output: W,
+    ) -> io::Result<(Args<'static>, String)>
+    where
+        R: BufRead,
+        W: Write,
+    {
+        let mut argv = Vec::new();
+        let visible = |spec: &&ArgSpec| !spec.hidden && !spec.is_removed(self.version);
+        for spec in self.specs().iter().filter(visible) {
+            let (typed, name) = match (spec.def.long(), spec.def.short()) {
+                (Some(long), _) => (format!("--{}", long), ArgName::Long(long.into())),
+                (None, Some(short)) => (format!("-{}", short), ArgName::Short(short)),
+                (None, None) => continue,
+            };
+
+            if !spec.takes_value() {
+                let prompt = format!("{}{}? [y/N] ", typed, describe(spec.help));
+                loop {
+                    match ask(&mut input, &mut output, &prompt)?.to_ascii_lowercase().as_str() {
+                        "y" | "yes" => argv.push(typed),
+                        "" | "n" | "no" => {}
+                        _ => {
+                            writeln!(output, "Please answer y or n.")?;
+                            continue;
+                        }
+                    }
+                    break;
+                }
+                continue;
+            }
+
+            let prompt = format!(
+                "{} <{}>{}: ",
+                typed,
+                spec.value_name.unwrap_or("VALUE"),
+                self.annotations(spec)
+            );
+            loop {
+                let answer = ask(&mut input, &mut output, &prompt)?;
+                if answer.is_empty() {
+                    if spec.required && self.fallback(spec).is_none() {
+                        writeln!(output, "{} is required.", typed)?;
+                        continue;
+                    }
+                    break;
+                }
+                match self.check_value(&name, &answer) {
+                    Err(err) => writeln!(output, "{}", self.render_error(&err))?,
+                    // The `=` form keeps values starting with a dash attached
+                    Ok(()) if spec.def.long().is_some() => {
+                        argv.push(format!("{}={}", typed, answer));
+                        break;
+                    }
+                    Ok(()) => {
+                        argv.extend([typed, answer]);
+                        break;
+                    }
+                }
+            }
+        }
+
+        let mut operands = Vec::new();
+        for spec in self.positionals() {
+            let more = if spec.variadic { " (one per line, empty to finish)" } else { "" };
+            let prompt = format!("<{}>{}{}: ", spec.name, describe(spec.help), more);
+            let mut given = 0;
+            loop {
+                let answer = ask(&mut input, &mut output, &prompt)?;
+                if !answer.is_empty() {
+                    operands.push(answer);
+                    given += 1;
+                    if spec.variadic {
+                        continue;
+                    }
+                } else if spec.required && given == 0 {
+                    writeln!(output, "<{}> is required.", spec.name)?;
+                    continue;
+                }
+                break;
+            }
+        }
+        // Operands starting with a dash must not be taken for options
+        if operands.iter().any(|operand| operand.starts_with('-')) {
+            argv.push("--".to_string());
+        }
+        argv.extend(operands);
+
+        let args = Args::parse_from_with_context(&argv, self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        let command_line = std::iter::once(name).chain(argv.iter().map(String::as_str));
+        Ok((args, shell_quote::join(command_line)))
+    }
+
+    /// Returns the help, possible values and default of `spec`, as shown in prompts.
+    fn annotations(&self, spec: &ArgSpec) -> String {
+        let mut out = describe(spec.help);
+        if !spec.possible_values.is_empty() {
+            out.push(' ');
+            out.push_str(&self.text(&Message::PossibleValues(spec.possible_values)));
+        }
+        if let Some(default) = spec.default {
+            out.push(' ');
+            out.push_str(&self.text(&Message::Default(default)));
+        }
+        out
+    }
+}
+
+/// Formats a help text for a prompt.
+fn describe(help: Option<&str>) -> String {
+    help.map(|help| format!(" ({})", help)).unwrap_or_default()
+}
+
+/// Writes `prompt` and reads the answer, without surrounding whitespace.
+fn ask(input: &mut impl BufRead, output: &mut impl Write, prompt: &str) -> io::Result<String> {
+    write!(output, "{}", prompt)?;
+    output.flush()?;
+
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        let message = "the input ended before the wizard finished";
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, message));
+    }
+    Ok(line.trim().to_string())
+}
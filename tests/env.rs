@@ -0,0 +1,235 @@
+//! Behaviors that depend on the process environment.
+//!
+//! The environment is shared by every thread of the process, so these cases cannot run as
+//! doctests, which are merged into one binary and run in parallel. Each test here holds
+//! [`ENV`] while it changes variables, and puts them back when done.
+
+use argsparse::{
+    ArgAction, ArgDef, ArgSpec, Finding, Matches, ParserContext, Shell, SourceKind,
+    ValueSource, define_args,
+};
+use std::env;
+use std::ffi::OsString;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Serializes the tests that change the environment.
+static ENV: Mutex<()> = Mutex::new(());
+
+/// Variables set for the duration of a test, restored when dropped.
+struct EnvGuard {
+    saved: Vec<(&'static str, Option<OsString>)>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl EnvGuard {
+    fn new() -> Self {
+        let lock = ENV.lock().unwrap_or_else(PoisonError::into_inner);
+        EnvGuard { saved: Vec::new(), _lock: lock }
+    }
+
+    /// Sets `name` to `value`, or removes it if `value` is `None`.
+    fn set(&mut self, name: &'static str, value: Option<&str>) -> &mut Self {
+        if !self.saved.iter().any(|(saved, _)| *saved == name) {
+            self.saved.push((name, env::var_os(name)));
+        }
+        // SAFETY: the tests changing the environment hold `ENV`, and no other thread of
+        // this binary reads it meanwhile
+        unsafe {
+            match value {
+                Some(value) => env::set_var(name, value),
+                None => env::remove_var(name),
+            }
+        }
+        self
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        for (name, value) in self.saved.drain(..).rev() {
+            // SAFETY: `ENV` is still held
+            unsafe {
+                match value {
+                    Some(value) => env::set_var(name, value),
+                    None => env::remove_var(name),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn value_of_falls_back_to_env_then_default() {
+    let mut env = EnvGuard::new();
+    env.set("ARGSPARSE_TEST_PORT", None);
+
+    let port = ArgDef::ShortAndLong { short: 'p', long: "port" };
+    let mut ctx = ParserContext::new();
+    ctx.register(ArgSpec::new(port).env("ARGSPARSE_TEST_PORT").default("8080")).unwrap();
+
+    let matches = Matches::parse(&[], &ctx).unwrap();
+    assert_eq!(matches.value_of(port).as_deref(), Some("8080"));
+
+    env.set("ARGSPARSE_TEST_PORT", Some("3000"));
+    assert_eq!(matches.value_of(port).as_deref(), Some("3000"));
+
+    let matches = Matches::parse(&["--port", "80"], &ctx).unwrap();
+    assert_eq!(matches.value_of(port).as_deref(), Some("80"));
+}
+
+#[test]
+fn value_with_source_names_the_env_var() {
+    let mut env = EnvGuard::new();
+    env.set("ARGSPARSE_TEST_SOURCE_PORT", None);
+
+    let port = ArgDef::ShortAndLong { short: 'p', long: "port" };
+    let mut ctx = ParserContext::new();
+    ctx.register(ArgSpec::new(port).env("ARGSPARSE_TEST_SOURCE_PORT").default("8080")).unwrap();
+
+    let matches = Matches::parse(&[], &ctx).unwrap();
+    assert_eq!(matches.value_with_source(port), Some(("8080".into(), ValueSource::Default)));
+
+    env.set("ARGSPARSE_TEST_SOURCE_PORT", Some("3000"));
+    let source = ValueSource::Env("ARGSPARSE_TEST_SOURCE_PORT");
+    assert_eq!(matches.value_with_source(port), Some(("3000".into(), source)));
+}
+
+#[test]
+fn required_argument_is_satisfied_by_env() {
+    let mut env = EnvGuard::new();
+    env.set("ARGSPARSE_TEST_TOKEN", None);
+
+    let token = ArgDef::Long("token");
+    let mut ctx = ParserContext::new();
+    ctx.register(ArgSpec::new(token).required(true).env("ARGSPARSE_TEST_TOKEN")).unwrap();
+    assert!(Matches::parse(&[], &ctx).is_err());
+
+    env.set("ARGSPARSE_TEST_TOKEN", Some("secret"));
+    let matches = Matches::parse(&[], &ctx).unwrap();
+    assert_eq!(matches.value_of(token).as_deref(), Some("secret"));
+}
+
+#[test]
+fn context_precedence_lets_env_win() {
+    let mut env = EnvGuard::new();
+    env.set("ARGSPARSE_TEST_PRECEDENCE_PORT", Some("3000"));
+
+    let port = ArgDef::Long("port");
+    let mut ctx = ParserContext::new();
+    ctx.register(ArgSpec::new(port).env("ARGSPARSE_TEST_PRECEDENCE_PORT")).unwrap();
+    ctx.precedence(&[SourceKind::Env, SourceKind::CommandLine]);
+
+    let matches = Matches::parse(&["--port", "80"], &ctx).unwrap();
+    let (value, source) = matches.value_with_source(port).unwrap();
+    assert_eq!(value, "3000");
+    assert_eq!(source, ValueSource::Env("ARGSPARSE_TEST_PRECEDENCE_PORT"));
+}
+
+#[test]
+fn spec_precedence_lets_env_win() {
+    let mut env = EnvGuard::new();
+    env.set("ARGSPARSE_TEST_LOG_LEVEL", None);
+
+    let level = ArgDef::Long("log-level");
+    let mut ctx = ParserContext::new();
+    ctx.register(ArgSpec::new(level)
+        .env("ARGSPARSE_TEST_LOG_LEVEL")
+        .precedence(&[SourceKind::Env, SourceKind::CommandLine, SourceKind::Default]))
+        .unwrap();
+
+    let matches = Matches::parse(&["--log-level", "debug"], &ctx).unwrap();
+    assert_eq!(matches.value_of(level).as_deref(), Some("debug"));
+
+    env.set("ARGSPARSE_TEST_LOG_LEVEL", Some("warn"));
+    assert_eq!(matches.value_of(level).as_deref(), Some("warn"));
+}
+
+#[test]
+fn audit_reports_values_superseded_by_env() {
+    let mut env = EnvGuard::new();
+    env.set("ARGSPARSE_TEST_AUDIT_COLOR", Some("never"));
+
+    let mut ctx = ParserContext::new();
+    ctx.option('j', "jobs");
+    ctx.register(ArgSpec::new(ArgDef::Long("fast")).action(ArgAction::Flag).deprecated("-j"))
+        .unwrap();
+    ctx.register(ArgSpec::new(ArgDef::Long("color")).env("ARGSPARSE_TEST_AUDIT_COLOR")).unwrap();
+    ctx.precedence(&[SourceKind::Env, SourceKind::CommandLine]);
+
+    let args = ["-j", "2", "--fast", "--color", "always", "-j", "8"];
+    let matches = Matches::parse(&args, &ctx).unwrap();
+
+    let findings = matches.audit();
+    assert_eq!(findings[..2], [
+        Finding::Overridden { index: 0, by: 3 },
+        Finding::Deprecated { index: 1 },
+    ]);
+    assert!(matches!(findings[2], Finding::Superseded {
+        source: ValueSource::CommandLine,
+        by: ValueSource::Env("ARGSPARSE_TEST_AUDIT_COLOR"),
+        ..
+    }));
+}
+
+define_args! {
+    struct Server {
+        port: u16 = required("-p, --port <PORT>").env("ARGSPARSE_TEST_MACRO_PORT").default("8080"),
+    }
+}
+
+#[test]
+fn define_args_reads_env_before_default() {
+    let mut env = EnvGuard::new();
+    env.set("ARGSPARSE_TEST_MACRO_PORT", None);
+
+    let ctx = Server::context();
+    let server = Server::from_matches(&Matches::parse(&[], &ctx).unwrap()).unwrap();
+    assert_eq!(server.port, 8080);
+
+    env.set("ARGSPARSE_TEST_MACRO_PORT", Some("3000"));
+    let server = Server::from_matches(&Matches::parse(&[], &ctx).unwrap()).unwrap();
+    assert_eq!(server.port, 3000);
+
+    let server = Server::from_matches(&Matches::parse(&["-p", "80"], &ctx).unwrap()).unwrap();
+    assert_eq!(server.port, 80);
+}
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn config_path_follows_xdg_config_home() {
+    let mut env = EnvGuard::new();
+    env.set("XDG_CONFIG_HOME", Some("/home/me/.config"));
+
+    let path = ParserContext::config_path("myapp", "config.ini").unwrap();
+    assert_eq!(path, Path::new("/home/me/.config/myapp/config.ini"));
+
+    // A relative directory is ignored in favor of the home directory
+    env.set("XDG_CONFIG_HOME", Some("relative")).set("HOME", Some("/home/me"));
+    let path = ParserContext::config_path("myapp", "config.ini").unwrap();
+    assert_eq!(path, Path::new("/home/me/.config/myapp/config.ini"));
+
+    env.set("XDG_CONFIG_HOME", None).set("HOME", None);
+    assert_eq!(ParserContext::config_path("myapp", "config.ini"), None);
+}
+
+#[test]
+fn shell_install_writes_under_home() {
+    let home = env::temp_dir().join("argsparse-test-install");
+    let mut env = EnvGuard::new();
+    env.set("HOME", home.to_str()).set("XDG_CONFIG_HOME", None);
+
+    let mut ctx = ParserContext::new();
+    ctx.flag('v', "verbose");
+
+    let path = Shell::Fish.install("myapp", &ctx, true).unwrap();
+    assert_eq!(path, home.join(".config/fish/completions/myapp.fish"));
+    assert!(!path.exists());
+
+    Shell::Fish.install("myapp", &ctx, false).unwrap();
+    assert!(std::fs::read_to_string(&path).unwrap().contains("complete -c myapp"));
+    std::fs::remove_dir_all(&home).unwrap();
+
+    env.set("HOME", None);
+    assert!(Shell::Fish.install("myapp", &ctx, true).is_err());
+}